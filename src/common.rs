@@ -1,4 +1,8 @@
-use super::{EquipSlot, Equipable};
+use super::{
+    raws::{spawn_item, RAWS},
+    CollectItem, EquipSlot, Equipable, Equipment, Inventory, InventoryCapacity, Name,
+};
+use bracket_lib::prelude::Point;
 use specs::prelude::*;
 
 /*
@@ -20,3 +24,56 @@ pub fn is_weapon(ecs: &World, equip: Entity) -> bool {
 
     false
 }
+
+/// An item (by name) in the player's inventory, preferring the ones that aren't equipped.
+fn find_item(ecs: &World, name: &str) -> Option<Entity> {
+    let player = ecs.fetch::<Entity>();
+    let entities = ecs.entities();
+    let inventory = ecs.read_storage::<Inventory>();
+    let names = ecs.read_storage::<Name>();
+    let equipment = ecs.read_storage::<Equipment>();
+
+    let mut items = (&entities, &inventory, &names)
+        .join()
+        .filter(|(_ent, inv, item_name)| inv.owner == *player && item_name.name == name)
+        .map(|(ent, _inv, _name)| ent)
+        .collect::<Vec<_>>();
+    items.sort_by_key(|ent| equipment.get(*ent).is_some());
+    items.first().copied()
+}
+
+pub fn has_item(ecs: &World, name: &str) -> bool {
+    find_item(ecs, name).is_some()
+}
+
+/// Gives the player an item, which is left at their feet if there's no room for it.
+pub fn give_item(ecs: &World, name: &str) -> Option<Entity> {
+    let player = *ecs.fetch::<Entity>();
+    let ppos = *ecs.fetch::<Point>();
+    let item = {
+        let raws = &RAWS.lock().unwrap();
+        spawn_item(name, Some(ppos), ecs.create_entity_unchecked(), raws)
+    };
+    if let Some(item) = item {
+        let mut collect = ecs.write_storage::<CollectItem>();
+        CollectItem::add_collect(&mut collect, item, player);
+    }
+    item
+}
+
+/// Takes an item from the player's inventory. Returns false if there's none.
+pub fn take_item(ecs: &World, name: &str) -> bool {
+    let item = match find_item(ecs, name) {
+        Some(item) => item,
+        None => return false,
+    };
+    let player = ecs.fetch::<Entity>();
+    if let Some(capacity) = ecs.write_storage::<InventoryCapacity>().get_mut(*player) {
+        capacity.curr = capacity.curr.saturating_sub(1);
+    }
+    ecs.write_storage::<Inventory>().remove(item);
+    ecs.entities()
+        .delete(item)
+        .expect("Unable to delete the item");
+    true
+}
@@ -180,9 +180,16 @@ pub struct MeleeAttack {
     pub target: Entity,
 }
 
+/// What a missile attack is aimed at: a mob, or a tile (explosive weapons only).
+#[derive(Debug, Clone, Copy)]
+pub enum MissileTarget {
+    Mob(Entity),
+    Tile(Point),
+}
+
 #[derive(Component)]
 pub struct MissileAttack {
-    pub target: Entity,
+    pub target: MissileTarget,
 }
 
 #[derive(Debug)]
@@ -203,6 +210,8 @@ pub enum AmmoType {
     Arrow,
     _32,
     _9mm,
+    Flare,
+    Grenade,
 }
 
 #[derive(Component)]
@@ -227,6 +236,16 @@ pub struct MissileWeapon {
     pub ammo: Ammunition,
 }
 
+#[derive(Component)]
+// Weapons that set the terrain where they hit on fire.
+pub struct Incendiary {}
+
+#[derive(Component)]
+// Weapons that blow up walls (and set things on fire) around where they hit.
+pub struct Explosive {
+    pub radius: i32,
+}
+
 #[derive(Component)]
 pub struct ActiveWeapon {}
 
@@ -347,6 +366,7 @@ pub struct Remains {} // The remains of a dead mob.
 pub struct Container {
     pub tiers: Vec<u8>,
     pub max_items: u8,
+    pub loot: Option<String>,
 }
 
 #[derive(Component)]
@@ -354,3 +374,173 @@ pub struct Contained {
     // Similar to Inventory, but specifically for containers.
     pub container: Entity,
 }
+
+#[derive(Component, Debug)]
+pub struct AutoExplore {
+    // HP at the last explored step; losing any of it stops the exploration.
+    pub hp: i32,
+}
+
+#[derive(Component, Debug, Copy, Clone, PartialEq)]
+// A lock on a container. Locked doors keep theirs in the map (see Map::locks).
+pub struct Lock {
+    pub id: u32, // Unique in the whole game, so old keys can't open new locks.
+    pub difficulty: i32,
+}
+
+#[derive(Component, Debug)]
+pub struct Key {
+    pub lock_id: u32,
+}
+
+#[derive(Component, Debug)]
+pub struct Lockpick {
+    pub bonus: i32,
+}
+
+#[derive(Component, Debug)]
+// Mobs with this will go check where the noise came from, if they can't see the player.
+pub struct HeardNoise {
+    pub pos: Position,
+}
+
+#[derive(Debug, Clone)]
+pub enum FurnitureUse {
+    Read { lore: Vec<String>, reveal_map: bool }, // Terminals, notes...
+    Rest { heal: i32, turns: i32 },               // Beds; heals `heal` HP per turn.
+    Push,                                         // Crates.
+    Search { loot: String },                      // Bookshelves; rolled once.
+}
+
+#[derive(Component, Debug)]
+// Furniture the player can use with the contextual action.
+pub struct Interactive {
+    pub action: FurnitureUse,
+    pub used: bool, // Maps are only revealed and bookshelves only searched once.
+}
+
+#[derive(Component, Debug)]
+pub struct Resting {
+    pub heal: i32,
+    pub turns: i32,
+    pub hp: i32, // HP at the last turn; losing any of it wakes the player up.
+}
+
+#[derive(Component, Debug)]
+// Turns an entity still needs to get out of slow terrain (e.g. water) before moving again.
+pub struct Slowed {
+    pub turns: i32,
+}
+
+#[derive(Component, Debug)]
+// Caught fire: keeps burning for a few turns after leaving the flames, unless it gets into water.
+pub struct Burning {
+    pub turns: i32,
+    pub by_player: bool, // The fire was lit by the player.
+}
+
+#[derive(Component)]
+// The killing blow came from the player (directly or by their fire), so it counts for quests.
+pub struct SlainByPlayer {}
+
+#[derive(Component, Debug, Copy, Clone)]
+// Lights up the tiles around it (see lighting.rs); items light up their user when equipped.
+pub struct LightSource {
+    pub radius: i32,
+    pub color: RGB,
+}
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum AwarenessState {
+    Asleep,     // Only noise wakes it up.
+    Wandering,  // Walks around, not looking for anything in particular.
+    Suspicious, // Heard or saw something (see HeardNoise) and goes check it.
+    Hunting,    // Noticed the player.
+}
+
+#[derive(Component, Debug)]
+// How much a mob knows about the player (see ai.rs).
+pub struct Awareness {
+    pub state: AwarenessState,
+}
+
+impl Awareness {
+    /// Makes the mob hunt the player. Returns true if it hadn't noticed them yet (a Suspicious mob
+    /// is already on guard).
+    pub fn alert(&mut self) -> bool {
+        let unaware = matches!(
+            self.state,
+            AwarenessState::Asleep | AwarenessState::Wandering
+        );
+        self.state = AwarenessState::Hunting;
+        unaware
+    }
+}
+
+#[derive(Component, Debug)]
+// Noise made by an entity on this turn; it travels over the map (see noise.rs).
+pub struct EmitNoise {
+    pub volume: i32,
+}
+
+impl EmitNoise {
+    /// Only the loudest noise made by an entity in a turn is heard.
+    pub fn add_noise(noise_store: &mut WriteStorage<EmitNoise>, source: Entity, volume: i32) {
+        if let Some(noise) = noise_store.get_mut(source) {
+            noise.volume = i32::max(noise.volume, volume);
+        } else {
+            noise_store
+                .insert(source, EmitNoise { volume })
+                .expect("Unable to insert noise");
+        }
+    }
+}
+
+#[derive(Component, Debug)]
+// Who an entity sides with; how factions react to each other is defined in the raws.
+pub struct Faction {
+    pub name: String,
+}
+
+#[derive(Component, Debug)]
+// Entities that attacked this one, which it now treats as hostile whatever their faction.
+pub struct Grudge {
+    pub against: Vec<Entity>,
+}
+
+impl Grudge {
+    pub fn add_grudge(grudge_store: &mut WriteStorage<Grudge>, victim: Entity, attacker: Entity) {
+        if let Some(grudge) = grudge_store.get_mut(victim) {
+            if !grudge.against.contains(&attacker) {
+                grudge.against.push(attacker);
+            }
+        } else {
+            grudge_store
+                .insert(
+                    victim,
+                    Grudge {
+                        against: vec![attacker],
+                    },
+                )
+                .expect("Unable to insert grudge");
+        }
+    }
+}
+
+#[derive(Component, Debug)]
+// NPCs the player can talk to, through the contextual action (see dialogues.ron).
+pub struct Talker {
+    pub dialogue: String,
+}
+
+#[derive(Component, Debug)]
+// Money. The player keeps all of it in a single pile (see item_collect.rs).
+pub struct Currency {
+    pub amount: i32,
+}
+
+#[derive(Component, Debug)]
+// NPCs that buy and sell items; what they sell is rolled from a loot table when they're spawned.
+pub struct Trader {
+    pub stock: String,
+}
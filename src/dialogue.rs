@@ -0,0 +1,162 @@
+use super::{
+    common::{give_item, has_item, take_item},
+    log::Log,
+    map_gen::Map,
+    quests::{complete_quest, quest_ready, reaction_to_player, start_quest, QuestLog, QuestStatus},
+    raws::{DialogueChoice, DialogueCondition, DialogueEffect, Reaction, DIALOGUES},
+    Awareness, AwarenessState, Name, RunState, Talker, Trader,
+};
+use crate::utils::colors::*;
+use specs::prelude::*;
+
+/*
+ *
+ * dialogue.rs
+ * -----------
+ * Conversations with NPCs: which choices the player has and what picking them does.
+ * The dialogue trees themselves are defined in dialogues.ron.
+ *
+ */
+
+/// Starts talking to an NPC, if it's willing to.
+pub fn talk(ecs: &World, npc: Entity) -> RunState {
+    let name = match ecs.read_storage::<Name>().get(npc) {
+        Some(name) => name.name.clone(),
+        None => "It".to_string(),
+    };
+    let dialogue = match ecs.read_storage::<Talker>().get(npc) {
+        Some(talker) => talker.dialogue.clone(),
+        None => return RunState::Waiting,
+    };
+
+    let asleep = ecs
+        .read_storage::<Awareness>()
+        .get(npc)
+        .is_some_and(|aware| aware.state == AwarenessState::Asleep);
+    let hostile = reaction_to_player(ecs, npc) == Reaction::Hostile;
+
+    let mut log = ecs.fetch_mut::<Log>();
+    if asleep {
+        log.add(format!("{} is asleep.", name), color("BrightWhite", 1.0));
+        return RunState::Waiting;
+    }
+    if hostile {
+        log.add(
+            format!("{} doesn't want to talk.", name),
+            color("BrightWhite", 1.0),
+        );
+        return RunState::Waiting;
+    }
+
+    match DIALOGUES.lock().unwrap().dialogue_idx(&dialogue) {
+        Some(dialogue) => RunState::Dialogue {
+            npc,
+            dialogue,
+            node: 0,
+        },
+        None => RunState::Waiting,
+    }
+}
+
+fn check(ecs: &World, condition: &DialogueCondition) -> bool {
+    let status = |quest: &str| ecs.fetch::<QuestLog>().status(quest);
+    match condition {
+        DialogueCondition::HasItem(item) => has_item(ecs, item),
+        DialogueCondition::Flag(flag) => ecs.fetch::<QuestLog>().flags.contains(flag),
+        DialogueCondition::NotFlag(flag) => !ecs.fetch::<QuestLog>().flags.contains(flag),
+        DialogueCondition::QuestNotStarted(quest) => status(quest).is_none(),
+        DialogueCondition::QuestActive(quest) => status(quest) == Some(QuestStatus::Active),
+        DialogueCondition::QuestReady(quest) => quest_ready(ecs, quest),
+        DialogueCondition::QuestDone(quest) => status(quest) == Some(QuestStatus::Done),
+    }
+}
+
+/// The choices of a node the player can pick right now.
+pub fn available_choices(ecs: &World, dialogue: usize, node: usize) -> Vec<DialogueChoice> {
+    let choices = DIALOGUES.lock().unwrap().get_dialogue(dialogue).nodes[node]
+        .choices
+        .clone();
+    choices
+        .into_iter()
+        .filter(|choice| choice.conditions.iter().all(|c| check(ecs, c)))
+        .collect()
+}
+
+/// Applies an effect of a choice. Returns a new state if it takes the player out of the
+/// conversation.
+fn apply(ecs: &World, npc: Entity, effect: &DialogueEffect) -> Option<RunState> {
+    match effect {
+        DialogueEffect::GiveItem(item) => {
+            give_item(ecs, item);
+            ecs.fetch_mut::<Log>()
+                .add(format!("You receive: {}.", item), color("BrightWhite", 1.0));
+        }
+        DialogueEffect::TakeItem(item) => {
+            if take_item(ecs, item) {
+                ecs.fetch_mut::<Log>().add(
+                    format!("You hand over: {}.", item),
+                    color("BrightWhite", 1.0),
+                );
+            }
+        }
+        DialogueEffect::SetFlag(flag) => {
+            ecs.fetch_mut::<QuestLog>().flags.insert(flag.clone());
+        }
+        DialogueEffect::RevealMap => {
+            for tile in ecs.fetch_mut::<Map>().tiles.iter_mut() {
+                tile.revealed = true;
+            }
+            ecs.fetch_mut::<Log>().add(
+                "The area is marked on your map.".to_string(),
+                color("BrightWhite", 1.0),
+            );
+        }
+        DialogueEffect::StartQuest(quest) => start_quest(ecs, quest),
+        DialogueEffect::CompleteQuest(quest) => complete_quest(ecs, quest),
+        DialogueEffect::Standing(faction, reaction) => {
+            ecs.fetch_mut::<QuestLog>()
+                .reputation
+                .insert(faction.clone(), *reaction);
+            ecs.fetch_mut::<Log>().add(
+                format!(
+                    "The {} regard you as {} now.",
+                    faction,
+                    format!("{:?}", reaction).to_lowercase()
+                ),
+                color("BrightYellow", 1.0),
+            );
+        }
+        // Only traders open the trading screen.
+        DialogueEffect::Trade => {
+            if ecs.read_storage::<Trader>().get(npc).is_some() {
+                return Some(RunState::Trade {
+                    trader: npc,
+                    selling: false,
+                });
+            }
+        }
+    }
+    None
+}
+
+/// Picks a choice, applying its effects. Returns where the player goes next: another node of the
+/// conversation, the trading screen or back to the game.
+pub fn choose(ecs: &World, npc: Entity, dialogue: usize, choice: &DialogueChoice) -> RunState {
+    let mut next = match &choice.next {
+        Some(id) => match DIALOGUES.lock().unwrap().node_idx(dialogue, id) {
+            Some(node) => RunState::Dialogue {
+                npc,
+                dialogue,
+                node,
+            },
+            None => RunState::PlayerTurn,
+        },
+        None => RunState::PlayerTurn,
+    };
+    for effect in choice.effects.iter() {
+        if let Some(state) = apply(ecs, npc, effect) {
+            next = state;
+        }
+    }
+    next
+}
@@ -0,0 +1,220 @@
+use super::{
+    map_gen::Map, player::visible_targets, BaseStats, Fov, FurnitureUse, Interactive, Name,
+    Position, Resting, RunState,
+};
+use crate::log::Log;
+use crate::raws::{roll_loot, spawn_item, RAWS};
+use crate::utils::colors::*;
+use bracket_lib::prelude::*;
+use specs::prelude::*;
+
+/*
+ *
+ * furniture.rs
+ * ------------
+ * What happens when the player uses a piece of furniture through the contextual action.
+ * The kind of use of each furniture is defined in its raw (see raws.ron).
+ *
+ */
+
+pub fn use_furniture(ecs: &World, furniture: Entity, pt: Point, map: &mut Map) -> RunState {
+    let name = match ecs.read_storage::<Name>().get(furniture) {
+        Some(name) => name.name.to_lowercase(),
+        None => "furniture".to_string(),
+    };
+    let action = match ecs.read_storage::<Interactive>().get(furniture) {
+        Some(interactive) => interactive.action.clone(),
+        None => return RunState::Waiting,
+    };
+
+    match action {
+        FurnitureUse::Read { lore, reveal_map } => read(ecs, furniture, &lore, reveal_map, map),
+        FurnitureUse::Rest { heal, turns } => start_resting(ecs, &name, heal, turns),
+        FurnitureUse::Push => push(ecs, furniture, &name, pt, map),
+        FurnitureUse::Search { loot } => search(ecs, furniture, &name, &loot, map),
+    }
+}
+
+fn set_used(ecs: &World, furniture: Entity) -> bool {
+    let mut interactives = ecs.write_storage::<Interactive>();
+    let interactive = interactives.get_mut(furniture).unwrap();
+    let used = interactive.used;
+    interactive.used = true;
+    used
+}
+
+/// Shows one of the lore texts and, the first time, reveals the whole map if it's able to.
+fn read(
+    ecs: &World,
+    furniture: Entity,
+    lore: &[String],
+    reveal_map: bool,
+    map: &mut Map,
+) -> RunState {
+    let mut log = ecs.fetch_mut::<Log>();
+    if !lore.is_empty() {
+        let mut rng = ecs.write_resource::<RandomNumberGenerator>();
+        let text = rng.random_slice_entry(lore).unwrap();
+        log.add(text.to_string(), color("BrightGreen", 1.0));
+    }
+
+    if reveal_map && !set_used(ecs, furniture) {
+        for tile in map.tiles.iter_mut() {
+            tile.revealed = true;
+        }
+        log.add(
+            "A map of the area is shown on the screen.".to_string(),
+            color("BrightWhite", 1.0),
+        );
+    }
+    RunState::PlayerTurn
+}
+
+/// Pushes the furniture one tile away from the player, who takes its place.
+fn push(ecs: &World, furniture: Entity, name: &str, pt: Point, map: &mut Map) -> RunState {
+    let ppos = *ecs.fetch::<Point>();
+    let dest = Point::new(pt.x * 2 - ppos.x, pt.y * 2 - ppos.y);
+    let mut log = ecs.fetch_mut::<Log>();
+
+    if pt == ppos
+        || !map.in_map_bounds(dest)
+        || map.tiles[map.idx_pt(dest)].block
+        || map.entities[map.idx_pt(dest)].is_some()
+    {
+        log.add(
+            format!("The {} won't budge.", name),
+            color("BrightWhite", 1.0),
+        );
+        return RunState::Waiting;
+    }
+
+    let mut positions = ecs.write_storage::<Position>();
+    if let Some(pos) = positions.get_mut(furniture) {
+        *pos = dest;
+    }
+    map.clear_blocker(pt.x, pt.y);
+    map.add_blocker(dest.x, dest.y);
+
+    // The player follows it.
+    let player = ecs.fetch::<Entity>();
+    if let Some(pos) = positions.get_mut(*player) {
+        *pos = pt;
+    }
+    let mut player_pos = ecs.write_resource::<Point>();
+    *player_pos = pt;
+    if let Some(fov) = ecs.write_storage::<Fov>().get_mut(*player) {
+        fov.dirty = true;
+    }
+
+    log.add(format!("You push the {}.", name), color("BrightWhite", 1.0));
+    RunState::PlayerTurn
+}
+
+/// Leaves what's found (once) at the player's feet.
+fn search(ecs: &World, furniture: Entity, name: &str, loot: &str, map: &Map) -> RunState {
+    if set_used(ecs, furniture) {
+        let mut log = ecs.fetch_mut::<Log>();
+        log.add(
+            format!("You find nothing else in the {}.", name),
+            color("BrightWhite", 1.0),
+        );
+        return RunState::Waiting;
+    }
+
+    let ppos = *ecs.fetch::<Point>();
+    let raws = &RAWS.lock().unwrap();
+    let items = {
+        let mut rng = ecs.write_resource::<RandomNumberGenerator>();
+        roll_loot(loot, map.depth, raws, &mut rng)
+    };
+    for item in items.iter() {
+        spawn_item(item, Some(ppos), ecs.create_entity_unchecked(), raws);
+    }
+
+    let mut log = ecs.fetch_mut::<Log>();
+    let msg = if items.is_empty() {
+        format!("You search the {}, but find nothing.", name)
+    } else {
+        format!("You search the {} and find: {}.", name, items.join(", "))
+    };
+    log.add(msg, color("BrightWhite", 1.0));
+    RunState::PlayerTurn
+}
+
+fn start_resting(ecs: &World, name: &str, heal: i32, turns: i32) -> RunState {
+    let player = ecs.fetch::<Entity>();
+    let mut log = ecs.fetch_mut::<Log>();
+    let stats = ecs.read_storage::<BaseStats>();
+    let health = &stats.get(*player).unwrap().health;
+
+    // Hostiles in sight are checked on the first turn of rest (see rest()).
+    if health.hp >= health.max_hp {
+        log.add("You're not tired.".to_string(), color("BrightWhite", 1.0));
+        return RunState::Waiting;
+    }
+
+    ecs.write_storage::<Resting>()
+        .insert(
+            *player,
+            Resting {
+                heal,
+                turns,
+                hp: health.hp,
+            },
+        )
+        .expect("Resting insertion failed");
+    log.add(
+        format!("You lie down on the {} to rest.", name),
+        color("BrightWhite", 1.0),
+    );
+    RunState::Resting
+}
+
+/// Checks if the player is currently resting.
+pub fn is_resting(ecs: &World) -> bool {
+    let player = ecs.fetch::<Entity>();
+    ecs.read_storage::<Resting>().get(*player).is_some()
+}
+
+/// Stops resting, telling the player why.
+pub fn stop_resting(ecs: &World, msg: &str) -> RunState {
+    let player = ecs.fetch::<Entity>();
+    let mut log = ecs.fetch_mut::<Log>();
+    ecs.write_storage::<Resting>().remove(*player);
+    log.add(msg.to_string(), color("BrightWhite", 1.0));
+    RunState::Waiting
+}
+
+/// Does a single turn of resting: heals the player, unless something interrupts it.
+pub fn rest(ecs: &mut World) -> RunState {
+    let player = *ecs.fetch::<Entity>();
+
+    if !visible_targets(ecs, false).is_empty() {
+        return stop_resting(ecs, "You spot a hostile and get up.");
+    }
+
+    let done = {
+        let mut stats = ecs.write_storage::<BaseStats>();
+        let mut resting = ecs.write_storage::<Resting>();
+        let health = &mut stats.get_mut(player).unwrap().health;
+        let rest = resting.get_mut(player).unwrap();
+        if health.hp < rest.hp {
+            None
+        } else {
+            health.hp = i32::min(health.hp + rest.heal, health.max_hp);
+            rest.hp = health.hp;
+            rest.turns -= 1;
+            Some(rest.turns <= 0 || health.hp >= health.max_hp)
+        }
+    };
+
+    match done {
+        None => stop_resting(ecs, "You've been hurt!"),
+        Some(true) => {
+            // Still let the mobs act on this turn.
+            stop_resting(ecs, "You feel rested.");
+            RunState::PlayerTurn
+        }
+        Some(false) => RunState::PlayerTurn,
+    }
+}
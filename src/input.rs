@@ -1,6 +1,8 @@
 use super::{
     map_gen::Map,
     player::*,
+    raws::{get_action, Action},
+    renderer::in_viewport,
     state::{RunState, State},
     utils::directions::*,
 };
@@ -11,71 +13,99 @@ use bracket_lib::prelude::*;
  * inputs.rs
  * ---------
  * Contains all the valid inputs for a given game state.
+ * Keys are mapped to actions in keybindings.ron (see raws/).
  *
  */
 
+/// Returns the direction of a movement action.
+fn action_dir(action: &Action) -> Option<Direction> {
+    match action {
+        Action::MoveEast => Some(EAST),
+        Action::MoveWest => Some(WEST),
+        Action::MoveNorth => Some(NORTH),
+        Action::MoveSouth => Some(SOUTH),
+        Action::MoveNortheast => Some(NORTHEAST),
+        Action::MoveNorthwest => Some(NORTHWEST),
+        Action::MoveSoutheast => Some(SOUTHEAST),
+        Action::MoveSouthwest => Some(SOUTHWEST),
+        _ => None,
+    }
+}
+
 /// Valid inputs while playing normally.
 pub fn player_input(gs: &mut State, term: &mut BTerm) -> RunState {
-    //let input_mutex = &INPUT.lock();
-    //let lshift_down = input_mutex.is_key_pressed(VirtualKeyCode::LShift);
-    match term.key {
+    match get_action(term) {
         None => return RunState::Waiting,
-        Some(key) => match key {
-            // Move East (E).
-            VirtualKeyCode::L | VirtualKeyCode::Numpad6 | VirtualKeyCode::Right => {
-                move_player(EAST, &mut gs.ecs)
-            }
-            // Move West (W).
-            VirtualKeyCode::H | VirtualKeyCode::Numpad4 | VirtualKeyCode::Left => {
-                move_player(WEST, &mut gs.ecs)
+        Some(action) => match action {
+            // Use missile weapon; explosives are aimed at a tile.
+            Action::Fire => {
+                if explosive_readied(&gs.ecs) {
+                    return choose_tile(&mut gs.ecs);
+                }
+                return choose_target(&mut gs.ecs, false);
             }
-            // Move North (N).
-            VirtualKeyCode::K | VirtualKeyCode::Numpad8 | VirtualKeyCode::Up => {
-                move_player(NORTH, &mut gs.ecs)
-            }
-            // Move South (S).
-            VirtualKeyCode::J | VirtualKeyCode::Numpad2 | VirtualKeyCode::Down => {
-                move_player(SOUTH, &mut gs.ecs)
-            }
-            // Move Northeast (NE).
-            VirtualKeyCode::U | VirtualKeyCode::Numpad9 => move_player(NORTHEAST, &mut gs.ecs),
-            // Move Northwest (NW).
-            VirtualKeyCode::Y | VirtualKeyCode::Numpad7 => move_player(NORTHWEST, &mut gs.ecs),
-            // Move Southeast (SE).
-            VirtualKeyCode::N | VirtualKeyCode::Numpad3 => move_player(SOUTHEAST, &mut gs.ecs),
-            // Move Southwest (SW).
-            VirtualKeyCode::B | VirtualKeyCode::Numpad1 => move_player(SOUTHWEST, &mut gs.ecs),
-
-            // Use missile weapon.
-            VirtualKeyCode::F => return choose_target(&mut gs.ecs, false),
 
             // Switch readied weapon.
-            VirtualKeyCode::Z => return switch_weapon(&mut gs.ecs),
+            Action::SwitchWeapon => return switch_weapon(&mut gs.ecs),
 
             // Pickup item.
-            VirtualKeyCode::G => return collect_item(&mut gs.ecs),
+            Action::Pickup => return collect_item(&mut gs.ecs),
 
             // Access inventory.
-            VirtualKeyCode::I => {
+            Action::Inventory => {
                 term.key = None;
                 return RunState::Inventory;
             }
 
             // Equipment management.
-            VirtualKeyCode::E => {
+            Action::Equipment => {
                 term.key = None;
                 return RunState::Equipment;
             }
 
             // Reload ranged weapon.
-            VirtualKeyCode::R => return reload_weapon(&mut gs.ecs),
+            Action::Reload => return reload_weapon(&mut gs.ecs),
+
+            Action::Interact => return context_action(&mut gs.ecs),
 
-            VirtualKeyCode::Space => return context_action(&mut gs.ecs),
+            // Pick or force a lock.
+            Action::PickLock => return lock_action(&mut gs.ecs),
+
+            // Auto-explore.
+            Action::AutoExplore => return start_auto_explore(&mut gs.ecs),
+
+            // Look around.
+            Action::Look => {
+                let ppos = *gs.ecs.fetch::<Point>();
+                return RunState::Look { cursor: ppos };
+            }
+
+            // Show the whole explored map.
+            Action::MapOverview => {
+                term.key = None;
+                return RunState::MapOverview;
+            }
+
+            // Show the quests taken.
+            Action::QuestLog => {
+                term.key = None;
+                return RunState::QuestLog;
+            }
+
+            // Show the key bindings.
+            Action::Help => {
+                term.key = None;
+                return RunState::Help { from_menu: false };
+            }
 
             // Wait (skip turn).
-            VirtualKeyCode::Period => return RunState::PlayerTurn,
+            Action::Wait => {}
 
-            _ => return RunState::Waiting,
+            // Move in any of the 8 directions.
+            _ => match action_dir(&action) {
+                Some(dir) => return move_player(dir, &mut gs.ecs),
+                None => return RunState::Waiting,
+            },
         },
     }
     RunState::PlayerTurn
@@ -83,22 +113,18 @@ pub fn player_input(gs: &mut State, term: &mut BTerm) -> RunState {
 
 /// Valid inputs while in Targeting mode.
 pub fn targeting_input(gs: &mut State, term: &mut BTerm) -> RunState {
-    match term.key {
+    match get_action(term) {
         None => return RunState::Targeting,
-        Some(key) => match key {
-            VirtualKeyCode::K | VirtualKeyCode::Numpad8 | VirtualKeyCode::Up => {
-                return choose_target(&mut gs.ecs, true)
-            }
+        Some(action) => match action {
+            Action::MoveNorth => return choose_target(&mut gs.ecs, true),
 
-            VirtualKeyCode::J | VirtualKeyCode::Numpad2 | VirtualKeyCode::Down => {
-                return choose_target(&mut gs.ecs, false)
-            }
+            Action::MoveSouth => return choose_target(&mut gs.ecs, false),
 
             // Use missile weapon.
-            VirtualKeyCode::F => missile_attack(&mut gs.ecs),
+            Action::Fire => missile_attack(&mut gs.ecs),
 
             // Cancel targeting mode.
-            VirtualKeyCode::Escape => return reset_targeting(&mut gs.ecs),
+            Action::Cancel => return reset_targeting(&mut gs.ecs),
 
             _ => return RunState::Targeting,
         },
@@ -106,53 +132,122 @@ pub fn targeting_input(gs: &mut State, term: &mut BTerm) -> RunState {
     RunState::PlayerTurn
 }
 
-/// Valid inputs while in Targeting mode.
+/// Valid inputs while aiming an explosive weapon at a tile.
+pub fn tile_targeting_input(gs: &mut State, term: &mut BTerm, cursor: Point) -> RunState {
+    match get_action(term) {
+        None => RunState::TargetTile { cursor },
+        Some(action) => match action {
+            // Jump to the next visible hostile.
+            Action::NextHostile => match next_hostile(&gs.ecs, cursor) {
+                Some(pos) => RunState::TargetTile { cursor: pos },
+                None => RunState::TargetTile { cursor },
+            },
+
+            // Use missile weapon (not on the player's own tile).
+            Action::Fire => {
+                if cursor == *gs.ecs.fetch::<Point>() {
+                    return RunState::TargetTile { cursor };
+                }
+                tile_missile_attack(&mut gs.ecs, cursor);
+                RunState::PlayerTurn
+            }
+
+            // Cancel targeting mode.
+            Action::Cancel => RunState::Waiting,
+
+            // Only visible tiles can be aimed at.
+            _ => match action_dir(&action) {
+                Some(dir) => {
+                    let map = gs.ecs.fetch::<Map>();
+                    let next = cursor + dir;
+                    if map.in_map_bounds(next) && map.tiles[map.idx_pt(next)].visible {
+                        RunState::TargetTile { cursor: next }
+                    } else {
+                        RunState::TargetTile { cursor }
+                    }
+                }
+                None => RunState::TargetTile { cursor },
+            },
+        },
+    }
+}
+
+/// Valid inputs while choosing the direction of a contextual action.
 pub fn action_dir_input(gs: &mut State, term: &mut BTerm) -> RunState {
     let ppos = **(&gs.ecs.fetch::<Point>());
     let mut map = gs.ecs.fetch_mut::<Map>();
-    match term.key {
+    match get_action(term) {
         None => return RunState::ChooseActionDir,
-        Some(key) => match key {
-            VirtualKeyCode::Period | VirtualKeyCode::Numpad5 => {
+        Some(action) => match action {
+            Action::Wait => {
                 return check_near(&gs.ecs, ppos, &mut map);
             }
-            // Move East (E).
-            VirtualKeyCode::L | VirtualKeyCode::Numpad6 | VirtualKeyCode::Right => {
-                return check_near(&gs.ecs, ppos + EAST, &mut map);
-            }
-            // Move West (W).
-            VirtualKeyCode::H | VirtualKeyCode::Numpad4 | VirtualKeyCode::Left => {
-                return check_near(&gs.ecs, ppos + WEST, &mut map);
-            }
-            // Move North (N).
-            VirtualKeyCode::K | VirtualKeyCode::Numpad8 | VirtualKeyCode::Up => {
-                return check_near(&gs.ecs, ppos + NORTH, &mut map);
-            }
-            // Move South (S).
-            VirtualKeyCode::J | VirtualKeyCode::Numpad2 | VirtualKeyCode::Down => {
-                return check_near(&gs.ecs, ppos + SOUTH, &mut map);
-            }
-            // Move Northeast (NE).
-            VirtualKeyCode::U | VirtualKeyCode::Numpad9 => {
-                return check_near(&gs.ecs, ppos + NORTHEAST, &mut map);
-            }
-            // Move Northwest (NW).
-            VirtualKeyCode::Y | VirtualKeyCode::Numpad7 => {
-                return check_near(&gs.ecs, ppos + NORTHWEST, &mut map);
-            }
-            // Move Southeast (SE).
-            VirtualKeyCode::N | VirtualKeyCode::Numpad3 => {
-                return check_near(&gs.ecs, ppos + SOUTHEAST, &mut map);
-            }
-            // Move Southwest (SW).
-            VirtualKeyCode::B | VirtualKeyCode::Numpad1 => {
-                return check_near(&gs.ecs, ppos + SOUTHWEST, &mut map);
-            }
 
             // Cancel targeting mode.
-            VirtualKeyCode::Escape => return RunState::Waiting,
+            Action::Cancel => return RunState::Waiting,
+
+            _ => match action_dir(&action) {
+                Some(dir) => return check_near(&gs.ecs, ppos + dir, &mut map),
+                None => return RunState::ChooseActionDir,
+            },
+        },
+    }
+}
+
+/// Valid inputs while choosing which lock to pick or force.
+pub fn lock_dir_input(gs: &mut State, term: &mut BTerm) -> RunState {
+    let ppos = *gs.ecs.fetch::<Point>();
+    let mut map = gs.ecs.fetch_mut::<Map>();
+    match get_action(term) {
+        None => RunState::ChooseLockDir,
+        Some(action) => match action {
+            Action::Wait => pick_lock(&gs.ecs, ppos, &mut map),
+
+            Action::Cancel => RunState::Waiting,
+
+            _ => match action_dir(&action) {
+                Some(dir) => pick_lock(&gs.ecs, ppos + dir, &mut map),
+                None => RunState::ChooseLockDir,
+            },
+        },
+    }
+}
+
+/// Valid inputs while looking at the map overview.
+pub fn overview_input(term: &mut BTerm) -> RunState {
+    match get_action(term) {
+        Some(Action::MapOverview) | Some(Action::Cancel) => RunState::Waiting,
+        _ => RunState::MapOverview,
+    }
+}
 
-            _ => return RunState::ChooseActionDir,
+/// Valid inputs while in Look mode.
+pub fn look_input(gs: &mut State, term: &mut BTerm, cursor: Point) -> RunState {
+    match get_action(term) {
+        None => RunState::Look { cursor },
+        Some(action) => match action {
+            // Jump to the next visible hostile.
+            Action::NextHostile => match next_hostile(&gs.ecs, cursor) {
+                Some(pos) => RunState::Look { cursor: pos },
+                None => RunState::Look { cursor },
+            },
+
+            Action::Look | Action::Cancel => RunState::Waiting,
+
+            _ => match action_dir(&action) {
+                Some(dir) => {
+                    let map = gs.ecs.fetch::<Map>();
+                    let ppos = *gs.ecs.fetch::<Point>();
+                    if map.in_map_bounds(cursor + dir) && in_viewport(ppos, cursor + dir) {
+                        RunState::Look {
+                            cursor: cursor + dir,
+                        }
+                    } else {
+                        RunState::Look { cursor }
+                    }
+                }
+                None => RunState::Look { cursor },
+            },
         },
     }
 }
@@ -1,5 +1,13 @@
-use super::{log::Log, spawner::spawn_remains, BaseStats, Inventory, Name, Player, Position};
+use super::{
+    log::Log,
+    map_gen::Map,
+    quests::QuestLog,
+    raws::{get_mob_loot, roll_loot, spawn_item, RAWS},
+    spawner::spawn_remains,
+    BaseStats, Inventory, Name, Player, Position, SlainByPlayer,
+};
 use crate::utils::colors::*;
+use bracket_lib::prelude::RandomNumberGenerator;
 use specs::prelude::*;
 
 /*
@@ -7,7 +15,9 @@ use specs::prelude::*;
  * killer.rs
  * ---------
  * Works as a "cleaner" by deleting the dead entities from the world.
- * Also inserts dead mob's remains (if there are items to be dropped).
+ * Counts the mobs killed by the player for their quests (see quests.rs).
+ * Also inserts dead mob's remains (if there are items to be dropped), with the loot of the mob
+ * (see loot_tables in raws.ron).
  *
  */
 
@@ -29,6 +39,9 @@ impl<'a> Killer<'a> {
             let names = self.ecs.read_storage::<Name>();
             let positions = self.ecs.read_storage::<Position>();
             let player = self.ecs.read_storage::<Player>();
+            let slain = self.ecs.read_storage::<SlainByPlayer>();
+            let map = self.ecs.fetch::<Map>();
+            let mut quest_log = self.ecs.fetch_mut::<QuestLog>();
             let mut log = self.ecs.fetch_mut::<Log>();
 
             let red = color("BrightRed", 1.0);
@@ -40,7 +53,15 @@ impl<'a> Killer<'a> {
                     if let Some(_p) = p {
                         log.add("You died...", red);
                     } else {
-                        log.add(format!("{} dies.", &name.name), yellow);
+                        // Mobs killed by the player (weapons or fire) count for their quests.
+                        let by_player = slain.get(ent).is_some();
+                        if by_player {
+                            quest_log.count_kill(&name.name);
+                        }
+                        // Other deaths are only told if the player sees them (see melee.rs).
+                        if by_player || map.is_visible(map.idx_pt(*pos)) {
+                            log.add(format!("{} dies.", &name.name), yellow);
+                        }
                         dead.push((ent, name.name.to_string(), *pos));
                     }
                 }
@@ -68,8 +89,27 @@ impl<'a> Killer<'a> {
                 .collect::<Vec<_>>();
         }
 
+        items.extend(self.roll_loot(&ent_name));
+
         if items.len() > 0 {
             spawn_remains(self.ecs, items, ent_name, ent_pos);
         }
     }
+
+    /// Creates the items of a mob's loot table, to be put on its remains.
+    fn roll_loot(&mut self, ent_name: &str) -> Vec<Entity> {
+        let raws = &RAWS.lock().unwrap();
+        let table = match get_mob_loot(ent_name, raws) {
+            Some(table) => table,
+            None => return Vec::new(),
+        };
+        let loot = {
+            let depth = self.ecs.fetch::<Map>().depth;
+            let mut rng = self.ecs.write_resource::<RandomNumberGenerator>();
+            roll_loot(&table, depth, raws, &mut rng)
+        };
+        loot.iter()
+            .filter_map(|item| spawn_item(item, None, self.ecs.create_entity(), raws))
+            .collect()
+    }
 }
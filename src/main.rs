@@ -6,18 +6,23 @@ mod state;
 use state::{RunState, State};
 mod components;
 pub use components::*;
+mod dialogue;
+mod furniture;
 mod input;
 mod killer;
 mod log;
 mod map_gen;
 use map_gen::TileType;
 mod player;
+mod quests;
 mod raws;
 mod renderer;
 mod rexloader;
+mod settings;
 pub use raws::load_raws;
 mod spawner;
 mod systems;
+mod trade;
 mod ui;
 mod utils;
 
@@ -29,10 +34,10 @@ pub const Y_OFFSET: i32 = 10; // Bottom box
 pub const WINDOW_WIDTH: i32 = 80;
 pub const WINDOW_HEIGHT: i32 = 60;
 //pub const WINDOW_HEIGHT: i32 = 80+Y_OFFSET;
-pub const TILE_WIDTH: i32 = 18;
-pub const TILE_HEIGHT: i32 = 18;
+pub const TILE_SIZE: i32 = 18; // Default; see settings.rs.
 
 pub const SHOW_MAP: bool = false;
+pub const AUTO_PICKUP: bool = true; // Pick up items while auto-exploring.
 
 embedded_resource!(FONT01, "../resources/fonts/Sapphos-square-16x16.png");
 embedded_resource!(FONT02, "../resources/fonts/Anikki-square-16x16.png");
@@ -41,22 +46,59 @@ fn main() -> BError {
     link_resource!(FONT01, "resources/Sapphos-square-16x16.png");
     link_resource!(FONT02, "resources/Anikki-square-16x16.png");
 
-    let term = BTermBuilder::new()
+    // Time the WFC without opening the game.
+    if std::env::args().any(|arg| arg == "--bench-wfc") {
+        rexloader::load_dungeons();
+        raws::load_raws();
+        if !map_gen::bench_wfc() {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    // Generate a map and save it as a .xp file, to edit it in REXPaint.
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(i) = args.iter().position(|arg| arg == "--export-xp") {
+        let path = args.get(i + 1).map_or("map.xp", |p| p.as_str());
+        rexloader::load_dungeons();
+        raws::load_raws();
+        let mut gen = map_gen::MapGenerator::new();
+        gen.push_map(80, 60);
+        gen.gen_map(0);
+        if let Err(e) = map_gen::export_map(&gen.maps[0], Some(&gen.prefab_spawns), path) {
+            println!("Couldn't write {}: {}", path, e);
+            std::process::exit(1);
+        }
+        println!("Map saved to {}.", path);
+        return Ok(());
+    }
+
+    let settings = settings::Settings::load();
+    let (font, font_w, font_h) =
+        settings::Settings::font_resource(&settings.font, settings::DEFAULT_FONT);
+    let (ui_font, ui_font_w, ui_font_h) =
+        settings::Settings::font_resource(&settings.ui_font, settings::DEFAULT_UI_FONT);
+
+    let mut term = BTermBuilder::new()
         .with_dimensions(WINDOW_WIDTH, WINDOW_HEIGHT)
         .with_title("TCC")
-        .with_tile_dimensions(TILE_WIDTH, TILE_HEIGHT)
-        .with_font("Sapphos-square-16x16.png", 16, 16)
-        .with_font("Anikki-square-16x16.png", 16, 16)
-        .with_simple_console(WINDOW_WIDTH, WINDOW_HEIGHT, "Sapphos-square-16x16.png")
-        .with_sparse_console(WINDOW_WIDTH, WINDOW_HEIGHT, "Anikki-square-16x16.png")
-        .with_fps_cap(60.0)
+        .with_tile_dimensions(settings.tile_size, settings.tile_size)
+        .with_font(&font, font_w, font_h)
+        .with_font(&ui_font, ui_font_w, ui_font_h)
+        .with_simple_console(WINDOW_WIDTH, WINDOW_HEIGHT, &font)
+        .with_sparse_console(WINDOW_WIDTH, WINDOW_HEIGHT, &ui_font)
+        .with_fps_cap(settings.fps_cap as f32)
         .build()?;
 
-    //term.post_scanlines = true;
+    term.post_scanlines = settings.scanlines;
 
     // Load external files.
     rexloader::load_dungeons();
     raws::load_raws();
+    raws::COLORS
+        .lock()
+        .unwrap()
+        .set_curr_colorscheme(&settings.colorscheme);
 
     let mut world = World::new();
 
@@ -79,6 +121,8 @@ fn main() -> BError {
     world.register::<MeleeWeapon>();
     world.register::<MissileWeapon>();
     world.register::<Ammunition>();
+    world.register::<Incendiary>();
+    world.register::<Explosive>();
     world.register::<ActiveWeapon>();
     world.register::<TryReload>();
     world.register::<Target>();
@@ -98,10 +142,33 @@ fn main() -> BError {
     world.register::<Remains>();
     world.register::<Container>();
     world.register::<Contained>();
+    world.register::<AutoExplore>();
+    world.register::<Lock>();
+    world.register::<Key>();
+    world.register::<Lockpick>();
+    world.register::<HeardNoise>();
+    world.register::<Interactive>();
+    world.register::<Resting>();
+    world.register::<Slowed>();
+    world.register::<Burning>();
+    world.register::<SlainByPlayer>();
+    world.register::<LightSource>();
+    world.register::<Awareness>();
+    world.register::<EmitNoise>();
+    world.register::<Faction>();
+    world.register::<Grudge>();
+    world.register::<Talker>();
+    world.register::<Currency>();
+    world.register::<Trader>();
 
     // Create game state.
     let mut game_state = State::new(world);
 
+    // Start on a map saved with --export-xp instead of a generated one.
+    if let Some(i) = args.iter().position(|arg| arg == "--load-xp") {
+        game_state.map_generator.first_level = args.get(i + 1).cloned();
+    }
+
     // Insert map into the ECS and generate it.
     let (height, width) = (80, 60);
 
@@ -131,6 +198,9 @@ fn main() -> BError {
 
     // Insert the Log into the ECS.
     game_state.ecs.insert(log::Log::new());
+    game_state.ecs.insert(quests::QuestLog::new());
+
+    game_state.ecs.insert(settings);
 
     bracket_lib::prelude::main_loop(term, game_state)
 }
@@ -0,0 +1,131 @@
+use super::{CustomRegion, MapGenerator, PrefabMap, TileType, WaveFunctionCollapse};
+use std::time::{Duration, Instant};
+
+/*
+ *
+ * bench.rs
+ * --------
+ * Times the WFC on whole maps (run the game with --bench-wfc).
+ * Works better with --release.
+ *
+ */
+
+const RUNS: u32 = 10;
+const MAP_WIDTH: i32 = 80;
+const MAP_HEIGHT: i32 = 60;
+// How long the player can be kept waiting for a new map.
+const INTERACTIVE_BUDGET: Duration = Duration::from_millis(1000);
+
+struct BenchCase {
+    name: &'static str,
+    template: &'static str,
+    input_w: i32,
+    input_h: i32,
+    tile_size: i32,
+    mix_match: bool,
+    overlapping: bool,
+}
+
+const CASES: [BenchCase; 4] = [
+    BenchCase {
+        name: "tiled (mix & match)",
+        template: "resources/wfc_20x20_5.xp",
+        input_w: 20,
+        input_h: 20,
+        tile_size: 10,
+        mix_match: true,
+        overlapping: false,
+    },
+    BenchCase {
+        name: "tiled",
+        template: "resources/wfc_9x9_1.xp",
+        input_w: 9,
+        input_h: 9,
+        tile_size: 6,
+        mix_match: false,
+        overlapping: false,
+    },
+    BenchCase {
+        name: "tiled",
+        template: "resources/wfc_20x20_2.xp",
+        input_w: 20,
+        input_h: 20,
+        tile_size: 5,
+        mix_match: false,
+        overlapping: false,
+    },
+    BenchCase {
+        name: "overlapping (rotations)",
+        template: "resources/wfc_20x20_2.xp",
+        input_w: 20,
+        input_h: 20,
+        tile_size: 3,
+        mix_match: false,
+        overlapping: true,
+    },
+];
+
+/// Generates a whole map with each of the benchmark cases several times and prints the times.
+/// Returns false if any case goes over the interactive budget.
+pub fn bench_wfc() -> bool {
+    let mut gen = MapGenerator::new();
+    gen.push_map(MAP_WIDTH, MAP_HEIGHT);
+    let region = CustomRegion::new_rect(0, 0, MAP_WIDTH, MAP_HEIGHT);
+    let mut all_ok = true;
+
+    let mut results = Vec::new();
+    for case in CASES.iter() {
+        let mut input = PrefabMap::new(case.template);
+        input.generate(&mut gen.wfc_input);
+        input.repeat_template(&mut gen.wfc_input);
+
+        let mut times: Vec<Duration> = Vec::new();
+        let mut successes = 0;
+        for _ in 0..RUNS {
+            gen.maps[0] = super::Map::new(MAP_WIDTH, MAP_HEIGHT, TileType::Wall, None);
+            let start = Instant::now();
+            let mut wfc = WaveFunctionCollapse::new(case.tile_size, case.mix_match);
+            wfc.set_overlapping(case.overlapping);
+            wfc.set_rotations(case.overlapping);
+            let outcome = wfc.generate(
+                &mut gen.maps[0],
+                &region,
+                &gen.wfc_input,
+                case.input_w,
+                case.input_h,
+                &mut gen.rng,
+            );
+            times.push(start.elapsed());
+            if outcome.success {
+                successes += 1;
+            }
+        }
+
+        let total: Duration = times.iter().sum();
+        let avg = total / RUNS;
+        let max = *times.iter().max().unwrap();
+        let ok = max <= INTERACTIVE_BUDGET;
+        all_ok &= ok;
+        results.push(format!(
+            "{},{},{},{:?},{:?},{}/{},{}",
+            case.template,
+            case.name,
+            case.tile_size,
+            avg,
+            max,
+            successes,
+            RUNS,
+            if ok { "OK" } else { "SLOW" }
+        ));
+    }
+
+    println!("template,model,tile size,avg,max,successes,budget");
+    for line in results {
+        println!("{}", line);
+    }
+    println!(
+        "Budget: {:?} per {}x{} map.",
+        INTERACTIVE_BUDGET, MAP_WIDTH, MAP_HEIGHT
+    );
+    all_ok
+}
@@ -1,4 +1,4 @@
-use super::{common::*, CustomRegion, Map, Room, TileType, Tunnel};
+use super::{common::*, BuildResult, CustomRegion, Map, MapBuilder, Room, TileType, Tunnel};
 use bracket_lib::prelude::RandomNumberGenerator;
 
 /*
@@ -18,30 +18,34 @@ use bracket_lib::prelude::RandomNumberGenerator;
  */
 
 #[allow(dead_code)]
-pub struct BSPDungeon<'a> {
-    region: &'a CustomRegion,
+pub struct BSPDungeon {
     rooms: Vec<Room>, // nodes (rooms)
     optimal_block_size: i32,
     connected: bool,
 }
 
 #[allow(dead_code)]
-impl<'a> BSPDungeon<'a> {
-    pub fn new(region: &'a CustomRegion, optimal_block_size: i32, connected: bool) -> Self {
+impl BSPDungeon {
+    pub fn new(optimal_block_size: i32, connected: bool) -> Self {
         Self {
-            region,
             rooms: vec![],
             optimal_block_size,
             connected,
         }
     }
 
-    pub fn generate(&mut self, map: &mut Map, rng: &mut RandomNumberGenerator) {
+    fn make_rooms(
+        &mut self,
+        map: &mut Map,
+        region: &CustomRegion,
+        rng: &mut RandomNumberGenerator,
+    ) {
+        self.rooms.clear();
         let mut root = Node::new(
-            self.region.x1,
-            self.region.y1,
-            self.region.width,
-            self.region.height,
+            region.x1,
+            region.y1,
+            region.width,
+            region.height,
             self.optimal_block_size,
         );
 
@@ -136,6 +140,45 @@ impl<'a> BSPDungeon<'a> {
     }
 }
 
+// Works properly only for RECTANGULAR/SQUARE regions.
+impl MapBuilder for BSPDungeon {
+    fn build(
+        &mut self,
+        map: &mut Map,
+        region: &CustomRegion,
+        rng: &mut RandomNumberGenerator,
+    ) -> BuildResult {
+        self.make_rooms(map, region, rng);
+
+        let tunnels = if self.connected {
+            // Tunnels get too clutered when they're ordered and we have "big connected rooms".
+            self.build_tunnels(map, rng)
+        } else {
+            match rng.range(0, 3) {
+                // With smaller block sizes (e.g. 5), tunnels_left and tunnels_down become bad.
+                0 => {
+                    println!("LEFT");
+                    self.build_tunnels_left(map, rng)
+                }
+                1 => {
+                    println!("DOWN");
+                    self.build_tunnels_down(map, rng)
+                }
+                _ => {
+                    println!("RANDOM");
+                    self.build_tunnels(map, rng)
+                }
+            }
+        };
+
+        BuildResult {
+            rooms: self.get_rooms(),
+            tunnels,
+            ..BuildResult::new()
+        }
+    }
+}
+
 pub struct Node {
     min_size: i32,
     x: i32,
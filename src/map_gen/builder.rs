@@ -0,0 +1,92 @@
+use super::{get_all_regions, CustomRegion, Map, Region, Room, Tunnel};
+use bracket_lib::prelude::RandomNumberGenerator;
+
+/*
+ *
+ * builder.rs
+ * ----------
+ * A common interface for all the generators: given a map, the region to work on and an rng,
+ * a builder paints the map and tells what it made (rooms, tunnels, regions, doors, entities).
+ * Builders can be chained with BuilderChain, e.g. a cave and then some rooms on top of it.
+ *
+ */
+
+/// What a builder made on the map.
+#[derive(Debug, Clone, Default)]
+pub struct BuildResult {
+    pub rooms: Vec<Room>,
+    pub tunnels: Vec<Tunnel>,
+    pub regions: Vec<Region>,
+    pub doors: Vec<usize>,            // Map indexes.
+    pub spawns: Vec<(usize, String)>, // Entities brought by prefabs/vaults, as (map index, raw name).
+}
+
+impl BuildResult {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds everything of another result to this one.
+    pub fn append(&mut self, mut other: BuildResult) {
+        self.rooms.append(&mut other.rooms);
+        self.tunnels.append(&mut other.tunnels);
+        self.regions.append(&mut other.regions);
+        self.doors.append(&mut other.doors);
+        self.spawns.append(&mut other.spawns);
+    }
+}
+
+pub trait MapBuilder {
+    fn build(
+        &mut self,
+        map: &mut Map,
+        region: &CustomRegion,
+        rng: &mut RandomNumberGenerator,
+    ) -> BuildResult;
+}
+
+/// Runs several builders, one after the other, on the same region.
+pub struct BuilderChain {
+    builders: Vec<Box<dyn MapBuilder>>,
+}
+
+impl BuilderChain {
+    pub fn new() -> Self {
+        Self {
+            builders: Vec::new(),
+        }
+    }
+
+    pub fn with(mut self, builder: impl MapBuilder + 'static) -> Self {
+        self.builders.push(Box::new(builder));
+        self
+    }
+}
+
+impl MapBuilder for BuilderChain {
+    fn build(
+        &mut self,
+        map: &mut Map,
+        region: &CustomRegion,
+        rng: &mut RandomNumberGenerator,
+    ) -> BuildResult {
+        let mut result = BuildResult::new();
+        for builder in self.builders.iter_mut() {
+            let step = builder.build(map, region, rng);
+            // Each builder repaints the region, so only the last floor regions are still right.
+            if !step.regions.is_empty() {
+                result.regions.clear();
+            }
+            result.append(step);
+        }
+        result
+    }
+}
+
+/// The (connected) floor areas of a region, for builders that don't make rooms.
+pub fn floor_regions(map: &Map, region: &CustomRegion) -> Vec<Region> {
+    get_all_regions(map, region)
+        .into_iter()
+        .filter(|r| !r.is_empty())
+        .collect()
+}
@@ -1,9 +1,11 @@
 use super::{
+    builder::floor_regions,
     common::{connect_regions, count_neighbor_tile},
     get_all_regions,
     region::Operations,
-    CustomRegion, Map, Point, Tile, TileType,
+    BuildResult, CustomRegion, Map, MapBuilder, Point, Tile, TileType,
 };
+use bracket_lib::prelude::RandomNumberGenerator;
 
 /*
  *
@@ -17,8 +19,7 @@ use super::{
  */
 
 #[allow(dead_code)]
-pub struct CellularAutomata<'a> {
-    region: &'a CustomRegion,
+pub struct CellularAutomata {
     n_iterations: u8, // the more iterations we have, the smoother the map will be
     n_walls_rule: u8,
     min_cave_size: usize,
@@ -27,9 +28,8 @@ pub struct CellularAutomata<'a> {
 }
 
 #[allow(dead_code)]
-impl<'a> CellularAutomata<'a> {
+impl CellularAutomata {
     pub fn new(
-        region: &'a CustomRegion,
         n_iterations: u8,
         n_walls_rule: u8,
         min_cave_size: usize,
@@ -37,7 +37,6 @@ impl<'a> CellularAutomata<'a> {
         dry_caves: bool,
     ) -> Self {
         Self {
-            region,
             n_iterations,
             n_walls_rule,
             min_cave_size,
@@ -46,14 +45,14 @@ impl<'a> CellularAutomata<'a> {
         }
     }
 
-    pub fn generate(&mut self, map: &mut Map) {
+    fn generate(&mut self, map: &mut Map, region: &CustomRegion) {
         // We need to make a clone here because the already replaced cells MUST NOT
         // affect the current cell.
         let mut tiles = map.tiles.clone();
 
         for _i in 0..self.n_iterations {
-            for y in self.region.y1..self.region.y2 {
-                for x in self.region.x1..self.region.x2 {
+            for y in region.y1..region.y2 {
+                for x in region.x1..region.x2 {
                     let mut flag = false;
                     let curr_pt = Point::new(x, y);
                     let curr_idx = map.idx(x, y);
@@ -82,7 +81,7 @@ impl<'a> CellularAutomata<'a> {
 
         map.tiles = tiles.clone();
 
-        let mut main_caves = get_all_regions(map, self.region);
+        let mut main_caves = get_all_regions(map, region);
         let mut lesser_caves = main_caves.clone();
 
         // Get caves < min_cave_size.
@@ -111,15 +110,15 @@ impl<'a> CellularAutomata<'a> {
         // change below to .y for mostly horizontal tunnels
         main_caves.sort_by(|a, b| map.idx_pos(a[0]).x.cmp(&map.idx_pos(b[0]).x));
         connect_regions(map, main_caves, TileType::Floor, true);
-        self.smooth_map(map);
+        self.smooth_map(map, region);
     }
 
-    fn smooth_map(&self, map: &mut Map) {
+    fn smooth_map(&self, map: &mut Map, region: &CustomRegion) {
         let mut tiles = map.tiles.clone();
 
         for _i in 0..self.n_iterations {
-            for y in self.region.y1..self.region.y2 {
-                for x in self.region.x1..self.region.x2 {
+            for y in region.y1..region.y2 {
+                for x in region.x1..region.x2 {
                     let curr_pt = Point::new(x, y);
                     let curr_idx = map.idx(x, y);
                     if !map.is_water(curr_idx) {
@@ -142,3 +141,18 @@ impl<'a> CellularAutomata<'a> {
         map.tiles = tiles;
     }
 }
+
+impl MapBuilder for CellularAutomata {
+    fn build(
+        &mut self,
+        map: &mut Map,
+        region: &CustomRegion,
+        _rng: &mut RandomNumberGenerator,
+    ) -> BuildResult {
+        self.generate(map, region);
+        BuildResult {
+            regions: floor_regions(map, region),
+            ..BuildResult::new()
+        }
+    }
+}
@@ -196,9 +196,10 @@ pub fn make_exact_tunnel(
         }
 
         let idx = map.idx(x, y);
-        if map.tiles[idx].ttype != TileType::ShallowWater
-            && map.tiles[idx].ttype != TileType::DeepWater
-        {
+        if map.tiles[idx].ttype == TileType::DeepWater {
+            // A ford, so the tunnel can be crossed without swimming.
+            map.paint_tile(idx, TileType::ShallowWater);
+        } else if map.tiles[idx].ttype != TileType::ShallowWater {
             map.paint_tile(idx, ttype);
 
             if natural {
@@ -424,21 +425,14 @@ pub fn connect_regions(map: &mut Map, regions: Vec<Region>, ttype: TileType, nat
     // Populate the vector cave_pts (same as before, but considering the
     // coordinates on the map instead of the index).
     for region in regions.iter() {
-        let mut pts: Vec<Point> = Vec::new();
-        for idx in region {
-            let pt = map.idx_pos(*idx);
-            if region.is_probably_edge(pt, map) {
-                pts.push(pt);
-            }
-        }
-        region_pts.push(pts);
+        region_pts.push(region_edge_points(map, region));
     }
 
     for i in 0..region_pts.len() - 1 {
         let this_region = &region_pts[i];
         let other_region = &region_pts[i + 1];
         if other_region.len() == 0 || this_region.len() == 0 {
-            return;
+            continue;
         }
         let mut shortest_dist = other_region.len();
         let mut this_idx = 0;
@@ -466,6 +460,87 @@ pub fn connect_regions(map: &mut Map, regions: Vec<Region>, ttype: TileType, nat
     }
 }
 
+/// Returns the points on the edges of a region, or all of its points if it seems to have none.
+fn region_edge_points(map: &Map, region: &Region) -> Vec<Point> {
+    let mut pts: Vec<Point> = Vec::new();
+    for idx in region {
+        let pt = map.idx_pos(*idx);
+        if region.is_probably_edge(pt, map) {
+            pts.push(pt);
+        }
+    }
+    if pts.is_empty() {
+        pts = region.iter().map(|idx| map.idx_pos(*idx)).collect();
+    }
+    pts
+}
+
+/// Returns the closest points between two sets of points and the (squared) distance between them.
+fn closest_points(a: &[Point], b: &[Point]) -> (Point, Point, i32) {
+    let mut closest = (a[0], b[0], i32::MAX);
+    for pa in a.iter() {
+        for pb in b.iter() {
+            let d = (pa.x - pb.x).pow(2) + (pa.y - pb.y).pow(2);
+            if d < closest.2 {
+                closest = (*pa, *pb, d);
+            }
+        }
+    }
+    closest
+}
+
+/// Connects all the selected regions with as few tunnels as possible.
+/// The tunnels follow a minimum spanning tree (Prim's algorithm) in which the cost of linking two
+/// regions is the distance between their closest edge points.
+/// Returns the number of tunnels dug.
+pub fn connect_regions_mst(
+    map: &mut Map,
+    regions: &[Region],
+    ttype: TileType,
+    natural: bool,
+) -> usize {
+    let region_pts: Vec<Vec<Point>> = regions
+        .iter()
+        .filter(|r| !r.is_empty())
+        .map(|r| region_edge_points(map, r))
+        .collect();
+    let n = region_pts.len();
+    if n <= 1 {
+        return 0;
+    }
+
+    let mut in_tree = vec![false; n];
+    // Cheapest known link from the tree to each region: (distance, tree point, region point).
+    let mut best: Vec<Option<(i32, Point, Point)>> = vec![None; n];
+    let mut tunnels: Vec<(Point, Point)> = Vec::new();
+    let mut curr = 0;
+
+    for _ in 1..n {
+        in_tree[curr] = true;
+        for other in 0..n {
+            if in_tree[other] {
+                continue;
+            }
+            let (p1, p2, d) = closest_points(&region_pts[curr], &region_pts[other]);
+            if best[other].is_none_or(|(bd, _, _)| d < bd) {
+                best[other] = Some((d, p1, p2));
+            }
+        }
+        let next = (0..n)
+            .filter(|i| !in_tree[*i])
+            .min_by_key(|i| best[*i].map_or(i32::MAX, |(d, _, _)| d))
+            .unwrap();
+        let (_, p1, p2) = best[next].unwrap();
+        tunnels.push((p1, p2));
+        curr = next;
+    }
+
+    for (p1, p2) in tunnels.iter() {
+        make_exact_tunnel(map, p1.x, p1.y, p2.x, p2.y, ttype, natural);
+    }
+    tunnels.len()
+}
+
 /// Makes map chaotic with a chance of floor_chance to change a tile to floor.
 /// Used in mapgen algorithms that require a "chaotic map" like Cellular Automata.
 pub fn make_chaotic(map: &mut Map, region: &CustomRegion, floor_chance: u8) {
@@ -488,8 +563,14 @@ pub fn apply_forest_theme(map: &mut Map, region: &CustomRegion) {
     }
 }
 
-/// Adds doors to ROOMS of a map, given a certain chance.
-pub fn add_doors(map: &mut Map, rooms: &Vec<Room>, chance: i32, rng: &mut RandomNumberGenerator) {
+/// Adds doors to ROOMS of a map, given a certain chance. Returns where they were placed.
+pub fn add_doors(
+    map: &mut Map,
+    rooms: &Vec<Room>,
+    chance: i32,
+    rng: &mut RandomNumberGenerator,
+) -> Vec<usize> {
+    let mut doors = Vec::new();
     if rooms.len() > 0 {
         let mut locs_vec: Vec<Vec<usize>> = Vec::new();
         //let mut r = rooms.unwrap().clone();
@@ -531,10 +612,12 @@ pub fn add_doors(map: &mut Map, rooms: &Vec<Room>, chance: i32, rng: &mut Random
                         continue;
                     }
                     map.tiles[*loc] = Tile::closed_door();
+                    doors.push(*loc);
                 }
             }
         }
     }
+    doors
 }
 
 #[allow(dead_code)]
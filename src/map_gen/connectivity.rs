@@ -0,0 +1,183 @@
+use super::{common::*, Map, Point, Region, TileType};
+use std::collections::VecDeque;
+
+/*
+ *
+ * connectivity.rs
+ * ---------------
+ * Checks a generated map before it's played: the spawn and the exit must be on walkable tiles,
+ * and every walkable tile must be reachable from the spawn on foot (without swimming).
+ *
+ */
+
+const MAX_PASSES: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConnectivityError {
+    NoWalkableTiles,
+    Unreachable { tiles: usize }, // Walkable tiles that couldn't be connected to the spawn.
+    ExitUnreachable,
+}
+
+/// What had to be fixed on a map.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ConnectivityReport {
+    pub regions: usize, // Separated regions found on the first pass.
+    pub tunnels: usize,
+    pub spawn_moved: bool,
+    pub exit_moved: bool,
+    pub has_exit: bool,
+}
+
+/// Whether a tile can be walked on without swimming (deep water can make you lose items).
+fn is_on_foot(map: &Map, idx: usize) -> bool {
+    map.is_walkable(idx) && map.tiles[idx].ttype != TileType::DeepWater
+}
+
+/// Whether a tile can be walked through (doors and exits included).
+pub fn is_passable(map: &Map, idx: usize) -> bool {
+    is_on_foot(map, idx) || map.is_door(idx) || map.is_exit(idx)
+}
+
+/// Flood fills (in 4 directions) the passable tiles from a point.
+pub fn reachable_from(map: &Map, start: Point) -> Vec<bool> {
+    let mut reached = vec![false; map.size as usize];
+    if !map.in_map_bounds(start) || !is_passable(map, map.idx_pt(start)) {
+        return reached;
+    }
+    let mut queue: VecDeque<Point> = VecDeque::new();
+    reached[map.idx_pt(start)] = true;
+    queue.push_back(start);
+
+    while let Some(pt) = queue.pop_front() {
+        for dir in [(1, 0), (-1, 0), (0, 1), (0, -1)].iter() {
+            let next = Point::new(pt.x + dir.0, pt.y + dir.1);
+            if !map.in_map_bounds(next) {
+                continue;
+            }
+            let idx = map.idx_pt(next);
+            if !reached[idx] && is_passable(map, idx) {
+                reached[idx] = true;
+                queue.push_back(next);
+            }
+        }
+    }
+    reached
+}
+
+/// Gets the separated regions of passable tiles (deep water splits them, unlike get_all_regions).
+fn passable_regions(map: &Map) -> Vec<Region> {
+    let mut marked = vec![false; map.size as usize];
+    let mut regions: Vec<Region> = Vec::new();
+    for idx in 0..map.size as usize {
+        if marked[idx] || !is_passable(map, idx) {
+            continue;
+        }
+        let region: Region = reachable_from(map, map.idx_pos(idx))
+            .iter()
+            .enumerate()
+            .filter(|(_, reached)| **reached)
+            .map(|(i, _)| i)
+            .collect();
+        for i in region.iter() {
+            marked[*i] = true;
+        }
+        regions.push(region);
+    }
+    regions
+}
+
+/// Returns the closest tile (as the crow flies) to pt that satisfies the condition.
+fn nearest_tile<F: Fn(usize) -> bool>(map: &Map, pt: Point, cond: F) -> Option<Point> {
+    (0..map.size as usize)
+        .filter(|idx| cond(*idx))
+        .map(|idx| map.idx_pos(idx))
+        .min_by_key(|p| (p.x - pt.x).pow(2) + (p.y - pt.y).pow(2))
+}
+
+/// Makes sure the map can be played from the spawn to the exit, fixing it when possible:
+/// - the spawn (and exit) are moved to the closest walkable tile if they're not on one;
+/// - separated regions are connected with tunnels (see connect_regions_mst), fording deep water;
+/// - an exit that's still unreachable gets a tunnel to the closest reachable tile.
+pub fn ensure_connectivity(map: &mut Map) -> Result<ConnectivityReport, ConnectivityError> {
+    let mut report = ConnectivityReport::default();
+
+    // Spawn on a walkable tile.
+    let spawn = map.spawn_point;
+    if !map.in_map_bounds(spawn) || !is_on_foot(map, map.idx_pt(spawn)) {
+        let new_spawn = nearest_tile(map, spawn, |idx| is_on_foot(map, idx))
+            .ok_or(ConnectivityError::NoWalkableTiles)?;
+        map.set_spawn(new_spawn);
+        report.spawn_moved = true;
+    }
+
+    // Connect every region.
+    for pass in 0..MAX_PASSES {
+        let regions = passable_regions(map);
+        if pass == 0 {
+            report.regions = regions.len();
+        }
+        if regions.len() <= 1 {
+            break;
+        }
+        report.tunnels += connect_regions_mst(map, &regions, TileType::Floor, false);
+    }
+
+    let mut reached = reachable_from(map, map.spawn_point);
+    let unreachable = (0..map.size as usize)
+        .filter(|idx| !reached[*idx] && is_on_foot(map, *idx))
+        .count();
+    if unreachable > 0 {
+        return Err(ConnectivityError::Unreachable { tiles: unreachable });
+    }
+
+    // Exit on a walkable tile, or any exit tile there is (preferably a reachable one).
+    let exit = map.exit_point;
+    if map.in_map_bounds(exit) {
+        let exit_idx = map.idx_pt(exit);
+        if !map.is_exit(exit_idx) {
+            let new_exit = if is_passable(map, exit_idx) {
+                exit
+            } else {
+                nearest_tile(map, exit, |idx| reached[idx])
+                    .ok_or(ConnectivityError::ExitUnreachable)?
+            };
+            let new_idx = map.idx_pt(new_exit);
+            map.paint_tile(new_idx, TileType::Exit);
+            map.set_exit(new_exit);
+            report.exit_moved = new_exit != exit;
+        }
+    } else if let Some(idx) = (0..map.size as usize)
+        .filter(|idx| map.is_exit(*idx))
+        .max_by_key(|idx| reached[*idx])
+    {
+        let pt = map.idx_pos(idx);
+        map.set_exit(pt);
+    }
+
+    // Spawn and exit reachable from each other.
+    let exit = map.exit_point;
+    if map.in_map_bounds(exit) {
+        report.has_exit = true;
+        if !reached[map.idx_pt(exit)] {
+            let target = nearest_tile(map, exit, |idx| reached[idx])
+                .ok_or(ConnectivityError::ExitUnreachable)?;
+            make_exact_tunnel(
+                map,
+                exit.x,
+                exit.y,
+                target.x,
+                target.y,
+                TileType::Floor,
+                false,
+            );
+            report.tunnels += 1;
+            reached = reachable_from(map, map.spawn_point);
+            if !reached[map.idx_pt(exit)] {
+                return Err(ConnectivityError::ExitUnreachable);
+            }
+        }
+    }
+
+    Ok(report)
+}
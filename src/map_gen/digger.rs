@@ -1,7 +1,7 @@
 use super::{
     common::{create_h_tunnel_room, create_room, create_v_tunnel_room},
     room::Operations,
-    CustomRegion, Map, Room, TileType,
+    BuildResult, CustomRegion, Map, MapBuilder, Room, TileType,
 };
 use crate::utils::directions::*;
 use bracket_lib::prelude::{DistanceAlg, Point, RandomNumberGenerator};
@@ -16,8 +16,7 @@ use bracket_lib::prelude::{DistanceAlg, Point, RandomNumberGenerator};
  */
 
 #[allow(dead_code)]
-pub struct Digger<'a> {
-    region: &'a CustomRegion,
+pub struct Digger {
     rooms: Vec<Room>,
     min_size: u8,
     max_size: u8,
@@ -26,10 +25,9 @@ pub struct Digger<'a> {
 }
 
 #[allow(dead_code)]
-impl<'a> Digger<'a> {
-    pub fn new(region: &'a CustomRegion, min_size: u8, max_size: u8, num_features: i32) -> Self {
+impl Digger {
+    pub fn new(min_size: u8, max_size: u8, num_features: i32) -> Self {
         Self {
-            region,
             rooms: vec![],
             min_size,
             max_size,
@@ -42,18 +40,24 @@ impl<'a> Digger<'a> {
         self.rooms.clone()
     }
 
-    pub fn generate(&mut self, map: &mut Map, rng: &mut RandomNumberGenerator) {
+    fn generate(&mut self, map: &mut Map, region: &CustomRegion, rng: &mut RandomNumberGenerator) {
         // Create initial room on the center of the map.
-        let center = self.region.get_center();
+        let center = region.get_center();
         let wi = rng.range(self.min_size, self.max_size) as i32;
         let hi = wi as i32;
         let initial_room = Room::with_size(center.x - 5, center.y - 5, wi, hi);
         create_room(map, initial_room, TileType::Floor);
         self.rooms.push(initial_room);
-        self.gen_feature(map, rng);
+        self.gen_feature(map, region, rng);
     }
 
-    fn add_feature(&mut self, map: &mut Map, room: Room, rng: &mut RandomNumberGenerator) -> bool {
+    fn add_feature(
+        &mut self,
+        map: &mut Map,
+        region: &CustomRegion,
+        room: Room,
+        rng: &mut RandomNumberGenerator,
+    ) -> bool {
         let w = rng.range(self.min_size, self.max_size);
         let h = rng.range(w, self.max_size);
         let dir = get_random_dir();
@@ -81,11 +85,11 @@ impl<'a> Digger<'a> {
 
         for r in self.rooms.iter() {
             if new_room.intersect(r)
-                || !self.region.in_bounds(Point {
+                || !region.in_bounds(Point {
                     x: new_room.x1,
                     y: new_room.y1,
                 })
-                || !self.region.in_bounds(Point {
+                || !region.in_bounds(Point {
                     x: new_room.x2,
                     y: new_room.y2,
                 })
@@ -100,19 +104,24 @@ impl<'a> Digger<'a> {
         return true;
     }
 
-    fn gen_feature(&mut self, map: &mut Map, rng: &mut RandomNumberGenerator) {
+    fn gen_feature(
+        &mut self,
+        map: &mut Map,
+        region: &CustomRegion,
+        rng: &mut RandomNumberGenerator,
+    ) {
         let num_features = 0;
         let repeat = self.num_features * 3; // max number of iterations
 
         // Main, bigger rooms.
-        self.gen_feature_loop(num_features, repeat, map, rng);
+        self.gen_feature_loop(num_features, repeat, map, region, rng);
 
         self.min_size /= 2;
         self.max_size /= 2;
 
         // Peripheric, smaller rooms.
         self.peripheral = true;
-        self.gen_feature_loop(num_features, repeat, map, rng);
+        self.gen_feature_loop(num_features, repeat, map, region, rng);
     }
 
     fn gen_feature_loop(
@@ -120,6 +129,7 @@ impl<'a> Digger<'a> {
         mut num_features: i32,
         mut repeat: i32,
         map: &mut Map,
+        region: &CustomRegion,
         rng: &mut RandomNumberGenerator,
     ) {
         let mut prev_idx = 0;
@@ -134,7 +144,7 @@ impl<'a> Digger<'a> {
                     continue;
                 }
                 prev_idx = idx;
-                if self.add_feature(map, self.rooms[idx], rng) {
+                if self.add_feature(map, region, self.rooms[idx], rng) {
                     repeat += 1;
                     num_features += 1;
                 }
@@ -258,3 +268,25 @@ impl<'a> Digger<'a> {
         }
     }
 }
+
+impl MapBuilder for Digger {
+    fn build(
+        &mut self,
+        map: &mut Map,
+        region: &CustomRegion,
+        rng: &mut RandomNumberGenerator,
+    ) -> BuildResult {
+        // The peripheral rooms shrink the sizes, so they're restored for the next build.
+        let (min_size, max_size) = (self.min_size, self.max_size);
+        self.rooms.clear();
+        self.peripheral = false;
+        self.generate(map, region, rng);
+        self.min_size = min_size;
+        self.max_size = max_size;
+
+        BuildResult {
+            rooms: self.get_rooms(),
+            ..BuildResult::new()
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use super::{builder::floor_regions, BuildResult, CustomRegion, Map, MapBuilder, TileType};
+use bracket_lib::prelude::{line2d_bresenham, Point, RandomNumberGenerator};
+
+/*
+ *
+ * dla.rs
+ * ------
+ * Diffusion-Limited Aggregation: particles wander until they hit the floor dug so far, and stick
+ * to it. Makes organic, branching caves.
+ *
+ * http://www.roguebasin.com/index.php?title=Diffusion-limited_aggregation
+ *
+ */
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DlaAlgorithm {
+    WalkInwards,      // Particles start anywhere and walk randomly until they hit the floor.
+    WalkOutwards,     // Particles start on the center and walk randomly until they leave it.
+    CentralAttractor, // Particles start anywhere and walk straight to the center.
+}
+
+const DIRS: [(i32, i32); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+
+fn in_bounds(map: &Map, region: &CustomRegion, pt: Point) -> bool {
+    region.in_bounds(pt) && map.in_map_bounds(pt)
+}
+
+pub struct DiffusionLimitedAggregation {
+    algorithm: DlaAlgorithm,
+    percent: f32,    // Floor wanted on the region.
+    brush_size: i32, // Bigger brushes dig wider passages.
+}
+
+impl DiffusionLimitedAggregation {
+    pub fn new(algorithm: DlaAlgorithm, percent: f32, brush_size: i32) -> Self {
+        Self {
+            algorithm,
+            percent,
+            brush_size: brush_size.max(1),
+        }
+    }
+
+    fn random_pos(
+        &self,
+        map: &Map,
+        region: &CustomRegion,
+        rng: &mut RandomNumberGenerator,
+    ) -> Option<Point> {
+        for _ in 0..100 {
+            let pt = Point::new(
+                rng.range(region.x1, region.x2),
+                rng.range(region.y1, region.y2),
+            );
+            if in_bounds(map, region, pt) {
+                return Some(pt);
+            }
+        }
+        None
+    }
+
+    /// Digs around a point with the brush. Returns how many new floor tiles there are.
+    fn dig(&self, map: &mut Map, region: &CustomRegion, pt: Point) -> usize {
+        let mut dug = 0;
+        for dy in 0..self.brush_size {
+            for dx in 0..self.brush_size {
+                let p = Point::new(
+                    pt.x + dx - self.brush_size / 2,
+                    pt.y + dy - self.brush_size / 2,
+                );
+                if in_bounds(map, region, p) && !map.is_floor(map.idx_pt(p)) {
+                    map.paint_tile(map.idx_pt(p), TileType::Floor);
+                    dug += 1;
+                }
+            }
+        }
+        dug
+    }
+
+    fn generate(&mut self, map: &mut Map, region: &CustomRegion, rng: &mut RandomNumberGenerator) {
+        let center = region.get_center();
+        if !in_bounds(map, region, center) {
+            return;
+        }
+        let mut n_floor = region
+            .pos
+            .iter()
+            .filter(|p| in_bounds(map, region, **p) && map.is_floor(map.idx_pt(**p)))
+            .count();
+        let needed = (self.percent * region.size as f32) as usize;
+        // The seed everything sticks to.
+        for (dx, dy) in [(0, 0), (1, 0), (-1, 0), (0, 1), (0, -1)].iter() {
+            n_floor += self.dig(map, region, Point::new(center.x + dx, center.y + dy));
+        }
+
+        let max_steps = region.size * 4;
+        let mut particles = 0;
+        while n_floor < needed && particles < region.size * 2 {
+            particles += 1;
+            match self.algorithm {
+                DlaAlgorithm::WalkInwards => {
+                    let mut pt = match self.random_pos(map, region, rng) {
+                        Some(pt) => pt,
+                        None => break,
+                    };
+                    let mut prev = pt;
+                    let mut steps = 0;
+                    while !map.is_floor(map.idx_pt(pt)) && steps < max_steps {
+                        let (dx, dy) = DIRS[rng.range(0, 4) as usize];
+                        let next = Point::new(pt.x + dx, pt.y + dy);
+                        if in_bounds(map, region, next) {
+                            prev = pt;
+                            pt = next;
+                        }
+                        steps += 1;
+                    }
+                    if steps < max_steps {
+                        n_floor += self.dig(map, region, prev);
+                    }
+                }
+                DlaAlgorithm::WalkOutwards => {
+                    let mut pt = center;
+                    let mut steps = 0;
+                    while map.is_floor(map.idx_pt(pt)) && steps < max_steps {
+                        let (dx, dy) = DIRS[rng.range(0, 4) as usize];
+                        let next = Point::new(pt.x + dx, pt.y + dy);
+                        if in_bounds(map, region, next) {
+                            pt = next;
+                        }
+                        steps += 1;
+                    }
+                    if !map.is_floor(map.idx_pt(pt)) {
+                        n_floor += self.dig(map, region, pt);
+                    }
+                }
+                DlaAlgorithm::CentralAttractor => {
+                    let start = match self.random_pos(map, region, rng) {
+                        Some(pt) => pt,
+                        None => break,
+                    };
+                    let mut prev = start;
+                    for pt in line2d_bresenham(start, center) {
+                        if map.is_floor(map.idx_pt(pt)) {
+                            n_floor += self.dig(map, region, prev);
+                            break;
+                        }
+                        prev = pt;
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl MapBuilder for DiffusionLimitedAggregation {
+    fn build(
+        &mut self,
+        map: &mut Map,
+        region: &CustomRegion,
+        rng: &mut RandomNumberGenerator,
+    ) -> BuildResult {
+        self.generate(map, region, rng);
+        BuildResult {
+            regions: floor_regions(map, region),
+            ..BuildResult::new()
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use super::{connectivity::is_passable, Map, Point, TileType};
+use crate::components::Lock;
+use bracket_lib::prelude::RandomNumberGenerator;
+use std::collections::VecDeque;
+
+/*
+ *
+ * locks.rs
+ * --------
+ * Locks some of the doors a generator made. Their keys are spawned later (see spawner.rs) on
+ * tiles that can be reached from the spawn without going through a locked door, so a level
+ * never needs lockpicking to be finished.
+ *
+ */
+
+/// Chance (in 1/x) of a closed door being locked.
+const DOOR_LOCK_CHANCE: i32 = 4;
+
+/// How hard a lock made on a given depth is to pick or force.
+pub fn lock_difficulty(depth: i32, rng: &mut RandomNumberGenerator) -> i32 {
+    rng.range(1, 4) + depth / 3
+}
+
+/// Locks some of the closed doors; `next_id` is the id of the next lock made in the game.
+pub fn lock_doors(
+    map: &mut Map,
+    doors: &[usize],
+    next_id: &mut u32,
+    rng: &mut RandomNumberGenerator,
+) {
+    for idx in doors.iter() {
+        if map.tiles[*idx].ttype != TileType::ClosedDoor || map.is_locked(*idx) {
+            continue;
+        }
+        if rng.range(0, DOOR_LOCK_CHANCE) == 0 {
+            let lock = Lock {
+                id: *next_id,
+                difficulty: lock_difficulty(map.depth, rng),
+            };
+            map.add_lock(*idx, lock);
+            *next_id += 1;
+        }
+    }
+}
+
+/// Tiles where a key can be left: reachable from the spawn without opening any locked door.
+pub fn key_spots(map: &Map) -> Vec<usize> {
+    let mut reached = vec![false; map.size as usize];
+    let spawn = map.spawn_point;
+    if !map.in_map_bounds(spawn) {
+        return Vec::new();
+    }
+    let mut queue: VecDeque<Point> = VecDeque::new();
+    reached[map.idx_pt(spawn)] = true;
+    queue.push_back(spawn);
+
+    while let Some(pt) = queue.pop_front() {
+        for dir in [(1, 0), (-1, 0), (0, 1), (0, -1)].iter() {
+            let next = Point::new(pt.x + dir.0, pt.y + dir.1);
+            if !map.in_map_bounds(next) {
+                continue;
+            }
+            let idx = map.idx_pt(next);
+            if !reached[idx] && is_passable(map, idx) && !map.is_locked(idx) {
+                reached[idx] = true;
+                queue.push_back(next);
+            }
+        }
+    }
+
+    (0..map.size as usize)
+        .filter(|idx| reached[*idx] && map.is_walkable(*idx) && !map.is_water(*idx))
+        .filter(|idx| map.idx_pos(*idx) != spawn)
+        .collect()
+}
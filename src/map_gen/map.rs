@@ -1,8 +1,9 @@
 use super::{get_tile_function, CustomRegion, Tile, TileType};
-use crate::components::Position;
+use crate::components::{Lock, Position};
 use crate::utils::directions::*;
 use bracket_lib::prelude::*;
 use specs::prelude::Entity;
+use std::collections::HashMap;
 use strum_macros::Display;
 
 /*
@@ -13,15 +14,29 @@ use strum_macros::Display;
  *
  */
 
+/// Light level from which a tile can be seen beyond the dark-vision radius.
+const LIT_LEVEL: f32 = 0.3;
+
 #[derive(Display, Debug, Copy, Clone)]
 pub enum MapType {
-    //Forest,
+    Forest,
     Ruins,
-    //Cave,
+    Cave,
     //Structure,
     //Town,
 }
 
+impl MapType {
+    /// Light level of every tile of a map of this type, before any light source.
+    pub fn ambient_light(&self) -> f32 {
+        match self {
+            MapType::Forest => 1.0,
+            MapType::Ruins => 0.6,
+            MapType::Cave => 0.05,
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Map {
     pub tiles: Vec<Tile>,
@@ -30,7 +45,11 @@ pub struct Map {
     pub width: i32,
     pub height: i32,
     pub maptype: Option<MapType>,
+    pub depth: i32,
     pub entities: Vec<Option<Vec<Entity>>>,
+    pub locks: HashMap<usize, Lock>,        // Locked doors.
+    pub fires: HashMap<usize, (i32, bool)>, // Burning tiles: turns they still burn, lit by the player.
+    pub light: Vec<RGB>,                    // Light over each tile (see lighting.rs).
     pub spawn_point: Position,
     pub exit_point: Position,
 }
@@ -47,7 +66,11 @@ impl Map {
             width,
             height,
             maptype,
+            depth: 1,
             entities: vec![None; map_size as usize],
+            locks: HashMap::new(),
+            fires: HashMap::new(),
+            light: vec![RGB::from_f32(1.0, 1.0, 1.0); map_size as usize],
             spawn_point: Position::new(-1, -1),
             exit_point: Position::new(-1, -1),
         }
@@ -57,6 +80,10 @@ impl Map {
         self.maptype = Some(maptype);
     }
 
+    pub fn set_depth(&mut self, depth: i32) {
+        self.depth = depth;
+    }
+
     pub fn set_spawn(&mut self, pos: Position) {
         self.spawn_point = pos;
     }
@@ -73,6 +100,23 @@ impl Map {
         self.maptype.unwrap()
     }
 
+    pub fn ambient_light(&self) -> f32 {
+        match self.maptype {
+            Some(maptype) => maptype.ambient_light(),
+            None => 1.0,
+        }
+    }
+
+    /// How lit a tile is, from 0 (pitch black) to 1.
+    pub fn light_level(&self, idx: usize) -> f32 {
+        let light = self.light[idx];
+        f32::max(light.r, f32::max(light.g, light.b))
+    }
+
+    pub fn is_lit(&self, idx: usize) -> bool {
+        self.light_level(idx) >= LIT_LEVEL
+    }
+
     /// Add solid borders to the map.
     pub fn add_borders(&mut self, ttype: TileType) {
         let mut idx;
@@ -163,66 +207,17 @@ impl Map {
         self.tiles[idx] = get_tile_function(ttype);
     }
 
+    /// Changes a tile while playing, keeping what the player knows about it.
+    pub fn change_tile(&mut self, idx: usize, ttype: TileType) {
+        let (revealed, visible) = (self.tiles[idx].revealed, self.tiles[idx].visible);
+        self.paint_tile(idx, ttype);
+        self.tiles[idx].revealed = revealed;
+        self.tiles[idx].visible = visible;
+    }
+
+    /// Paints a tile from its character in a prefab (see TileType::from_char).
     pub fn paint_tile_char(&mut self, idx: usize, ch: char) {
-        match ch {
-            '.' => {
-                self.tiles[idx] = Tile::floor();
-            }
-            '-' => {
-                self.tiles[idx] = Tile::floor2();
-            }
-            '_' => {
-                self.tiles[idx] = Tile::woodenfloor();
-            }
-            '+' => {
-                self.tiles[idx] = Tile::closed_door();
-            }
-            '/' => {
-                self.tiles[idx] = Tile::open_door();
-            }
-            '#' => {
-                self.tiles[idx] = Tile::wall();
-            }
-            'g' => {
-                self.tiles[idx] = Tile::fakemob();
-            }
-            'Φ' => {
-                self.tiles[idx] = Tile::computer();
-            }
-            '~' => {
-                self.tiles[idx] = Tile::shallow_water();
-            }
-            '≈' => {
-                self.tiles[idx] = Tile::deep_water();
-            }
-            '♣' => {
-                self.tiles[idx] = Tile::tree();
-            }
-            'T' => {
-                self.tiles[idx] = Tile::tree();
-            }
-            '♠' => {
-                self.tiles[idx] = Tile::mushroom();
-            }
-            '⌠' => {
-                self.tiles[idx] = Tile::tallgrass();
-            }
-            '░' => {
-                self.tiles[idx] = Tile::path1();
-            }
-            ',' => {
-                self.tiles[idx] = Tile::grass();
-            }
-            '¥' => {
-                self.tiles[idx] = Tile::flower();
-            }
-            '>' => {
-                self.tiles[idx] = Tile::exit();
-            }
-            _ => {
-                self.tiles[idx] = Tile::floor();
-            }
-        }
+        self.paint_tile(idx, TileType::from_char(ch));
     }
 
     pub fn reload_tile_colors(&mut self) {
@@ -250,10 +245,75 @@ impl Map {
             TileType::Floor2 => true,
             TileType::WoodenFloor => true,
             TileType::Path1 => true,
+            TileType::Ash => true,
+            TileType::Rubble => true,
+            TileType::Fire => true, // Walkable, but it hurts.
             _ => false,
         }
     }
 
+    /// Tiles that can catch fire (see systems/fire.rs).
+    pub fn is_flammable(&self, idx: usize) -> bool {
+        let ttype = self.tiles[idx].ttype;
+        match ttype {
+            TileType::Grass => true,
+            TileType::Grass2 => true,
+            TileType::Grass3 => true,
+            TileType::Grass4 => true,
+            TileType::TallGrass => true,
+            TileType::Tree => true,
+            TileType::WoodenFloor => true,
+            _ => false,
+        }
+    }
+
+    /// Sets a flammable tile on fire; what it was decides how long it burns.
+    /// Returns false if it can't burn.
+    pub fn ignite(&mut self, idx: usize, by_player: bool) -> bool {
+        // Wet from the water nearby.
+        if self.near_water(idx) {
+            return false;
+        }
+        let fuel = match self.tiles[idx].ttype {
+            TileType::Tree => 6,
+            TileType::WoodenFloor => 4,
+            TileType::TallGrass => 3,
+            _ if self.is_flammable(idx) => 2,
+            _ => return false,
+        };
+        self.change_tile(idx, TileType::Fire);
+        self.fires.insert(idx, (fuel, by_player));
+        true
+    }
+
+    fn near_water(&self, idx: usize) -> bool {
+        let pt = self.idx_pos(idx);
+        (0..4).any(|i| {
+            let neighbor = pt + dir_idx(i);
+            self.in_map_bounds(neighbor) && self.is_water(self.idx_pt(neighbor))
+        })
+    }
+
+    /// Brings a wall down, leaving rubble. The map borders can't be destroyed.
+    pub fn destroy_wall(&mut self, idx: usize) -> bool {
+        if self.tiles[idx].ttype != TileType::Wall {
+            return false;
+        }
+        self.change_tile(idx, TileType::Rubble);
+
+        // The walls around lost a neighbor, so their glyphs must be recomputed.
+        let pt = self.idx_pos(idx);
+        for i in 0..4 {
+            let neighbor = pt + dir_idx(i);
+            if self.in_map_bounds(neighbor) && self.is_wall(self.idx_pt(neighbor)) {
+                let glyph = self.get_wall_glyph(neighbor.x, neighbor.y);
+                let nidx = self.idx_pt(neighbor);
+                self.tiles[nidx].change_glyph(glyph);
+            }
+        }
+        true
+    }
+
     pub fn is_foliage(&self, idx: usize) -> bool {
         let ttype = self.tiles[idx].ttype;
         match ttype {
@@ -357,6 +417,22 @@ impl Map {
         self.tiles[idx].block = true;
     }
 
+    pub fn get_lock(&self, idx: usize) -> Option<Lock> {
+        self.locks.get(&idx).copied()
+    }
+
+    pub fn is_locked(&self, idx: usize) -> bool {
+        self.locks.contains_key(&idx)
+    }
+
+    pub fn add_lock(&mut self, idx: usize, lock: Lock) {
+        self.locks.insert(idx, lock);
+    }
+
+    pub fn remove_lock(&mut self, idx: usize) {
+        self.locks.remove(&idx);
+    }
+
     pub fn reveal(&mut self, idx: usize) {
         self.tiles[idx].revealed = true;
         self.tiles[idx].visible = true;
@@ -384,10 +460,33 @@ impl Map {
         }
     }
 
+    /// How many turns walking into a tile takes (see the Slowed component).
+    pub fn move_cost(&self, idx: usize) -> i32 {
+        if self.is_water(idx) {
+            2
+        } else {
+            1
+        }
+    }
+
+    /// Cost of a tile for pathfinding: slow terrain and fire are avoided when possible.
+    fn path_cost(&self, idx: usize) -> f32 {
+        let danger = if self.tiles[idx].ttype == TileType::Fire {
+            5.0
+        } else {
+            0.0
+        };
+        self.move_cost(idx) as f32 + danger
+    }
+
+    // Deep water can only be crossed by swimming, which mobs don't do.
     fn valid_exit(&self, loc: Point, delta: Point) -> Option<usize> {
         let destination = loc + delta;
         let idx = self.point2d_to_index(destination);
-        if self.in_map_bounds(destination) && !self.tiles[idx].block {
+        if self.in_map_bounds(destination)
+            && !self.tiles[idx].block
+            && self.tiles[idx].ttype != TileType::DeepWater
+        {
             Some(idx)
         } else {
             None
@@ -412,7 +511,10 @@ impl BaseMap for Map {
     // Automatically prevents FOV from looking behind opaque tiles.
     fn is_opaque(&self, idx: usize) -> bool {
         let ttype = self.tiles[idx as usize].ttype;
-        ttype == TileType::Wall || ttype == TileType::Tree || ttype == TileType::ClosedDoor
+        ttype == TileType::Wall
+            || ttype == TileType::Tree
+            || ttype == TileType::ClosedDoor
+            || ttype == TileType::TallGrass
     }
 
     // A* needs this or it won't work!
@@ -420,29 +522,21 @@ impl BaseMap for Map {
         let mut exits = SmallVec::new();
         let location = self.index_to_point2d(idx);
 
-        if let Some(idx) = self.valid_exit(location, Point::new(-1, 0)) {
-            exits.push((idx, 1.0))
-        }
-        if let Some(idx) = self.valid_exit(location, Point::new(1, 0)) {
-            exits.push((idx, 1.0))
-        }
-        if let Some(idx) = self.valid_exit(location, Point::new(0, -1)) {
-            exits.push((idx, 1.0))
-        }
-        if let Some(idx) = self.valid_exit(location, Point::new(0, 1)) {
-            exits.push((idx, 1.0))
-        }
-        if let Some(idx) = self.valid_exit(location, Point::new(-1, -1)) {
-            exits.push((idx, 1.4))
-        }
-        if let Some(idx) = self.valid_exit(location, Point::new(-1, 1)) {
-            exits.push((idx, 1.4))
-        }
-        if let Some(idx) = self.valid_exit(location, Point::new(1, -1)) {
-            exits.push((idx, 1.4))
-        }
-        if let Some(idx) = self.valid_exit(location, Point::new(1, 1)) {
-            exits.push((idx, 1.4))
+        for (delta, cost) in [
+            (Point::new(-1, 0), 1.0),
+            (Point::new(1, 0), 1.0),
+            (Point::new(0, -1), 1.0),
+            (Point::new(0, 1), 1.0),
+            (Point::new(-1, -1), 1.4),
+            (Point::new(-1, 1), 1.4),
+            (Point::new(1, -1), 1.4),
+            (Point::new(1, 1), 1.4),
+        ]
+        .iter()
+        {
+            if let Some(idx) = self.valid_exit(location, *delta) {
+                exits.push((idx, cost * self.path_cost(idx)))
+            }
         }
 
         exits
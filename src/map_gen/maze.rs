@@ -0,0 +1,163 @@
+use super::{builder::floor_regions, BuildResult, CustomRegion, Map, MapBuilder, TileType};
+use bracket_lib::prelude::{Point, RandomNumberGenerator};
+
+/*
+ *
+ * maze.rs
+ * -------
+ * Perfect mazes (every cell reachable by exactly one path), with some optional loops.
+ * - Recursive backtracker: long, winding corridors.
+ * - Randomized Prim: lots of short dead ends.
+ *
+ * http://weblog.jamisbuck.org/2011/2/7/maze-generation-algorithm-recap
+ *
+ */
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MazeAlgorithm {
+    Backtracker,
+    Prim,
+}
+
+// Offsets (in cells) to the neighbors of a cell.
+const NEIGHBORS: [(i32, i32); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+
+pub struct Maze {
+    algorithm: MazeAlgorithm,
+    loop_chance: i32, // Chance (%) of opening each remaining wall between two cells.
+    cols: i32,        // Set on each build, from the region.
+    rows: i32,
+}
+
+impl Maze {
+    pub fn new(algorithm: MazeAlgorithm, loop_chance: i32) -> Self {
+        Self {
+            algorithm,
+            loop_chance,
+            cols: 0,
+            rows: 0,
+        }
+    }
+
+    /// Map position of a cell.
+    fn cell_pt(&self, region: &CustomRegion, cx: i32, cy: i32) -> Point {
+        Point::new(region.x1 + 1 + cx * 2, region.y1 + 1 + cy * 2)
+    }
+
+    fn is_valid(&self, map: &Map, region: &CustomRegion, cx: i32, cy: i32) -> bool {
+        if cx < 0 || cy < 0 || cx >= self.cols || cy >= self.rows {
+            return false;
+        }
+        let pt = self.cell_pt(region, cx, cy);
+        region.in_bounds(pt) && map.in_map_bounds(pt)
+    }
+
+    fn cell_idx(&self, cx: i32, cy: i32) -> usize {
+        (cy * self.cols + cx) as usize
+    }
+
+    /// Opens a cell, and the wall between it and the cell it was reached from.
+    fn carve(&self, map: &mut Map, region: &CustomRegion, from: (i32, i32), to: (i32, i32)) {
+        let a = self.cell_pt(region, from.0, from.1);
+        let b = self.cell_pt(region, to.0, to.1);
+        let mid = Point::new((a.x + b.x) / 2, (a.y + b.y) / 2);
+        map.paint_tile(map.idx_pt(mid), TileType::Floor);
+        map.paint_tile(map.idx_pt(b), TileType::Floor);
+    }
+
+    fn generate(&mut self, map: &mut Map, region: &CustomRegion, rng: &mut RandomNumberGenerator) {
+        // Cells are on odd tiles, with a wall between each pair.
+        self.cols = (region.width - 1) / 2;
+        self.rows = (region.height - 1) / 2;
+        let cells: Vec<(i32, i32)> = (0..self.rows)
+            .flat_map(|cy| (0..self.cols).map(move |cx| (cx, cy)))
+            .filter(|(cx, cy)| self.is_valid(map, region, *cx, *cy))
+            .collect();
+        if cells.is_empty() {
+            return;
+        }
+        for pt in region.pos.iter() {
+            if region.in_bounds(*pt) && map.in_map_bounds(*pt) {
+                map.paint_tile(map.idx_pt(*pt), TileType::Wall);
+            }
+        }
+
+        let mut visited = vec![false; (self.cols * self.rows) as usize];
+        let start = cells[rng.range(0, cells.len() as i32) as usize];
+        visited[self.cell_idx(start.0, start.1)] = true;
+        self.carve(map, region, start, start);
+
+        match self.algorithm {
+            MazeAlgorithm::Backtracker => {
+                let mut stack = vec![start];
+                while let Some(&(cx, cy)) = stack.last() {
+                    let next: Vec<(i32, i32)> = NEIGHBORS
+                        .iter()
+                        .map(|(dx, dy)| (cx + dx, cy + dy))
+                        .filter(|(nx, ny)| {
+                            self.is_valid(map, region, *nx, *ny)
+                                && !visited[self.cell_idx(*nx, *ny)]
+                        })
+                        .collect();
+                    if next.is_empty() {
+                        stack.pop();
+                        continue;
+                    }
+                    let n = next[rng.range(0, next.len() as i32) as usize];
+                    visited[self.cell_idx(n.0, n.1)] = true;
+                    self.carve(map, region, (cx, cy), n);
+                    stack.push(n);
+                }
+            }
+            MazeAlgorithm::Prim => {
+                let mut frontier: Vec<((i32, i32), (i32, i32))> = NEIGHBORS
+                    .iter()
+                    .map(|(dx, dy)| (start, (start.0 + dx, start.1 + dy)))
+                    .collect();
+                while !frontier.is_empty() {
+                    let (from, to) =
+                        frontier.swap_remove(rng.range(0, frontier.len() as i32) as usize);
+                    if !self.is_valid(map, region, to.0, to.1) || visited[self.cell_idx(to.0, to.1)]
+                    {
+                        continue;
+                    }
+                    visited[self.cell_idx(to.0, to.1)] = true;
+                    self.carve(map, region, from, to);
+                    for (dx, dy) in NEIGHBORS.iter() {
+                        frontier.push((to, (to.0 + dx, to.1 + dy)));
+                    }
+                }
+            }
+        }
+
+        // Loops make the maze less frustrating to walk around.
+        if self.loop_chance > 0 {
+            for (cx, cy) in cells.iter() {
+                for (nx, ny) in [(cx + 1, *cy), (*cx, cy + 1)].iter() {
+                    if self.is_valid(map, region, *nx, *ny)
+                        && visited[self.cell_idx(*cx, *cy)]
+                        && visited[self.cell_idx(*nx, *ny)]
+                        && rng.range(0, 100) < self.loop_chance
+                    {
+                        self.carve(map, region, (*cx, *cy), (*nx, *ny));
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl MapBuilder for Maze {
+    fn build(
+        &mut self,
+        map: &mut Map,
+        region: &CustomRegion,
+        rng: &mut RandomNumberGenerator,
+    ) -> BuildResult {
+        self.generate(map, region, rng);
+        BuildResult {
+            regions: floor_regions(map, region),
+            ..BuildResult::new()
+        }
+    }
+}
@@ -1,9 +1,9 @@
 use super::{Point, Position};
-use crate::raws::RAWS;
+use crate::raws::{RAWS, VAULTS};
 use crate::spawner::*;
 use bracket_lib::prelude::RandomNumberGenerator;
 use specs::prelude::World;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 pub mod tile;
 pub use tile::{get_tile_function, Tile, TileType};
@@ -11,6 +11,8 @@ pub mod map;
 pub use map::{Map, MapType};
 pub mod common;
 pub use common::*;
+mod builder;
+pub use builder::*;
 mod random_walk;
 use random_walk::*;
 mod cellular_automata;
@@ -22,9 +24,18 @@ use digger::*;
 mod prefab_map;
 use prefab_map::*;
 mod prefab_section;
-//use prefab_section::*;
 mod wfc;
 use wfc::*;
+mod voronoi;
+use voronoi::*;
+mod maze;
+pub use maze::MazeAlgorithm;
+use maze::*;
+mod dla;
+pub use dla::DlaAlgorithm;
+use dla::*;
+mod rooms_corridors;
+use rooms_corridors::*;
 
 mod room;
 use room::*;
@@ -34,6 +45,32 @@ mod custom_region;
 use custom_region::*;
 mod region;
 use region::*;
+mod bench;
+pub use bench::bench_wfc;
+pub mod connectivity;
+use connectivity::*;
+mod placement;
+use placement::*;
+mod vaults;
+use vaults::*;
+mod locks;
+pub use locks::*;
+mod xp_export;
+pub use xp_export::*;
+
+// Times a level is generated again if it can't be made playable.
+const MAX_REGENERATIONS: usize = 5;
+
+/// A generator to use on a region when another one (e.g. WFC) fails.
+pub type FallbackGenerator = fn(&mut MapGenerator, usize, Option<&CustomRegion>);
+
+/// An .xp file the WFC takes its patterns from, with the size of the template drawn in it.
+#[derive(Debug, Clone, Copy)]
+pub struct WfcTemplate {
+    pub path: &'static str,
+    pub w: i32,
+    pub h: i32,
+}
 
 pub struct MapGenerator {
     pub maps: Vec<Map>,
@@ -41,6 +78,10 @@ pub struct MapGenerator {
     pub tunnels: HashSet<Tunnel>,
     pub regions: HashSet<Region>,
     pub wfc_input: Map,
+    pub prefab_spawns: Vec<(usize, String)>,
+    pub doors: Vec<usize>,
+    pub next_lock_id: u32, // Lock ids are never reused, not even on other levels.
+    pub first_level: Option<String>, // A .xp file to load instead of generating the 1st level.
     pub rng: RandomNumberGenerator,
 }
 
@@ -54,6 +95,10 @@ impl MapGenerator {
             tunnels: HashSet::new(),
             regions: HashSet::new(),
             wfc_input: Map::new(80, 60, TileType::Floor, None),
+            prefab_spawns: Vec::new(),
+            doors: Vec::new(),
+            next_lock_id: 1,
+            first_level: None,
             rng: RandomNumberGenerator::new(),
         }
     }
@@ -105,8 +150,28 @@ impl MapGenerator {
                 );
             }
         }
+        // Prefabs and vaults bring their own entities.
+        spawn_list.retain(|(i, _)| self.prefab_spawns.iter().all(|(v, _)| v != i));
+        spawn_list.extend(self.prefab_spawns.iter().cloned());
+        // Computers (e.g. from .xp maps) can be used as terminals.
+        for (i, tile) in self.maps[idx].tiles.iter().enumerate() {
+            if tile.ttype == TileType::Computer {
+                spawn_list.retain(|(s, _)| *s != i);
+                spawn_list.push((i, "Terminal".to_string()));
+            }
+        }
         //println!("Spawn list size: {}", spawn_list.len());
         spawn_from_list(ecs, spawn_list, &self.maps[idx], raws, &mut self.rng);
+
+        // Lock some containers and leave the keys of every lock (doors too) around.
+        let mut lock_ids: Vec<u32> = self.maps[idx].locks.values().map(|l| l.id).collect();
+        lock_ids.extend(lock_containers(
+            ecs,
+            self.maps[idx].depth,
+            &mut self.next_lock_id,
+            &mut self.rng,
+        ));
+        spawn_keys(ecs, &self.maps[idx], lock_ids, raws, &mut self.rng);
     }
 
     pub fn push_map(&mut self, width: i32, height: i32) {
@@ -118,6 +183,32 @@ impl MapGenerator {
         self.rooms.clear();
         self.tunnels.clear();
         self.regions.clear();
+        self.prefab_spawns.clear();
+        self.doors.clear();
+    }
+
+    /// Keeps what a builder made, for spawning entities later.
+    pub fn apply_build(&mut self, result: BuildResult) {
+        self.rooms.extend(result.rooms);
+        self.tunnels.extend(result.tunnels);
+        self.regions
+            .extend(result.regions.into_iter().filter(|r| !r.is_empty()));
+        self.doors.extend(result.doors);
+        self.prefab_spawns.extend(result.spawns);
+    }
+
+    /// Runs a builder (or a BuilderChain) on a region of a map, or on the whole map if there's
+    /// no region.
+    pub fn run_builder(
+        &mut self,
+        idx: usize,
+        region: Option<&CustomRegion>,
+        builder: &mut dyn MapBuilder,
+    ) {
+        let map_region = &self.maps[idx].get_region();
+        let reg = region.unwrap_or(map_region);
+        let result = builder.build(&mut self.maps[idx], reg, &mut self.rng);
+        self.apply_build(result);
     }
 
     pub fn gen_map(&mut self, idx: usize) {
@@ -129,7 +220,6 @@ impl MapGenerator {
         //self.gen_digger(idx, None);
         //self.gen_forest(idx, Some(region));
         //self.gen_forest(idx, None);
-        //HOUSE01.generate(Point::new(20, 20), &mut self.maps[idx]);
         //self.gen_tight_cave(idx, Some(region));
         //self.gen_tight_cave(idx, None);
         //self.gen_cave(idx, None);
@@ -147,12 +237,50 @@ impl MapGenerator {
         self.gen_wfc(idx, Some(region), "../rex_resources/wfc_6x6_internal.xp", 9, 9, 3);
         */
 
-        self.generate_next_level(idx);
-        //self.gen_wfc(idx, None, "../rex_resources/wfc_15x15.xp", 15, 15, 5);
-
-        self.maps[idx].add_borders(TileType::InvisibleWall);
-        //self.maps[idx].add_borders(TileType::Wall);
+        for attempt in 1..=MAX_REGENERATIONS {
+            self.generate_next_level(idx);
+            //self.gen_wfc(idx, None, "../rex_resources/wfc_15x15.xp", 15, 15, 5);
+
+            self.maps[idx].add_borders(TileType::InvisibleWall);
+            //self.maps[idx].add_borders(TileType::Wall);
+
+            let auto_spawn = place_spawn(&mut self.maps[idx], &mut self.rng);
+            match ensure_connectivity(&mut self.maps[idx]) {
+                Ok(_) => {
+                    let vaults = VAULTS
+                        .lock()
+                        .unwrap()
+                        .get_vaults(self.maps[idx].get_maptype(), idx as i32 + 1);
+                    let occupied: Vec<usize> = self.prefab_spawns.iter().map(|(i, _)| *i).collect();
+                    let vault_spawns =
+                        place_vaults(&mut self.maps[idx], &vaults, &occupied, &mut self.rng);
+                    self.prefab_spawns.extend(vault_spawns);
+                    place_exit(&mut self.maps[idx], auto_spawn);
+                    break;
+                }
+                Err(_) if attempt < MAX_REGENERATIONS => {
+                    let (w, h) = (self.maps[idx].width, self.maps[idx].height);
+                    self.maps[idx] = Map::new(w, h, TileType::Wall, None);
+                }
+                // Out of attempts: the last map is kept as it is, but it still needs an exit.
+                Err(e) => {
+                    println!(
+                        "Map {} isn't playable ({:?}) after {} attempts!",
+                        idx, e, attempt
+                    );
+                    place_exit(&mut self.maps[idx], auto_spawn);
+                }
+            }
+        }
         self.maps[idx].pretty_walls();
+        self.maps[idx].set_depth(idx as i32 + 1);
+        let doors = self.doors.clone();
+        lock_doors(
+            &mut self.maps[idx],
+            &doors,
+            &mut self.next_lock_id,
+            &mut self.rng,
+        );
 
         //add_vegetation(&mut self.maps[idx], region, false);
 
@@ -162,126 +290,192 @@ impl MapGenerator {
     pub fn generate_next_level(&mut self, idx: usize) {
         self.clear_regions_generator();
 
-        /* Time tests
-        let chance1 = self.rng.range(0, 2);
-        let d1: bool = if chance1 == 0 { false } else { true };
-        let chance2 = self.rng.range(0, 2);
-        let d2: bool = if chance2 == 0 { false } else { true };
-
-        use std::time::{Instant};
-        let start = Instant::now();
-
-        self.test(idx, d1, d2);
-
-        let duration = start.elapsed();
-        println!("Time elapsed in expensive_function() is: {:?}", duration);
-
-        use std::fs::OpenOptions;
-        use std::io::prelude::*;
-
-        let mut file = OpenOptions::new()
-            .write(true)
-            .append(true)
-            .open("times.csv")
-            .unwrap();
-
-        if let Err(e) = writeln!(file, "WFC_01,{:?}", duration) {
-            eprintln!("Couldn't write to file: {}", e);
-        }
-        */
-
         //self.wfc_test(idx);
-        self.level_01(idx);
         //self.maps[idx].set_maptype(MapType::Ruins);
         //self.maps[idx].set_spawn(Position::new(8, 16));
         //self.wfc_01(idx);
 
-        /*
         match idx {
-            0 => self.level_00(idx),
-            1 => self.level_01(idx),
-            _ => println!("Oops!"),
+            0 => match self.first_level.clone() {
+                Some(path) => self.level_from_file(idx, &path),
+                None => self.level_01(idx),
+            },
+            1 => self.level_02(idx),
+            2 => self.level_03(idx),
+            _ if idx % 2 == 1 => self.level_04(idx),
+            _ => self.level_05(idx),
         }
-        */
     }
 
     pub fn wfc_test(&mut self, idx: usize) {
         self.maps[idx].set_maptype(MapType::Ruins);
-        self.maps[idx].set_spawn(Position::new(8, 16));
-
-        let reg1 = &CustomRegion::new_rect(0, 0, self.maps[idx].width, self.maps[idx].height);
-
-        self.gen_wfc(idx, Some(reg1), "resources/wfc_9x9_1.xp", 9, 9, 6, false);
-    }
-
-    /*
-    pub fn test(&mut self, idx: usize, d1: bool, d2: bool) {
-        self.maps[idx].set_maptype(MapType::Ruins);
-        self.maps[idx].set_spawn(Position::new(8, 16));
 
         let reg1 = &CustomRegion::new_rect(0, 0, self.maps[idx].width, self.maps[idx].height);
 
-        self.wfc_01(idx);
-
         self.gen_wfc(
             idx,
             Some(reg1),
-            "resources/wfc_20x20_2.xp",
-            20,
-            20,
-            20,
+            WfcTemplate {
+                path: "resources/wfc_9x9_1.xp",
+                w: 9,
+                h: 9,
+            },
+            6,
+            false,
+            Some(MapGenerator::gen_bsp_ruin),
         );
-
-
-        let mut random_walker = RandomWalker::new(reg1, 0.25, false, false);
-        random_walker.generate(&mut self.maps[idx], &mut self.rng);
-
-        // n_iterations, n_walls_rule, min_cave_size, open_halls, dry_caves
-        //let mut cell_automata = CellularAutomata::new(reg1, 12, 5, 10, false, true);
-        //cell_automata.generate(&mut self.maps[idx]);
-
-        //make_lake(&mut self.maps[idx], reg1, TileType::ShallowWater, 200);
-
-        let mut cell_automata2 = CellularAutomata::new(reg1, 1, 5, 15, true, true);
-        cell_automata2.generate(&mut self.maps[idx]);
-
-        let mut all_regions = get_all_regions(&self.maps[idx], &self.maps[idx].get_region());
-        all_regions.sort_by(|a, b| self.maps[idx].idx_pos(a[0]).x.cmp(&self.maps[idx].idx_pos(b[0]).x));
-        connect_regions(&mut self.maps[idx], all_regions, TileType::Floor, true);
     }
-    */
 
     pub fn level_00(&mut self, idx: usize) {
         self.maps[idx].set_maptype(MapType::Ruins);
-        self.maps[idx].set_spawn(Position::new(8, 16));
 
-        self.gen_prefab_map(idx, "resources/level01_80x60.xp");
+        // Spawn, exit, entities and spawn areas come from the .xp layers.
+        let tags = self.gen_prefab_map(idx, "resources/level01_80x60.xp");
         let reg = &CustomRegion::new_rect(0, 0, self.maps[idx].width, self.maps[idx].height);
         add_vegetation(&mut self.maps[idx], reg, false);
 
-        self.regions.insert(
-            get_all_regions(&self.maps[idx], &reg)
-                .iter()
-                .flat_map(|arr| arr.iter())
-                .map(|e| *e)
-                .collect::<Region>(),
-        );
+        if tags.is_empty() {
+            self.regions.insert(
+                get_all_regions(&self.maps[idx], &reg)
+                    .iter()
+                    .flat_map(|arr| arr.iter())
+                    .map(|e| *e)
+                    .collect::<Region>(),
+            );
+        }
+    }
 
-        let mut i;
-        for y in 0..self.maps[idx].height {
-            i = self.maps[idx].idx(self.maps[idx].width - 2, y);
-            if self.maps[idx].is_walkable(i) {
-                self.maps[idx].paint_tile(i, TileType::Exit);
+    /// A map saved with --export-xp (and maybe edited in REXPaint), with its own entities.
+    pub fn level_from_file(&mut self, idx: usize, path: &str) {
+        self.maps[idx].set_maptype(MapType::Ruins);
+        match PrefabMap::from_file(path) {
+            Ok(mut prefab) => self.run_builder(idx, None, &mut prefab),
+            Err(e) => {
+                println!("Couldn't read {}: {}", path, e);
+                self.level_01(idx);
             }
         }
     }
 
     pub fn level_01(&mut self, idx: usize) {
-        self.maps[idx].set_maptype(MapType::Ruins);
-        self.maps[idx].set_spawn(Position::new(1, 25));
+        self.maps[idx].set_maptype(MapType::Forest);
         self.forest_bsp_ruin(idx);
     }
 
+    /// Collapsed halls grown with the overlapping WFC, so they keep the "texture" of the input
+    /// instead of being copies of its pieces.
+    pub fn level_02(&mut self, idx: usize) {
+        self.maps[idx].set_maptype(MapType::Ruins);
+        let (w, h) = (self.maps[idx].width, self.maps[idx].height);
+        let center = Point::new(w / 2, h / 2);
+
+        let mut wfc = WaveFunctionCollapse::new(3, false);
+        wfc.set_overlapping(true);
+        wfc.set_rotations(true);
+        // The halls of the input run off its borders, so they're continued on the other side.
+        wfc.set_periodic_input(true);
+        wfc.set_tile_weight(TileType::Floor, 2.0);
+        // A whole map needs a lot more backtracking than a room.
+        wfc.set_limits(3, 200);
+        // A clearing in the middle and some open ground on each quarter of the map, so the
+        // halls spread over all of it.
+        wfc.add_pin(Pin::Pattern(center, vec![TileType::Floor; 9]));
+        for (x, y) in [
+            (w / 4, h / 4),
+            (w * 3 / 4, h / 4),
+            (w / 4, h * 3 / 4),
+            (w * 3 / 4, h * 3 / 4),
+        ] {
+            wfc.add_pin(Pin::Tile(Point::new(x, y), TileType::Floor));
+        }
+        let outcome = self.run_wfc(
+            idx,
+            None,
+            WfcTemplate {
+                path: "resources/wfc_20x20_6.xp",
+                w: 20,
+                h: 20,
+            },
+            &mut wfc,
+            Some(MapGenerator::gen_bsp_ruin),
+        );
+
+        let reg = self.maps[idx].get_region();
+        if !outcome.fallback_used {
+            self.regions.extend(floor_regions(&self.maps[idx], &reg));
+        }
+        let all_regions = get_all_regions(&self.maps[idx], &reg);
+        connect_regions(&mut self.maps[idx], all_regions, TileType::Floor, false);
+        add_vegetation(&mut self.maps[idx], &reg, false);
+    }
+
+    /// A ruined town: walled districts on the top and the remains of a building below them.
+    pub fn level_03(&mut self, idx: usize) {
+        self.maps[idx].set_maptype(MapType::Ruins);
+        let (w, h) = (self.maps[idx].width, self.maps[idx].height);
+        let region_top = &CustomRegion::new_rect(0, 0, w, h / 2);
+        let region_bottom = &CustomRegion::new_rect(0, h / 2, w, h - h / 2);
+        self.gen_voronoi(idx, Some(region_top), 10);
+        self.gen_rooms_corridors(idx, Some(region_bottom));
+
+        let all_regions = get_all_regions(&self.maps[idx], &self.maps[idx].get_region());
+        connect_regions(&mut self.maps[idx], all_regions, TileType::Floor, false);
+    }
+
+    /// Caves grown by aggregation, with the maze of some old tunnels in the middle of them.
+    pub fn level_04(&mut self, idx: usize) {
+        self.maps[idx].set_maptype(MapType::Cave);
+        let (w, h) = (self.maps[idx].width, self.maps[idx].height);
+        let algorithm = match self.rng.range(0, 3) {
+            0 => DlaAlgorithm::WalkInwards,
+            1 => DlaAlgorithm::WalkOutwards,
+            _ => DlaAlgorithm::CentralAttractor,
+        };
+        self.gen_dla(idx, None, algorithm);
+
+        let region_maze = &CustomRegion::new_rect(w / 2 - 15, h / 2 - 10, 31, 21);
+        let algorithm = if self.rng.range(0, 2) == 0 {
+            MazeAlgorithm::Backtracker
+        } else {
+            MazeAlgorithm::Prim
+        };
+        self.gen_maze(idx, Some(region_maze), algorithm);
+
+        let all_regions = get_all_regions(&self.maps[idx], &self.maps[idx].get_region());
+        connect_regions(&mut self.maps[idx], all_regions, TileType::Floor, true);
+    }
+
+    /// Deep caves, with the ruins of something older in the middle being taken back by them.
+    pub fn level_05(&mut self, idx: usize) {
+        self.maps[idx].set_maptype(MapType::Cave);
+        let (w, h) = (self.maps[idx].width, self.maps[idx].height);
+
+        // n_iterations, n_walls_rule, min_cave_size, open_halls, dry_caves
+        let mut caves = BuilderChain::new()
+            .with(RandomWalker::new(0.5, true, true))
+            .with(CellularAutomata::new(12, 5, 20, false, false))
+            .with(CellularAutomata::new(1, 4, 5, true, true));
+        self.run_builder(idx, None, &mut caves);
+
+        let mut input = PrefabMap::new("resources/wfc_9x9_1.xp");
+        input.generate(&mut self.wfc_input);
+        input.repeat_template(&mut self.wfc_input);
+        let mut wfc = WaveFunctionCollapse::new(3, false);
+        wfc.set_input(&self.wfc_input, 9, 9);
+        let mut ruins = BuilderChain::new()
+            .with(wfc)
+            .with(CellularAutomata::new(2, 3, 10, true, false));
+        let region_ruins = &CustomRegion::new_rect(w / 2 - 15, h / 2 - 9, 30, 18);
+        self.run_builder(idx, Some(region_ruins), &mut ruins);
+
+        // The ruins were built over some of the caves.
+        let reg = self.maps[idx].get_region();
+        self.regions.clear();
+        self.regions.extend(floor_regions(&self.maps[idx], &reg));
+        let all_regions = get_all_regions(&self.maps[idx], &reg);
+        connect_regions(&mut self.maps[idx], all_regions, TileType::Floor, true);
+    }
+
     pub fn forest_bsp_ruin(&mut self, idx: usize) {
         let region_top = &CustomRegion::new_rect(0, 0, self.maps[idx].width, 25);
         let region_middle = &CustomRegion::new_rect(0, 20, self.maps[idx].width, 15);
@@ -293,7 +487,19 @@ impl MapGenerator {
             if self.rng.range(0, 4) > 1 || (room.width() >= 6 && room.height() >= 6) {
                 let room_reg =
                     &CustomRegion::new_rect(room.x1, room.y1, room.width(), room.height());
-                self.gen_wfc(idx, Some(room_reg), "resources/wfc_6x6.xp", 9, 9, 2, false);
+                // If it fails, the room just stays as it is.
+                self.gen_wfc(
+                    idx,
+                    Some(room_reg),
+                    WfcTemplate {
+                        path: "resources/wfc_6x6.xp",
+                        w: 9,
+                        h: 9,
+                    },
+                    2,
+                    false,
+                    None,
+                );
             }
         }
 
@@ -314,11 +520,14 @@ impl MapGenerator {
         self.gen_wfc(
             idx,
             Some(region_left),
-            "resources/wfc_20x20_5.xp",
-            20,
-            20,
+            WfcTemplate {
+                path: "resources/wfc_20x20_5.xp",
+                w: 20,
+                h: 20,
+            },
             10,
             true,
+            Some(MapGenerator::gen_cave),
         );
         if self.rng.range(0, 2) < 1 {
             self.gen_digger(idx, Some(region_middle));
@@ -333,11 +542,14 @@ impl MapGenerator {
                 self.gen_wfc(
                     idx,
                     Some(room_reg),
-                    "resources/small_6x6.xp",
-                    6,
-                    6,
+                    WfcTemplate {
+                        path: "resources/small_6x6.xp",
+                        w: 6,
+                        h: 6,
+                    },
                     2,
                     false,
+                    None,
                 );
             }
         }
@@ -357,12 +569,26 @@ impl MapGenerator {
         &mut self,
         idx: usize,
         region: Option<&CustomRegion>,
-        template: &'static str,
-        w: i32,
-        h: i32,
+        template: WfcTemplate,
         tile_size: i32,
         mix_match: bool,
-    ) {
+        fallback: Option<FallbackGenerator>,
+    ) -> WfcOutcome {
+        let mut wfc = WaveFunctionCollapse::new(tile_size, mix_match);
+        self.run_wfc(idx, region, template, &mut wfc, fallback)
+    }
+
+    /// Runs an already configured WFC (model, pins, weights...) on a region, taking the patterns
+    /// from a template. The tiles on the border of the region that lead outside are pinned as
+    /// passable.
+    pub fn run_wfc(
+        &mut self,
+        idx: usize,
+        region: Option<&CustomRegion>,
+        template: WfcTemplate,
+        wfc: &mut WaveFunctionCollapse,
+        fallback: Option<FallbackGenerator>,
+    ) -> WfcOutcome {
         let map_region = &self.maps[idx].get_region();
         let reg = if region != None {
             region.unwrap()
@@ -370,16 +596,66 @@ impl MapGenerator {
             map_region
         };
 
-        let mut input = PrefabMap::new(template);
+        let mut input = PrefabMap::new(template.path);
         input.generate(&mut self.wfc_input);
         input.repeat_template(&mut self.wfc_input);
         //input.repeat_template_cont(&mut self.wfc_input);
         //input.generate(&mut self.maps[idx]);
         //input.repeat_template_cont(&mut self.maps[idx]);
         //input.repeat_template(&mut self.maps[idx]);
-        let mut wfc = WaveFunctionCollapse::new(tile_size, &reg, mix_match);
+        for pin in self.wfc_border_pins(idx, reg) {
+            wfc.add_pin(pin);
+        }
         // (output, input taken, template width, template height, rng)
-        wfc.generate(&mut self.maps[idx], &self.wfc_input, w, h, &mut self.rng);
+        let mut outcome = wfc.generate(
+            &mut self.maps[idx],
+            reg,
+            &self.wfc_input,
+            template.w,
+            template.h,
+            &mut self.rng,
+        );
+
+        if !outcome.success {
+            if let Some(fallback) = fallback {
+                fallback(self, idx, Some(reg));
+                outcome.fallback_used = true;
+            }
+        }
+        outcome
+    }
+
+    /// Pins as passable the tiles on the border of a (rectangular) region that lead to something
+    /// walkable outside of it, so the WFC output stays connected to its surroundings.
+    fn wfc_border_pins(&self, idx: usize, region: &CustomRegion) -> Vec<Pin> {
+        let map = &self.maps[idx];
+        let mut pins = Vec::new();
+        if region.circular {
+            return pins;
+        }
+        for y in region.y1..region.y2 {
+            for x in region.x1..region.x2 {
+                if x != region.x1 && x != region.x2 - 1 && y != region.y1 && y != region.y2 - 1 {
+                    continue;
+                }
+                let pt = Point::new(x, y);
+                let opening = [(1, 0), (-1, 0), (0, 1), (0, -1)].iter().any(|(dx, dy)| {
+                    let out = Point::new(x + dx, y + dy);
+                    let outside = out.x < region.x1
+                        || out.x >= region.x2
+                        || out.y < region.y1
+                        || out.y >= region.y2;
+                    outside && map.in_map_bounds(out) && {
+                        let out_idx = map.idx(out.x, out.y);
+                        map.is_walkable(out_idx) || map.is_door(out_idx)
+                    }
+                });
+                if opening {
+                    pins.push(Pin::Passable(pt));
+                }
+            }
+        }
+        pins
     }
 
     pub fn gen_forest(&mut self, idx: usize, region: Option<&CustomRegion>) {
@@ -393,15 +669,15 @@ impl MapGenerator {
         make_chaotic(&mut self.maps[idx], reg, 50);
 
         // n_iterations, n_walls_rule, min_cave_size, open_halls, dry_caves
-        let mut cell_automata = CellularAutomata::new(reg, 12, 5, 20, true, true);
-        cell_automata.generate(&mut self.maps[idx]);
+        let mut cell_automata = CellularAutomata::new(12, 5, 20, true, true);
+        cell_automata.build(&mut self.maps[idx], reg, &mut self.rng);
 
         // Make two big lakes.
         make_lake(&mut self.maps[idx], reg, TileType::ShallowWater, 600);
         make_lake(&mut self.maps[idx], reg, TileType::ShallowWater, 600);
 
-        let mut cell_automata2 = CellularAutomata::new(reg, 1, 3, 20, true, true);
-        cell_automata2.generate(&mut self.maps[idx]);
+        let mut cell_automata2 = CellularAutomata::new(1, 3, 20, true, true);
+        cell_automata2.build(&mut self.maps[idx], reg, &mut self.rng);
 
         self.regions.insert(
             get_all_regions(&self.maps[idx], &reg)
@@ -427,16 +703,16 @@ impl MapGenerator {
         };
 
         // floor_percent, grouped_walkers, diagonals
-        let mut walker = RandomWalker::new(reg, 0.55, false, d);
-        walker.generate(&mut self.maps[idx], &mut self.rng);
+        let mut walker = RandomWalker::new(0.55, false, d);
+        walker.build(&mut self.maps[idx], reg, &mut self.rng);
 
         // n_iterations, n_walls_rule, min_cave_size, open_halls, dry_caves
-        let mut cell_automata = CellularAutomata::new(reg, 12, 5, 20, false, false);
-        cell_automata.generate(&mut self.maps[idx]);
+        let mut cell_automata = CellularAutomata::new(12, 5, 20, false, false);
+        cell_automata.build(&mut self.maps[idx], reg, &mut self.rng);
         make_lake(&mut self.maps[idx], reg, TileType::ShallowWater, 200);
 
-        let mut cell_automata2 = CellularAutomata::new(reg, 1, 4, 5, true, true);
-        cell_automata2.generate(&mut self.maps[idx]);
+        let mut cell_automata2 = CellularAutomata::new(1, 4, 5, true, true);
+        cell_automata2.build(&mut self.maps[idx], reg, &mut self.rng);
 
         self.regions.insert(
             get_all_regions(&self.maps[idx], &reg)
@@ -463,18 +739,18 @@ impl MapGenerator {
         };
 
         // floor_percent, grouped_walkers, diagonals
-        let mut walker = RandomWalker::new(reg, 0.60, true, d);
-        walker.generate(&mut self.maps[idx], &mut self.rng);
+        let mut walker = RandomWalker::new(0.60, true, d);
+        walker.build(&mut self.maps[idx], reg, &mut self.rng);
 
         // n_iterations, n_walls_rule, min_cave_size, open_halls, dry_caves
-        let mut cell_automata = CellularAutomata::new(reg, 12, 5, 5, false, true);
-        cell_automata.generate(&mut self.maps[idx]);
+        let mut cell_automata = CellularAutomata::new(12, 5, 5, false, true);
+        cell_automata.build(&mut self.maps[idx], reg, &mut self.rng);
 
         chance = self.rng.range(0, 3);
         let rule = if chance <= 1 { 5 } else { 2 };
 
-        let mut cell_automata2 = CellularAutomata::new(reg, 5, rule, 5, true, true);
-        cell_automata2.generate(&mut self.maps[idx]);
+        let mut cell_automata2 = CellularAutomata::new(5, rule, 5, true, true);
+        cell_automata2.build(&mut self.maps[idx], reg, &mut self.rng);
 
         self.regions.insert(
             get_all_regions(&self.maps[idx], &reg)
@@ -490,7 +766,7 @@ impl MapGenerator {
     }
 
     pub fn gen_bsp(&mut self, idx: usize, region: Option<&CustomRegion>) {
-        let mut chance = self.rng.range(0, 5);
+        let chance = self.rng.range(0, 5);
         let c = if chance < 4 { false } else { true };
 
         // Works properly only for RECTANGULAR/SQUARE regions.
@@ -501,39 +777,12 @@ impl MapGenerator {
             map_region
         };
 
-        let mut bsp = BSPDungeon::new(reg, 10, c);
-        bsp.generate(&mut self.maps[idx], &mut self.rng);
-
-        chance = self.rng.range(0, 3);
-        if c == false {
-            match chance {
-                // With smaller block sizes (e.g. 5), tunnels_left and tunnels_down become bad.
-                0 => {
-                    bsp.build_tunnels_left(&mut self.maps[idx], &mut self.rng);
-                    println!("LEFT");
-                }
-                1 => {
-                    bsp.build_tunnels_down(&mut self.maps[idx], &mut self.rng);
-                    println!("DOWN");
-                }
-                _ => {
-                    bsp.build_tunnels(&mut self.maps[idx], &mut self.rng);
-                    println!("RANDOM");
-                }
-            }
-        } else {
-            match chance {
-                // Tunnels get too clutered when they're ordered and we have "big connected rooms".
-                _ => {
-                    bsp.build_tunnels(&mut self.maps[idx], &mut self.rng);
-                }
-            }
-        }
-        let rooms = bsp.get_rooms();
-        add_doors(&mut self.maps[idx], rooms.as_ref(), 30, &mut self.rng);
-        for room in rooms.iter() {
-            self.rooms.push(*room);
-        }
+        let mut bsp = BSPDungeon::new(10, c);
+        let mut result = bsp.build(&mut self.maps[idx], reg, &mut self.rng);
+        // There are too many tunnels for each one to get its own mobs.
+        result.tunnels.clear();
+        result.doors = add_doors(&mut self.maps[idx], &result.rooms, 30, &mut self.rng);
+        self.apply_build(result);
     }
 
     pub fn gen_bsp_ruin(&mut self, idx: usize, region: Option<&CustomRegion>) {
@@ -547,8 +796,8 @@ impl MapGenerator {
         };
 
         make_lake(&mut self.maps[idx], reg, TileType::ShallowWater, 100);
-        let mut cell_automata = CellularAutomata::new(reg, 2, 3, 10, true, false);
-        cell_automata.generate(&mut self.maps[idx]);
+        let mut cell_automata = CellularAutomata::new(2, 3, 10, true, false);
+        cell_automata.build(&mut self.maps[idx], reg, &mut self.rng);
         add_vegetation(&mut self.maps[idx], reg, false);
     }
 
@@ -581,14 +830,10 @@ impl MapGenerator {
         // Biggers rooms are more aesthetically pleasing, but require a much greater map (from
         // 100x100 to 200x200) to have more features.
         // (min_size, max_size, num_features (approx)
-        let mut digger = Digger::new(reg, 10, 15, 30);
-        digger.generate(&mut self.maps[idx], &mut self.rng);
-        //self.rooms = digger.get_rooms();
-        let rooms = digger.get_rooms();
-        add_doors(&mut self.maps[idx], rooms.as_ref(), 30, &mut self.rng);
-        for room in rooms.iter() {
-            self.rooms.push(*room);
-        }
+        let mut digger = Digger::new(10, 15, 30);
+        let mut result = digger.build(&mut self.maps[idx], reg, &mut self.rng);
+        result.doors = add_doors(&mut self.maps[idx], &result.rooms, 30, &mut self.rng);
+        self.apply_build(result);
     }
 
     pub fn gen_digger_inverted(&mut self, idx: usize, region: Option<&CustomRegion>) {
@@ -602,16 +847,67 @@ impl MapGenerator {
         };
 
         // n_iterations, n_walls_rule, min_cave_size, open_halls, dry_caves
-        let mut cell_automata = CellularAutomata::new(reg, 3, 7, 10, false, false);
-        cell_automata.generate(&mut self.maps[idx]);
+        let mut cell_automata = CellularAutomata::new(3, 7, 10, false, false);
+        cell_automata.build(&mut self.maps[idx], reg, &mut self.rng);
         if self.rng.range(0, 2) < 1 {
             add_vegetation(&mut self.maps[idx], reg, false);
         }
     }
 
-    pub fn gen_prefab_map(&mut self, idx: usize, template: &'static str) {
-        let mut map = PrefabMap::new(template);
-        map.generate(&mut self.maps[idx]);
+    /// Walled districts (e.g. town quarters), connected by doors.
+    pub fn gen_voronoi(&mut self, idx: usize, region: Option<&CustomRegion>, n_seeds: usize) {
+        let mut voronoi = VoronoiDistricts::new(n_seeds, self.rng.range(0, 2) == 0);
+        self.run_builder(idx, region, &mut voronoi);
+    }
+
+    pub fn gen_maze(
+        &mut self,
+        idx: usize,
+        region: Option<&CustomRegion>,
+        algorithm: MazeAlgorithm,
+    ) {
+        // algorithm, loop_chance
+        let mut maze = Maze::new(algorithm, 10);
+        self.run_builder(idx, region, &mut maze);
+    }
+
+    pub fn gen_dla(&mut self, idx: usize, region: Option<&CustomRegion>, algorithm: DlaAlgorithm) {
+        // algorithm, percent, brush_size
+        let mut dla = DiffusionLimitedAggregation::new(algorithm, 0.3, self.rng.range(1, 3));
+        self.run_builder(idx, region, &mut dla);
+
+        if self.rng.range(0, 3) < 1 {
+            let map_region = &self.maps[idx].get_region();
+            add_vegetation(&mut self.maps[idx], region.unwrap_or(map_region), false);
+        }
+    }
+
+    /// Rooms and corridors, with vaults inside some of the rooms.
+    pub fn gen_rooms_corridors(&mut self, idx: usize, region: Option<&CustomRegion>) {
+        let map_region = &self.maps[idx].get_region();
+        let reg = region.unwrap_or(map_region);
+        let vaults = match self.maps[idx].maptype {
+            Some(maptype) => VAULTS.lock().unwrap().get_vaults(maptype, idx as i32 + 1),
+            None => Vec::new(),
+        };
+
+        // max_rooms, min_size, max_size, prefab_chance, vaults
+        let mut gen = RoomsAndCorridors::new(30, 6, 16, 40, vaults);
+        let mut result = gen.build(&mut self.maps[idx], reg, &mut self.rng);
+        result.doors = add_doors(&mut self.maps[idx], &result.rooms, 30, &mut self.rng);
+        self.apply_build(result);
+    }
+
+    /// Generates a map from a .xp file, with the spawn, exit and entities of its other layers.
+    /// Returns the tagged regions.
+    pub fn gen_prefab_map(
+        &mut self,
+        idx: usize,
+        template: &'static str,
+    ) -> HashMap<String, Region> {
+        let mut prefab = PrefabMap::new(template);
+        self.run_builder(idx, None, &mut prefab);
+        prefab.tags
     }
 
     pub fn get_map(&self, idx: usize) -> Map {
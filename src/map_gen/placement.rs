@@ -0,0 +1,95 @@
+use super::{connectivity::is_passable, Map, Point, TileType};
+use bracket_lib::prelude::RandomNumberGenerator;
+use std::collections::VecDeque;
+
+/*
+ *
+ * placement.rs
+ * ------------
+ * Automatic placement of the spawn and the exit, for generators that don't choose them.
+ * The exit goes to the walkable tile farthest (walking) from the spawn.
+ *
+ */
+
+/// Whether the spawn or the exit can be placed on a tile.
+fn is_good_spot(map: &Map, idx: usize) -> bool {
+    map.is_walkable(idx) && !map.is_water(idx)
+}
+
+/// Walking distances (4 directions) from a point to every tile; None if unreachable.
+/// All moves cost the same, so a BFS gives the same result as Dijkstra.
+pub fn distance_map(map: &Map, start: Point) -> Vec<Option<u32>> {
+    let mut dists: Vec<Option<u32>> = vec![None; map.size as usize];
+    if !map.in_map_bounds(start) {
+        return dists;
+    }
+    let mut queue: VecDeque<Point> = VecDeque::new();
+    dists[map.idx_pt(start)] = Some(0);
+    queue.push_back(start);
+
+    while let Some(pt) = queue.pop_front() {
+        let d = dists[map.idx_pt(pt)].unwrap();
+        for dir in [(1, 0), (-1, 0), (0, 1), (0, -1)].iter() {
+            let next = Point::new(pt.x + dir.0, pt.y + dir.1);
+            if !map.in_map_bounds(next) {
+                continue;
+            }
+            let idx = map.idx_pt(next);
+            if dists[idx].is_none() && is_passable(map, idx) {
+                dists[idx] = Some(d + 1);
+                queue.push_back(next);
+            }
+        }
+    }
+    dists
+}
+
+/// Returns the good spot farthest from a point.
+fn farthest_spot(map: &Map, from: Point) -> Option<Point> {
+    let dists = distance_map(map, from);
+    (0..map.size as usize)
+        .filter(|idx| is_good_spot(map, *idx))
+        .filter_map(|idx| dists[idx].map(|d| (idx, d)))
+        .max_by_key(|(_, d)| *d)
+        .map(|(idx, _)| map.idx_pos(idx))
+}
+
+/// Picks a random walkable tile as the spawn if the generator didn't choose one (or chose a bad
+/// one). Returns true if it did.
+pub fn place_spawn(map: &mut Map, rng: &mut RandomNumberGenerator) -> bool {
+    let spawn = map.spawn_point;
+    if map.in_map_bounds(spawn) && is_good_spot(map, map.idx_pt(spawn)) {
+        return false;
+    }
+    let spots: Vec<usize> = (0..map.size as usize)
+        .filter(|idx| is_good_spot(map, *idx))
+        .collect();
+    if spots.is_empty() {
+        return false;
+    }
+    let idx = spots[rng.range(0, spots.len() as i32) as usize];
+    let pt = map.idx_pos(idx);
+    map.set_spawn(pt);
+    true
+}
+
+/// Places the exit on the walkable tile farthest from the spawn, if the map has none yet.
+/// With `move_spawn`, the spawn is first moved to the tile farthest from where it is, so both
+/// end up at opposite ends of the map.
+pub fn place_exit(map: &mut Map, move_spawn: bool) {
+    if move_spawn {
+        if let Some(spawn) = farthest_spot(map, map.spawn_point) {
+            map.set_spawn(spawn);
+        }
+    }
+    if map.in_map_bounds(map.exit_point) {
+        return;
+    }
+    if let Some(exit) = farthest_spot(map, map.spawn_point) {
+        if exit != map.spawn_point {
+            let idx = map.idx_pt(exit);
+            map.paint_tile(idx, TileType::Exit);
+            map.set_exit(exit);
+        }
+    }
+}
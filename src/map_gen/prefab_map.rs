@@ -1,49 +1,151 @@
-use super::{Map, Point, Tile};
-use bracket_lib::prelude::{to_char, XpFile};
+use super::{BuildResult, CustomRegion, Map, MapBuilder, Point, Region, Tile, TileType};
+use crate::raws::{MetaTag, REX_LEGEND};
+use bracket_lib::prelude::{to_char, RandomNumberGenerator, XpCell, XpFile, XpLayer};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io;
 
 /*
  *
  * prefab_map.rs
  * -------------
  * Generates a map based on a prefabricated .xp map.
+ * The 1st layer has the terrain, the 2nd one the entities and the 3rd one the metadata (see
+ * resources/raws/rex_legend.ron).
  * Based on the examples by TheBracket.
  *
  */
 
+/// Where the .xp map comes from.
+enum Template {
+    Resource(&'static str), // Embedded in the game (see rexloader.rs).
+    File(XpFile),           // Read from the disk, e.g. a map saved with --export-xp.
+}
+
 pub struct PrefabMap {
-    template: &'static str,
+    template: Template,
+    pub entities: Vec<(usize, String)>, // (map index, raw name)
+    pub spawn: Option<Point>,
+    pub exit: Option<Point>,
+    pub tags: HashMap<String, Region>,
+}
+
+/// Whether a cell was left empty in REXPaint.
+fn is_blank(cell: &XpCell) -> bool {
+    cell.ch == 0 || cell.ch == 32
+}
+
+/// Reads the entities of a layer with the glyph + colour legend, as (position, raw name).
+pub fn read_entity_layer(layer: &XpLayer) -> Vec<(Point, String)> {
+    let legend = REX_LEGEND.lock().unwrap();
+    let mut entities = Vec::new();
+    for y in 0..layer.height {
+        for x in 0..layer.width {
+            let cell = layer.get(x, y).unwrap();
+            if is_blank(cell) {
+                continue;
+            }
+            let glyph = to_char(cell.ch as u8);
+            match legend.get_entity(glyph, (cell.fg.r, cell.fg.g, cell.fg.b)) {
+                Some(name) => entities.push((Point::new(x, y), name.to_string())),
+                None => println!("No entity for glyph {} at ({}, {}).", glyph, x, y),
+            }
+        }
+    }
+    entities
 }
 
 #[allow(dead_code)]
 impl PrefabMap {
     pub fn new(template: &'static str) -> Self {
-        Self { template }
+        Self {
+            template: Template::Resource(template),
+            entities: Vec::new(),
+            spawn: None,
+            exit: None,
+            tags: HashMap::new(),
+        }
+    }
+
+    /// Reads the .xp map from a file instead of the embedded resources.
+    pub fn from_file(path: &str) -> io::Result<Self> {
+        let xp = XpFile::read(&mut File::open(path)?)?;
+        Ok(Self {
+            template: Template::File(xp),
+            ..Self::new("")
+        })
+    }
+
+    fn load(&self) -> XpFile {
+        match &self.template {
+            Template::Resource(name) => XpFile::from_resource(name).unwrap(),
+            Template::File(xp) => xp.clone(),
+        }
     }
 
     pub fn generate(&mut self, map: &mut Map) {
         map.tiles = vec![Tile::floor(); (map.width * map.height) as usize];
-        let prefab_map = XpFile::from_resource(self.template).unwrap();
+        let prefab_map = self.load();
 
-        for layer in &prefab_map.layers {
+        if let Some(layer) = prefab_map.layers.first() {
             println!("height: {}", layer.height);
             println!("width: {}", layer.width);
             for y in 0..layer.height {
                 for x in 0..layer.width {
+                    // Files loaded with --load-xp may be larger than the map.
+                    if x as i32 >= map.width || y as i32 >= map.height {
+                        continue;
+                    }
                     let cell = layer.get(x, y).unwrap();
-                    //println!("{}", (cell.ch as u8));
-                    //if map.in_map_bounds_xy(x as i32, y as i32) {
                     let idx = map.idx(x as i32, y as i32);
-                    //map.paint_tile_char(idx, (cell.ch as u8) as char);
                     map.paint_tile_char(idx, to_char(cell.ch as u8));
                 }
             }
         }
+        if let Some(layer) = prefab_map.layers.get(1) {
+            self.entities = read_entity_layer(layer)
+                .into_iter()
+                .filter(|(pt, _)| map.in_map_bounds(*pt))
+                .map(|(pt, name)| (map.idx_pt(pt), name))
+                .collect();
+        }
+        if let Some(layer) = prefab_map.layers.get(2) {
+            self.read_metadata(layer, map);
+        }
+    }
+
+    fn read_metadata(&mut self, layer: &XpLayer, map: &mut Map) {
+        let legend = REX_LEGEND.lock().unwrap();
+        for y in 0..layer.height {
+            for x in 0..layer.width {
+                let cell = layer.get(x, y).unwrap();
+                let pt = Point::new(x, y);
+                if is_blank(cell) || !map.in_map_bounds(pt) {
+                    continue;
+                }
+                let glyph = to_char(cell.ch as u8);
+                match legend.get_meta(glyph) {
+                    Some(MetaTag::Spawn) => self.spawn = Some(pt),
+                    Some(MetaTag::Exit) => {
+                        map.paint_tile(map.idx_pt(pt), TileType::Exit);
+                        self.exit = Some(pt);
+                    }
+                    Some(MetaTag::Region(tag)) => self
+                        .tags
+                        .entry(tag.clone())
+                        .or_default()
+                        .push(map.idx_pt(pt)),
+                    None => println!("No metadata for glyph {} at ({}, {}).", glyph, x, y),
+                }
+            }
+        }
     }
 
     pub fn repeat_template(&mut self, map: &mut Map) {
-        let prefab_map = XpFile::from_resource(self.template).unwrap();
+        let prefab_map = self.load();
 
-        for layer in &prefab_map.layers {
+        // Only the terrain is repeated.
+        if let Some(layer) = prefab_map.layers.first() {
             let tx = map.width / (layer.width as i32);
             let ty = map.height / (layer.height as i32);
             for y in 0..ty {
@@ -66,9 +168,9 @@ impl PrefabMap {
     }
 
     pub fn repeat_template_cont(&mut self, map: &mut Map) {
-        let prefab_map = XpFile::from_resource(self.template).unwrap();
+        let prefab_map = self.load();
 
-        for layer in &prefab_map.layers {
+        if let Some(layer) = prefab_map.layers.first() {
             let xt = layer.width as i32 - 1;
             let yt = layer.height as i32 - 1;
             for y in 0..map.height {
@@ -88,3 +190,26 @@ impl PrefabMap {
         }
     }
 }
+
+// The template always covers the whole map, so the region is ignored.
+impl MapBuilder for PrefabMap {
+    fn build(
+        &mut self,
+        map: &mut Map,
+        _region: &CustomRegion,
+        _rng: &mut RandomNumberGenerator,
+    ) -> BuildResult {
+        self.generate(map);
+        if let Some(spawn) = self.spawn {
+            map.set_spawn(spawn);
+        }
+        if let Some(exit) = self.exit {
+            map.set_exit(exit);
+        }
+        BuildResult {
+            regions: self.tags.values().cloned().collect(),
+            spawns: self.entities.clone(),
+            ..BuildResult::new()
+        }
+    }
+}
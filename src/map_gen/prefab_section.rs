@@ -1,67 +1,121 @@
-use super::{Map, Point};
+use super::{prefab_map::read_entity_layer, Map, Point};
+use crate::raws::Vault;
+use bracket_lib::prelude::{to_char, XpFile};
 
 /*
  *
  * prefab_section.rs
  * -----------------
- * Inserts pre-made structures on a map.
+ * Inserts pre-made structures (vaults) on a map.
  * Based on the examples by TheBracket.
  *
  */
 
 pub struct PrefabSection {
-    template: &'static str,
+    rows: Vec<Vec<char>>,
     width: i32,
     height: i32,
-    pos: Point,
+    legend: Vec<(char, String)>,
+    entities: Vec<(Point, String)>, // From the entities layer of a .xp file.
 }
 
-#[allow(dead_code)]
 impl PrefabSection {
-    pub fn generate(&mut self, pt: Point, map: &mut Map) {
-        self.pos = pt;
-        let v = self.str_to_vec();
-        let mut i = 0;
-        for y in 0..self.height {
-            for x in 0..self.width {
-                if map.in_map_bounds_xy(x + pt.x, y + pt.y) {
-                    let idx = map.idx(x + pt.x, y + pt.y);
-                    map.paint_tile_char(idx, v[i])
+    /// Reads the layout of a vault, from its rows or its .xp file.
+    /// Returns None if the vault has no (valid) layout.
+    pub fn from_vault(vault: &Vault) -> Option<Self> {
+        let mut entities = Vec::new();
+        let mut rows: Vec<Vec<char>> = if let Some(layout) = &vault.layout {
+            layout.iter().map(|row| row.chars().collect()).collect()
+        } else if let Some(path) = &vault.xp {
+            let xp = XpFile::from_resource(path).ok()?;
+            if let Some(layer) = xp.layers.get(1) {
+                entities = read_entity_layer(layer);
+            }
+            let layer = xp.layers.first()?;
+            (0..layer.height)
+                .map(|y| {
+                    (0..layer.width)
+                        .map(|x| match layer.get(x, y).unwrap().ch {
+                            0 => ' ',
+                            ch => to_char(ch as u8),
+                        })
+                        .collect()
+                })
+                .collect()
+        } else {
+            return None;
+        };
+
+        // No-break spaces are blank too; rows are padded to the same width.
+        for c in rows.iter_mut().flat_map(|row| row.iter_mut()) {
+            if *c == '\u{a0}' {
+                *c = ' ';
+            }
+        }
+        let width = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+        if width == 0 {
+            return None;
+        }
+        for row in rows.iter_mut() {
+            row.resize(width, ' ');
+        }
+
+        Some(Self {
+            width: width as i32,
+            height: rows.len() as i32,
+            rows,
+            legend: vault.legend.clone(),
+            entities,
+        })
+    }
+
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    /// Map indexes the section changes when placed at pt (blanks excluded).
+    pub fn footprint(&self, pt: Point, map: &Map) -> Vec<usize> {
+        let mut tiles = Vec::new();
+        for (y, row) in self.rows.iter().enumerate() {
+            for (x, ch) in row.iter().enumerate() {
+                let (mx, my) = (pt.x + x as i32, pt.y + y as i32);
+                if *ch != ' ' && map.in_map_bounds_xy(mx, my) {
+                    tiles.push(map.idx(mx, my));
                 }
-                i += 1;
             }
         }
+        tiles
     }
 
-    fn str_to_vec(&self) -> Vec<char> {
-        let mut string_vec: Vec<char> = self
-            .template
-            .chars()
-            .filter(|a| *a != '\r' && *a != '\n')
-            .collect();
-        for c in string_vec.iter_mut() {
-            if *c as u8 == 160u8 {
-                *c = ' ';
+    /// Paints the section on the map with its top left corner at pt.
+    /// Returns the entities to spawn, as (map index, raw name).
+    pub fn generate(&self, pt: Point, map: &mut Map) -> Vec<(usize, String)> {
+        let mut spawns = Vec::new();
+        for (y, row) in self.rows.iter().enumerate() {
+            for (x, ch) in row.iter().enumerate() {
+                let (mx, my) = (pt.x + x as i32, pt.y + y as i32);
+                if *ch == ' ' || !map.in_map_bounds_xy(mx, my) {
+                    continue;
+                }
+                let idx = map.idx(mx, my);
+                if let Some((_, name)) = self.legend.iter().find(|(c, _)| c == ch) {
+                    map.paint_tile_char(idx, '.');
+                    spawns.push((idx, name.clone()));
+                } else {
+                    map.paint_tile_char(idx, *ch);
+                }
+            }
+        }
+        for (offset, name) in self.entities.iter() {
+            let (mx, my) = (pt.x + offset.x, pt.y + offset.y);
+            if map.in_map_bounds_xy(mx, my) {
+                spawns.push((map.idx(mx, my), name.clone()));
             }
         }
-        string_vec
+        spawns
     }
 }
-
-#[allow(dead_code)]
-pub const HOUSE01: PrefabSection = PrefabSection {
-    template: HOUSE01STR,
-    width: 16,
-    height: 7,
-    pos: Point { x: 0, y: 0 }, //pos: Point::new(0, 0),
-};
-
-const HOUSE01STR: &str = "
-##########.⌠.⌠..
-#________####..⌠
-#___________###.
-#_____________+.
-#___________###.
-#________####.⌠.
-##########.⌠.⌠.⌠
-";
@@ -1,4 +1,6 @@
-use super::{CustomRegion, Map, Position, Tile, TileType};
+use super::{
+    builder::floor_regions, BuildResult, CustomRegion, Map, MapBuilder, Position, Tile, TileType,
+};
 use crate::utils::directions::*;
 use bracket_lib::prelude::RandomNumberGenerator;
 
@@ -23,39 +25,31 @@ struct Walker {
 // Walkers that can only move orthogonally produce neater dungeons,
 // while allowing diagonal movement produces chaotic dungeons.
 #[allow(dead_code)]
-pub struct RandomWalker<'a> {
-    region: &'a CustomRegion,
+pub struct RandomWalker {
     percent: f32,
     grouped_walkers: bool,
     can_walk_diagonally: bool,
 }
 
 #[allow(dead_code)]
-impl<'a> RandomWalker<'a> {
-    pub fn new(
-        region: &'a CustomRegion,
-        percent: f32,
-        grouped_walkers: bool,
-        can_walk_diagonally: bool,
-    ) -> Self {
+impl RandomWalker {
+    pub fn new(percent: f32, grouped_walkers: bool, can_walk_diagonally: bool) -> Self {
         //pub fn new(percent: f32, grouped_walkers: bool, can_walk_diagonally: bool) -> Self {
         Self {
-            region,
             percent,
             grouped_walkers,
             can_walk_diagonally,
         }
     }
 
-    pub fn generate(&mut self, map: &mut Map, rng: &mut RandomNumberGenerator) {
-        let mut n_floor_tiles = self
-            .region
+    fn generate(&mut self, map: &mut Map, region: &CustomRegion, rng: &mut RandomNumberGenerator) {
+        let mut n_floor_tiles = region
             .pos
             .iter()
             .filter(|p| map.is_floor(map.idx_pt(**p)))
             .count();
-        let needed_floor_tiles = (self.percent * self.region.size as f32) as usize;
-        let center = self.region.get_center();
+        let needed_floor_tiles = (self.percent * region.size as f32) as usize;
+        let center = region.get_center();
 
         let max = 500;
         let mut n_walkers = 0;
@@ -73,15 +67,15 @@ impl<'a> RandomWalker<'a> {
                 walker = Walker {
                     life: rng.range(200, 500),
                     pos: Position::new(
-                        rng.range(self.region.x1, self.region.x2),
-                        rng.range(self.region.y1, self.region.y2),
+                        rng.range(region.x1, region.x2),
+                        rng.range(region.y1, region.y2),
                     ),
                 };
             }
             //println!("{}", n_walkers);
             while walker.life > 0 {
                 let idx = map.idx(walker.pos.x, walker.pos.y);
-                if self.region.in_bounds(walker.pos) {
+                if region.in_bounds(walker.pos) {
                     let new_dir = rng.range(0, 8);
                     match new_dir {
                         0 => {
@@ -128,3 +122,18 @@ impl<'a> RandomWalker<'a> {
         //println!("Total walkers: {}", _n_walkers);
     }
 }
+
+impl MapBuilder for RandomWalker {
+    fn build(
+        &mut self,
+        map: &mut Map,
+        region: &CustomRegion,
+        rng: &mut RandomNumberGenerator,
+    ) -> BuildResult {
+        self.generate(map, region, rng);
+        BuildResult {
+            regions: floor_regions(map, region),
+            ..BuildResult::new()
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use super::{
+    common::*, prefab_section::PrefabSection, BuildResult, CustomRegion, Map, MapBuilder, Room,
+    TileType, Tunnel,
+};
+use crate::raws::Vault;
+use bracket_lib::prelude::{Point, RandomNumberGenerator};
+
+/*
+ *
+ * rooms_corridors.rs
+ * ------------------
+ * The classic "rooms and corridors": random rooms that don't overlap, joined by L-shaped
+ * corridors. Some rooms get a vault (see vaults.ron) in the middle, with a ring of floor around it
+ * so the corridors can still reach it.
+ *
+ * http://www.roguebasin.com/index.php?title=Complete_Roguelike_Tutorial,_using_python%2Blibtcod,_part_3
+ *
+ */
+
+pub struct RoomsAndCorridors {
+    max_rooms: i32,
+    min_size: i32,
+    max_size: i32,
+    prefab_chance: i32, // Chance (%) of a room getting a vault, if one fits.
+    vaults: Vec<Vault>,
+    rooms: Vec<Room>,
+    tunnels: Vec<Tunnel>,
+    spawns: Vec<(usize, String)>,
+}
+
+impl RoomsAndCorridors {
+    pub fn new(
+        max_rooms: i32,
+        min_size: i32,
+        max_size: i32,
+        prefab_chance: i32,
+        vaults: Vec<Vault>,
+    ) -> Self {
+        Self {
+            max_rooms,
+            min_size,
+            max_size,
+            prefab_chance,
+            vaults,
+            rooms: vec![],
+            tunnels: vec![],
+            spawns: vec![],
+        }
+    }
+
+    pub fn get_rooms(&self) -> Vec<Room> {
+        self.rooms.clone()
+    }
+
+    pub fn get_tunnels(&self) -> Vec<Tunnel> {
+        self.tunnels.clone()
+    }
+
+    /// Entities of the vaults placed in rooms, as (map index, raw name).
+    pub fn get_spawns(&self) -> Vec<(usize, String)> {
+        self.spawns.clone()
+    }
+
+    fn fits(&self, map: &Map, region: &CustomRegion, room: &Room) -> bool {
+        let corners = [
+            Point::new(room.x1, room.y1),
+            Point::new(room.x2, room.y1),
+            Point::new(room.x1, room.y2),
+            Point::new(room.x2, room.y2),
+        ];
+        corners.iter().all(|pt| {
+            map.in_map_bounds(*pt)
+                && pt.x >= region.x1
+                && pt.x < region.x2
+                && pt.y >= region.y1
+                && pt.y < region.y2
+                && (!region.circular || region.in_bounds(*pt))
+        })
+    }
+
+    /// Puts a vault that fits in the middle of a room, leaving a ring of floor around it.
+    /// Returns the tile corridors should lead to.
+    fn add_prefab(&mut self, map: &mut Map, room: &Room, rng: &mut RandomNumberGenerator) -> Point {
+        let center = room.center();
+        if rng.range(0, 100) >= self.prefab_chance {
+            return center;
+        }
+        // Interior (x1 + 1..x2) minus the ring.
+        let (inner_w, inner_h) = (room.width() - 3, room.height() - 3);
+        let sections: Vec<PrefabSection> = self
+            .vaults
+            .iter()
+            .filter_map(PrefabSection::from_vault)
+            .filter(|s| s.width() <= inner_w && s.height() <= inner_h)
+            .collect();
+        if sections.is_empty() {
+            return center;
+        }
+        let section = &sections[rng.range(0, sections.len() as i32) as usize];
+        let pt = Point::new(
+            center.x - section.width() / 2,
+            center.y - section.height() / 2,
+        );
+        self.spawns.extend(section.generate(pt, map));
+        Point::new(room.x1 + 1, room.y1 + 1)
+    }
+
+    fn generate(&mut self, map: &mut Map, region: &CustomRegion, rng: &mut RandomNumberGenerator) {
+        self.rooms.clear();
+        self.tunnels.clear();
+        self.spawns.clear();
+        let mut anchors: Vec<Point> = Vec::new();
+        for _ in 0..self.max_rooms {
+            let w = rng.range(self.min_size, self.max_size + 1);
+            let h = rng.range(self.min_size, self.max_size + 1);
+            if w >= region.width || h >= region.height {
+                continue;
+            }
+            let x = rng.range(region.x1, region.x2 - w);
+            let y = rng.range(region.y1, region.y2 - h);
+            let room = Room::with_size(x, y, w, h);
+            if !self.fits(map, region, &room) || self.rooms.iter().any(|r| r.intersect(&room)) {
+                continue;
+            }
+            create_room(map, room, TileType::Floor);
+            anchors.push(self.add_prefab(map, &room, rng));
+            self.rooms.push(room);
+        }
+
+        // Each room is joined to the previous one.
+        for pair in anchors.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            let mut tunnel = Vec::new();
+            if rng.range(0, 2) == 0 {
+                tunnel.extend(create_h_tunnel(map, a.x, b.x, a.y, 1));
+                tunnel.extend(create_v_tunnel(map, a.y, b.y, b.x, 1));
+            } else {
+                tunnel.extend(create_v_tunnel(map, a.y, b.y, a.x, 1));
+                tunnel.extend(create_h_tunnel(map, a.x, b.x, b.y, 1));
+            }
+            self.tunnels.push(tunnel);
+        }
+    }
+}
+
+impl MapBuilder for RoomsAndCorridors {
+    fn build(
+        &mut self,
+        map: &mut Map,
+        region: &CustomRegion,
+        rng: &mut RandomNumberGenerator,
+    ) -> BuildResult {
+        self.generate(map, region, rng);
+        BuildResult {
+            rooms: self.get_rooms(),
+            tunnels: self.get_tunnels(),
+            spawns: self.get_spawns(),
+            ..BuildResult::new()
+        }
+    }
+}
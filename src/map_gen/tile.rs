@@ -1,5 +1,5 @@
 use crate::utils::colors::*;
-use bracket_lib::prelude::{to_cp437, ColorPair};
+use bracket_lib::prelude::{to_cp437, ColorPair, RGB};
 
 /*
  *
@@ -33,6 +33,9 @@ pub enum TileType {
     DeepWater,
     Computer,
     FakeMob,
+    Fire,
+    Ash,
+    Rubble,
 }
 
 impl Default for TileType {
@@ -41,6 +44,101 @@ impl Default for TileType {
     }
 }
 
+impl TileType {
+    /// Name shown when looking at a tile.
+    pub fn get_name(&self) -> String {
+        String::from(match self {
+            TileType::Empty => "Nothing",
+            TileType::Exit => "Way down",
+            TileType::Wall | TileType::InvisibleWall => "Wall",
+            TileType::Floor | TileType::Floor2 => "Floor",
+            TileType::WoodenFloor => "Wooden floor",
+            TileType::Path1 => "Path",
+            TileType::ClosedDoor => "Closed door",
+            TileType::OpenDoor => "Open door",
+            TileType::Grass | TileType::Grass2 | TileType::Grass3 | TileType::Grass4 => "Grass",
+            TileType::TallGrass => "Tall grass",
+            TileType::Flower => "Flowers",
+            TileType::Tree => "Tree",
+            TileType::Mushroom => "Mushrooms",
+            TileType::ShallowWater => "Shallow water",
+            TileType::DeepWater => "Deep water",
+            TileType::Computer => "Computer",
+            TileType::FakeMob => "Strange figure",
+            TileType::Fire => "Fire",
+            TileType::Ash => "Ashes",
+            TileType::Rubble => "Rubble",
+        })
+    }
+
+    /// Tile type of a character in a prefab (.xp map or vault layout).
+    /// Unknown characters are floor.
+    pub fn from_char(ch: char) -> TileType {
+        match ch {
+            '.' => TileType::Floor,
+            '-' => TileType::Floor2,
+            '_' => TileType::WoodenFloor,
+            '+' => TileType::ClosedDoor,
+            '/' => TileType::OpenDoor,
+            // Walls may also be drawn as in the game (see Map::pretty_walls).
+            '#' | '■' | '║' | '═' | '╝' | '╗' | '╣' | '╚' | '╔' | '╠' | '╩' | '╦' | '╬' | '█' => {
+                TileType::Wall
+            }
+            '▓' => TileType::InvisibleWall,
+            'g' => TileType::FakeMob,
+            'Φ' => TileType::Computer,
+            '~' => TileType::ShallowWater,
+            '≈' => TileType::DeepWater,
+            '♣' | 'T' => TileType::Tree,
+            '♠' => TileType::Mushroom,
+            '⌠' => TileType::TallGrass,
+            '░' => TileType::Path1,
+            ',' => TileType::Grass,
+            '`' => TileType::Grass2,
+            '╨' => TileType::Grass3,
+            '╙' => TileType::Grass4,
+            '¥' => TileType::Flower,
+            '>' => TileType::Exit,
+            '·' => TileType::Empty,
+            '^' => TileType::Fire,
+            '∙' => TileType::Ash,
+            '%' => TileType::Rubble,
+            _ => TileType::Floor,
+        }
+    }
+
+    /// The character from_char reads as this tile type.
+    pub fn to_char(self) -> char {
+        match self {
+            TileType::Empty => '·',
+            TileType::Exit => '>',
+            TileType::Wall => '#',
+            TileType::InvisibleWall => '▓',
+            TileType::Floor => '.',
+            TileType::Floor2 => '-',
+            TileType::WoodenFloor => '_',
+            TileType::Path1 => '░',
+            TileType::ClosedDoor => '+',
+            TileType::OpenDoor => '/',
+            TileType::Grass => ',',
+            TileType::Grass2 => '`',
+            TileType::Grass3 => '╨',
+            TileType::Grass4 => '╙',
+            TileType::TallGrass => '⌠',
+            TileType::Flower => '¥',
+            TileType::Tree => '♣',
+            TileType::Mushroom => '♠',
+            TileType::ShallowWater => '~',
+            TileType::DeepWater => '≈',
+            TileType::Computer => 'Φ',
+            TileType::FakeMob => 'g',
+            TileType::Fire => '^',
+            TileType::Ash => '∙',
+            TileType::Rubble => '%',
+        }
+    }
+}
+
 #[derive(Copy, Clone, Default, PartialEq, Debug)]
 pub struct Tile {
     pub ttype: TileType,
@@ -124,6 +222,33 @@ impl Tile {
         }
     }
 
+    pub fn fire() -> Self {
+        Self {
+            ttype: TileType::Fire,
+            glyph: to_cp437('^'),
+            color: ColorPair::new(color("BrightYellow", 1.0), color("Red", 1.0)),
+            ..Default::default()
+        }
+    }
+
+    pub fn ash() -> Self {
+        Self {
+            ttype: TileType::Ash,
+            glyph: to_cp437('∙'),
+            color: ColorPair::new(color("BrightBlack", 1.0), color("Background", 1.0)),
+            ..Default::default()
+        }
+    }
+
+    pub fn rubble() -> Self {
+        Self {
+            ttype: TileType::Rubble,
+            glyph: to_cp437('%'),
+            color: ColorPair::new(color("White", 1.0), color("Background", 1.0)),
+            ..Default::default()
+        }
+    }
+
     pub fn path1() -> Self {
         Self {
             ttype: TileType::Path1,
@@ -274,6 +399,14 @@ impl Tile {
         };
     }
 
+    /// Tints the tile by the light over it: dim where it's dark, coloured near light sources.
+    pub fn lit(&mut self, light: RGB) {
+        let fg = &mut self.color.fg;
+        fg.r *= 0.4 + 0.6 * light.r;
+        fg.g *= 0.4 + 0.6 * light.g;
+        fg.b *= 0.4 + 0.6 * light.b;
+    }
+
     pub fn reload_color(&mut self) {
         match self.ttype {
             TileType::Floor => {
@@ -330,6 +463,15 @@ impl Tile {
             TileType::Exit => {
                 self.color = ColorPair::new(color("BrightMagenta", 1.0), color("Background", 1.0));
             }
+            TileType::Fire => {
+                self.color = ColorPair::new(color("BrightYellow", 1.0), color("Red", 1.0));
+            }
+            TileType::Ash => {
+                self.color = ColorPair::new(color("BrightBlack", 1.0), color("Background", 1.0));
+            }
+            TileType::Rubble => {
+                self.color = ColorPair::new(color("White", 1.0), color("Background", 1.0));
+            }
             _ => {
                 self.color = ColorPair::new(color("Background", 1.0), color("Background", 1.0));
             }
@@ -363,6 +505,10 @@ pub fn get_tile_function(ttype: TileType) -> Tile {
         TileType::Computer => Tile::computer(),
         TileType::FakeMob => Tile::fakemob(),
         TileType::Exit => Tile::exit(),
-        _ => Tile::floor(),
+        TileType::Path1 => Tile::path1(),
+        TileType::Empty => Tile::empty(),
+        TileType::Fire => Tile::fire(),
+        TileType::Ash => Tile::ash(),
+        TileType::Rubble => Tile::rubble(),
     }
 }
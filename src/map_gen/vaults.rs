@@ -0,0 +1,94 @@
+use super::{connectivity::*, prefab_section::PrefabSection, Map, Point};
+use crate::raws::Vault;
+use bracket_lib::prelude::RandomNumberGenerator;
+use std::collections::HashSet;
+
+/*
+ *
+ * vaults.rs
+ * ---------
+ * Places vaults (see resources/raws/vaults.ron) into generated maps.
+ * A vault goes over an empty area: solid rock (it gets connected with a tunnel) or open ground.
+ * Placements that make the map unplayable are undone.
+ *
+ */
+
+const MAX_VAULTS: i32 = 2;
+const TRIES_PER_VAULT: usize = 50;
+// Share of the area that may differ from the rest (e.g. a few trees on open ground).
+const EMPTY_TOLERANCE: f32 = 0.1;
+
+/// Whether an area is empty: mostly walls or mostly walkable, with nothing important on it.
+fn is_empty_area(map: &Map, footprint: &[usize], taken: &HashSet<usize>) -> bool {
+    let spawn = map.idx_pt(map.spawn_point);
+    let important = footprint.iter().any(|idx| {
+        taken.contains(idx)
+            || *idx == spawn
+            || map.is_exit(*idx)
+            || map.is_door(*idx)
+            || (map.in_map_bounds(map.exit_point) && *idx == map.idx_pt(map.exit_point))
+    });
+    if important || footprint.is_empty() {
+        return false;
+    }
+    let blocked = footprint
+        .iter()
+        .filter(|idx| !is_passable(map, **idx))
+        .count() as f32;
+    let share = blocked / footprint.len() as f32;
+    share <= EMPTY_TOLERANCE || share >= 1.0 - EMPTY_TOLERANCE
+}
+
+/// Places up to MAX_VAULTS of the given vaults on the map, away from the occupied tiles.
+/// Returns the entities the placed vaults spawn, as (map index, raw name).
+pub fn place_vaults(
+    map: &mut Map,
+    vaults: &[Vault],
+    occupied: &[usize],
+    rng: &mut RandomNumberGenerator,
+) -> Vec<(usize, String)> {
+    let mut spawns = Vec::new();
+    let mut candidates: Vec<&Vault> = vaults.iter().collect();
+    let mut taken: HashSet<usize> = occupied.iter().copied().collect();
+
+    for _ in 0..rng.range(0, MAX_VAULTS + 1) {
+        if candidates.is_empty() {
+            break;
+        }
+        let vault = candidates.remove(rng.range(0, candidates.len() as i32) as usize);
+        let section = match PrefabSection::from_vault(vault) {
+            Some(section) => section,
+            None => {
+                println!("Vault {} has no layout!", vault.name);
+                continue;
+            }
+        };
+        // Keep off the borders.
+        if section.width() + 2 > map.width || section.height() + 2 > map.height {
+            continue;
+        }
+
+        for _ in 0..TRIES_PER_VAULT {
+            let pt = Point::new(
+                rng.range(1, map.width - section.width()),
+                rng.range(1, map.height - section.height()),
+            );
+            let footprint = section.footprint(pt, map);
+            if !is_empty_area(map, &footprint, &taken) {
+                continue;
+            }
+
+            let backup = map.clone();
+            let vault_spawns = section.generate(pt, map);
+            match ensure_connectivity(map) {
+                Ok(report) if !report.spawn_moved && !report.exit_moved => {
+                    taken.extend(footprint);
+                    spawns.extend(vault_spawns);
+                    break;
+                }
+                _ => *map = backup,
+            }
+        }
+    }
+    spawns
+}
@@ -0,0 +1,148 @@
+use super::{BuildResult, CustomRegion, Map, MapBuilder, Region, TileType};
+use bracket_lib::prelude::{DistanceAlg, Point, RandomNumberGenerator};
+use std::collections::BTreeMap;
+
+/*
+ *
+ * voronoi.rs
+ * ----------
+ * Splits a region into Voronoi cells (districts) around random seeds. District borders become
+ * walls, with a door between every pair of neighboring districts. Good for towns and walled
+ * quarters.
+ *
+ * http://www.roguebasin.com/index.php?title=Voronoi_diagrams
+ *
+ */
+
+pub struct VoronoiDistricts {
+    n_seeds: usize,
+    manhattan: bool, // Manhattan distance makes blockier districts.
+    districts: Vec<Region>,
+    doors: Vec<usize>,
+}
+
+impl VoronoiDistricts {
+    pub fn new(n_seeds: usize, manhattan: bool) -> Self {
+        Self {
+            n_seeds,
+            manhattan,
+            districts: vec![],
+            doors: vec![],
+        }
+    }
+
+    /// The floor tiles of each district.
+    pub fn get_districts(&self) -> Vec<Region> {
+        self.districts.clone()
+    }
+
+    fn generate(&mut self, map: &mut Map, region: &CustomRegion, rng: &mut RandomNumberGenerator) {
+        self.districts.clear();
+        self.doors.clear();
+        let tiles: Vec<Point> = region
+            .pos
+            .iter()
+            .filter(|p| region.in_bounds(**p) && map.in_map_bounds(**p))
+            .copied()
+            .collect();
+        if tiles.is_empty() {
+            return;
+        }
+        let seeds: Vec<Point> = (0..self.n_seeds.max(1))
+            .map(|_| tiles[rng.range(0, tiles.len() as i32) as usize])
+            .collect();
+        let alg = if self.manhattan {
+            DistanceAlg::Manhattan
+        } else {
+            DistanceAlg::Pythagoras
+        };
+
+        // Each tile belongs to the district of the closest seed.
+        let mut owner: Vec<Option<usize>> = vec![None; map.size as usize];
+        for pt in tiles.iter() {
+            let closest = seeds
+                .iter()
+                .enumerate()
+                .map(|(i, seed)| (i, alg.distance2d(*pt, *seed)))
+                .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+                .map(|(i, _)| i);
+            owner[map.idx_pt(*pt)] = closest;
+        }
+
+        // A tile next to a district with a lower index is a border, so walls are one tile thick.
+        let is_border = |pt: Point, owner: &Vec<Option<usize>>| {
+            let o = owner[map.idx_pt(pt)];
+            [(1, 0), (-1, 0), (0, 1), (0, -1)].iter().any(|(dx, dy)| {
+                let n = Point::new(pt.x + dx, pt.y + dy);
+                map.in_map_bounds(n) && owner[map.idx_pt(n)].is_some_and(|other| Some(other) < o)
+            })
+        };
+        let borders: Vec<Point> = tiles
+            .iter()
+            .filter(|pt| is_border(**pt, &owner))
+            .copied()
+            .collect();
+        let mut is_wall = vec![false; map.size as usize];
+        for pt in borders.iter() {
+            is_wall[map.idx_pt(*pt)] = true;
+        }
+
+        self.districts = vec![Vec::new(); seeds.len()];
+        for pt in tiles.iter() {
+            let idx = map.idx_pt(*pt);
+            if is_wall[idx] {
+                map.paint_tile(idx, TileType::Wall);
+            } else {
+                map.paint_tile(idx, TileType::Floor);
+                self.districts[owner[idx].unwrap()].push(idx);
+            }
+        }
+
+        // Border tiles with floor of two districts on opposite sides can be doors.
+        let mut gates: BTreeMap<(usize, usize), Vec<usize>> = BTreeMap::new();
+        for pt in borders.iter() {
+            for (dx, dy) in [(1, 0), (0, 1)].iter() {
+                let a = Point::new(pt.x - dx, pt.y - dy);
+                let b = Point::new(pt.x + dx, pt.y + dy);
+                if !map.in_map_bounds(a) || !map.in_map_bounds(b) {
+                    continue;
+                }
+                let (ia, ib) = (map.idx_pt(a), map.idx_pt(b));
+                if !map.is_floor(ia) || !map.is_floor(ib) {
+                    continue;
+                }
+                if let (Some(oa), Some(ob)) = (owner[ia], owner[ib]) {
+                    if oa != ob {
+                        let pair = (oa.min(ob), oa.max(ob));
+                        gates.entry(pair).or_default().push(map.idx_pt(*pt));
+                    }
+                }
+            }
+        }
+        for candidates in gates.values() {
+            let idx = candidates[rng.range(0, candidates.len() as i32) as usize];
+            map.paint_tile(idx, TileType::ClosedDoor);
+            self.doors.push(idx);
+        }
+    }
+}
+
+impl MapBuilder for VoronoiDistricts {
+    fn build(
+        &mut self,
+        map: &mut Map,
+        region: &CustomRegion,
+        rng: &mut RandomNumberGenerator,
+    ) -> BuildResult {
+        self.generate(map, region, rng);
+        BuildResult {
+            regions: self
+                .get_districts()
+                .into_iter()
+                .filter(|d| !d.is_empty())
+                .collect(),
+            doors: self.doors.clone(),
+            ..BuildResult::new()
+        }
+    }
+}
@@ -1,17 +1,75 @@
-use super::TileType;
-use std::collections::HashSet;
+use crate::utils::directions::*;
+
+// The 4 directions used by the adjacency rules, in the order of TileEnablerCount.
+pub const DIRECTIONS: [Direction; 4] = [EAST, WEST, NORTH, SOUTH];
 
 pub fn tile_idx(tile_size: i32, x: i32, y: i32) -> usize {
     ((y * tile_size) + x) as usize
 }
 
-#[allow(dead_code)]
 pub fn in_tile_bounds(tile_size: i32, x: i32, y: i32) -> bool {
     x >= 0 && x < tile_size && y >= 0 && y < tile_size
 }
 
-// https://www.reddit.com/r/rust/comments/38zzbk/best_way_to_remove_duplicates_from_a_veclist/
-pub fn deduplicate(vs: &mut Vec<Vec<TileType>>) {
-    let set: HashSet<Vec<TileType>> = vs.drain(..).collect();
-    vs.extend(set.into_iter());
+/// A set of tile (pattern) indexes, stored as bits.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TileSet {
+    bits: Vec<u64>,
+    len: usize,
+}
+
+impl TileSet {
+    /// A set with all the tiles from 0 to n-1.
+    pub fn full(n: usize) -> Self {
+        let mut bits = vec![u64::MAX; n.div_ceil(64)];
+        let rest = n % 64;
+        if rest > 0 {
+            if let Some(last) = bits.last_mut() {
+                *last = (1 << rest) - 1;
+            }
+        }
+        Self { bits, len: n }
+    }
+
+    pub fn contains(&self, tile: usize) -> bool {
+        self.bits[tile / 64] & (1 << (tile % 64)) != 0
+    }
+
+    /// Returns true if the tile was in the set.
+    pub fn remove(&mut self, tile: usize) -> bool {
+        if !self.contains(tile) {
+            return false;
+        }
+        self.bits[tile / 64] &= !(1 << (tile % 64));
+        self.len -= 1;
+        true
+    }
+
+    /// Returns true if the tile wasn't in the set.
+    pub fn insert(&mut self, tile: usize) -> bool {
+        if self.contains(tile) {
+            return false;
+        }
+        self.bits[tile / 64] |= 1 << (tile % 64);
+        self.len += 1;
+        true
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.bits.iter().enumerate().flat_map(|(i, word)| {
+            let mut word = *word;
+            std::iter::from_fn(move || {
+                if word == 0 {
+                    return None;
+                }
+                let bit = word.trailing_zeros() as usize;
+                word &= word - 1;
+                Some(i * 64 + bit)
+            })
+        })
+    }
 }
@@ -1,7 +1,11 @@
-use super::{CustomRegion, Map, Point, TileType};
+use super::{
+    builder::floor_regions, get_tile_function, BuildResult, CustomRegion, Map, MapBuilder, Point,
+    TileType,
+};
 use crate::utils::directions::*;
 use bracket_lib::prelude::RandomNumberGenerator;
 use std::collections::HashMap;
+use std::rc::Rc;
 
 mod common;
 use common::*;
@@ -16,7 +20,8 @@ use wave::*;
  *
  * This is NOT meant to be the fastest WFC algorithm possible; stevebob/wfc is plenty
  * fast already. Our point here is to LEARN and USE WFC, comparing it with traditional
- * PCG methods.
+ * PCG methods. Still, it should be fast enough to generate a whole map while the player
+ * waits (run the game with --bench-wfc to check).
  *
  * For reference, I used the following resources:
  * - https://gridbugs.org/wave-function-collapse/ (mainly)
@@ -27,116 +32,301 @@ use wave::*;
  *
  */
 
+const MAX_ATTEMPTS: u32 = 5;
+const MAX_BACKTRACKS: u32 = 50;
+
+// Symmetries of a pattern taken from the input (see get_pattern).
+const REFLECTIONS: [&str; 4] = ["normal", "vertical", "horizontal", "both"];
+const ROTATIONS: [&str; 4] = ["rot90", "rot270", "transpose", "antitranspose"];
+
+/// Forces what the output must have at a given map position.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pin {
+    Tile(Point, TileType),         // This exact tile.
+    Pattern(Point, Vec<TileType>), // This pattern, on the cell that starts at (or contains) the point.
+    Passable(Point),               // Anything that can be walked through (doors included).
+}
+
+/// What happened when running the WFC.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WfcOutcome {
+    pub success: bool,
+    pub attempts: u32, // Restarts (with fresh noise) used, including the first run.
+    pub backtracks: u32, // Backtracks used in the last attempt.
+    pub fallback_used: bool, // Set by the caller if it had to use another generator instead.
+}
+
 #[derive(Debug, Clone)]
-pub struct WaveFunctionCollapse<'a> {
+pub struct WaveFunctionCollapse {
     tile_size: i32,
     patterns: Vec<Vec<TileType>>,
-    constraints: Vec<MapTile>,
-    frequencies: HashMap<usize, f32>,
-    region: &'a CustomRegion,
+    pattern_counts: Vec<f32>, // How many times each pattern appears in the input.
+    constraints: Rc<Vec<MapTile>>,
+    frequencies: Vec<f32>,
     mix_match: bool, // Used for larger inputs.
+    max_attempts: u32,
+    max_backtracks: u32,
+    overlapping: bool, // Each cell is one output tile instead of a whole pattern.
+    rotations: bool,   // Also take the 90° rotations of the input patterns.
+    periodic_input: bool, // The input wraps around its borders.
+    tile_weights: HashMap<TileType, f32>,
+    pins: Vec<Pin>,
+    input: Option<(Map, i32, i32)>, // Used when running as a MapBuilder (see set_input).
 }
 
-#[allow(dead_code)]
-impl<'a> WaveFunctionCollapse<'a> {
-    pub fn new(tile_size: i32, region: &'a CustomRegion, mix_match: bool) -> Self {
+impl WaveFunctionCollapse {
+    pub fn new(tile_size: i32, mix_match: bool) -> Self {
         Self {
             tile_size,
             patterns: Vec::new(),
-            constraints: Vec::new(),
-            frequencies: HashMap::new(),
-            region,
+            pattern_counts: Vec::new(),
+            constraints: Rc::new(Vec::new()),
+            frequencies: Vec::new(),
             mix_match,
+            max_attempts: MAX_ATTEMPTS,
+            max_backtracks: MAX_BACKTRACKS,
+            overlapping: false,
+            rotations: false,
+            periodic_input: false,
+            tile_weights: HashMap::new(),
+            pins: Vec::new(),
+            input: None,
         }
     }
 
+    /// Uses the N×N overlapping model: the output has one cell per tile of the region, and
+    /// neighbor cells must overlap in all but one row/column of their patterns.
+    /// Otherwise each cell is painted with a whole tile_size×tile_size pattern.
+    pub fn set_overlapping(&mut self, overlapping: bool) {
+        self.overlapping = overlapping;
+    }
+
+    /// Also takes the 90° and 270° rotations (and both diagonal reflections) of each pattern.
+    pub fn set_rotations(&mut self, rotations: bool) {
+        self.rotations = rotations;
+    }
+
+    /// If the input is periodic, patterns are also taken across its borders (wrapping around).
+    /// Otherwise only the patterns that fit entirely inside it are used.
+    pub fn set_periodic_input(&mut self, periodic: bool) {
+        self.periodic_input = periodic;
+    }
+
+    /// Multiplies the weight of every pattern that contains the given tile type.
+    /// By default a pattern weighs as much as the times it appears in the input.
+    pub fn set_tile_weight(&mut self, ttype: TileType, weight: f32) {
+        // A weight of 0 would break the entropy calculations.
+        self.tile_weights.insert(ttype, weight.max(0.001));
+    }
+
+    pub fn add_pin(&mut self, pin: Pin) {
+        self.pins.push(pin);
+    }
+
+    /// The input (and its width and height) to take the patterns from when running as a
+    /// MapBuilder.
+    pub fn set_input(&mut self, input: &Map, input_x: i32, input_y: i32) {
+        self.input = Some((input.clone(), input_x, input_y));
+    }
+
+    /// Sets how many times the WFC restarts after a contradiction it couldn't backtrack from,
+    /// and how many backtracks each attempt may do (0 disables backtracking).
+    pub fn set_limits(&mut self, max_attempts: u32, max_backtracks: u32) {
+        self.max_attempts = max_attempts.max(1);
+        self.max_backtracks = max_backtracks;
+    }
+
     /// Runs the whole WFC algorithm.
+    /// The output map is only painted if the WFC succeeds.
     pub fn generate(
         &mut self,
         output_map: &mut Map,
+        region: &CustomRegion,
         input_map: &Map,
         input_x: i32,
         input_y: i32,
         rng: &mut RandomNumberGenerator,
-    ) -> bool {
-        self.build_patterns(input_map, input_x, input_y);
-        let patterns = self.patterns.clone();
-        //println!("Patterns: {}", patterns.len());
-        //map.tiles = vec![Tile::woodenfloor(); (map.width * map.height) as usize];
-
-        deduplicate(&mut self.patterns);
+    ) -> WfcOutcome {
+        self.build_patterns(input_map, input_x, input_y); // already deduplicated
 
         self.build_constraints(); // patterns + adjacency rules
-        let constraints = self.constraints.clone();
-        self.compute_frequencies(&patterns, &constraints); // frequency hints
+        self.compute_frequencies(); // frequency hints
+        let constraints = Rc::clone(&self.constraints);
+
+        let (out_width, out_height) = self.out_dimensions(region);
 
-        let out_width = self.region.width / self.tile_size;
-        let out_height = self.region.height / self.tile_size;
+        let mut outcome = WfcOutcome {
+            success: false,
+            attempts: 0,
+            backtracks: 0,
+            fallback_used: false,
+        };
+
+        if out_width <= 0 || out_height <= 0 || constraints.is_empty() {
+            return outcome;
+        }
         let out_size = out_width * out_height;
 
-        // Initialize Cells.
-        let cells = self.init_cells(out_size, rng);
+        while outcome.attempts < self.max_attempts {
+            outcome.attempts += 1;
 
-        // Initialize Wave.
-        let mut wave = Wave::new(cells, constraints, out_width, out_height);
-        wave.init_entropy_queue();
+            // Initialize Cells (with fresh noise on every attempt).
+            let cells = self.init_cells(out_size, rng);
+
+            // Initialize Wave.
+            let mut wave = Wave::new(
+                cells,
+                Rc::clone(&constraints),
+                out_width,
+                out_height,
+                self.max_backtracks as usize,
+            );
+            wave.init_entropy_queue();
+
+            // Pins are the same on every attempt, so there's no point in trying again.
+            if !self.apply_pins(&mut wave, region) {
+                break;
+            }
 
-        // Run Wave.
-        if !self.run_wave(&mut wave, rng) {
-            return false;
+            // Run Wave.
+            if let Some(backtracks) = self.run_wave(&mut wave, rng) {
+                outcome.backtracks = backtracks;
+                outcome.success = true;
+                // Generate the output.
+                self.generate_output(wave, output_map, region);
+                break;
+            }
         }
 
-        // Generate the output.
-        //map.tiles = vec![Tile::wall(); (output_map.width * output_map.height) as usize];
-        self.generate_output(wave, output_map);
+        outcome
+    }
+
+    /// Distance (in tiles) between the origins of two neighbor cells.
+    fn stride(&self) -> i32 {
+        if self.overlapping {
+            1
+        } else {
+            self.tile_size
+        }
+    }
 
-        true
+    /// Width and height of the output, in cells.
+    fn out_dimensions(&self, region: &CustomRegion) -> (i32, i32) {
+        if self.overlapping {
+            (
+                region.width - self.tile_size + 1,
+                region.height - self.tile_size + 1,
+            )
+        } else {
+            (
+                region.width / self.tile_size,
+                region.height / self.tile_size,
+            )
+        }
+    }
+
+    /// Removes from the wave every pattern that goes against a pin.
+    /// Returns false if it's impossible to satisfy them.
+    fn apply_pins(&self, wave: &mut Wave, region: &CustomRegion) -> bool {
+        let stride = self.stride();
+        let n = self.tile_size;
+        for pin in self.pins.iter() {
+            let pt = match pin {
+                Pin::Tile(pt, _) | Pin::Pattern(pt, _) | Pin::Passable(pt) => *pt,
+            };
+            let (rx, ry) = (pt.x - region.x1, pt.y - region.y1);
+
+            // Every cell whose pattern covers the point, and where the point is in the pattern.
+            let mut covering: Vec<(Point, usize)> = Vec::new();
+            for cy in 0..wave.out_height {
+                for cx in 0..wave.out_width {
+                    let (ox, oy) = (rx - cx * stride, ry - cy * stride);
+                    if in_tile_bounds(n, ox, oy) {
+                        covering.push((Point::new(cx, cy), tile_idx(n, ox, oy)));
+                    }
+                }
+            }
+
+            for (coord, offset) in covering {
+                // A pattern pin only concerns the cell that starts at the point (or the one
+                // containing it, if the cells don't overlap).
+                if let Pin::Pattern(_, _) = pin {
+                    if offset != 0 && stride == 1 {
+                        continue;
+                    }
+                }
+                let keep = |t: usize| {
+                    let pattern = &self.constraints[t].pattern;
+                    match pin {
+                        Pin::Tile(_, ttype) => pattern[offset] == *ttype,
+                        Pin::Pattern(_, p) => pattern == p,
+                        Pin::Passable(_) => passable(pattern[offset]),
+                    }
+                };
+                if !wave.restrict_cell(coord, keep, &self.frequencies) {
+                    return false;
+                }
+            }
+        }
+        wave.propagate(&self.frequencies)
     }
 
     /// Initialize all the cells.
     fn init_cells(&self, n_cells: i32, rng: &mut RandomNumberGenerator) -> Vec<Cell> {
+        let enabler_count = Cell::initial_enabler_count(&self.constraints);
         let mut cells: Vec<Cell> = Vec::new();
         for _i in 0..n_cells {
             let noise = rng.rand::<f32>() / 10000.0; // Random noise to break entropy ties
-            let cell = Cell::new(self.constraints.len(), noise);
+            let cell = Cell::new(
+                self.constraints.len(),
+                noise,
+                enabler_count.clone(),
+                &self.frequencies,
+            );
             cells.push(cell);
         }
-        for cell in cells.iter_mut() {
-            cell.total_possible_tile_freq(&self.frequencies);
-            cell.initial_enabler_count(self.constraints.clone());
-        }
         cells
     }
 
-    /// Runs the core WFC solver.
-    fn run_wave(&mut self, wave: &mut Wave, rng: &mut RandomNumberGenerator) -> bool {
+    /// Runs the core WFC solver, backtracking on contradictions.
+    /// Returns the number of backtracks used, or None if it couldn't solve the wave.
+    fn run_wave(&mut self, wave: &mut Wave, rng: &mut RandomNumberGenerator) -> Option<u32> {
+        let mut backtracks = 0;
         while wave.uncollapsed_cells > 0 {
             let next_coord = wave.choose_next_cell();
             wave.collapse_cell_at(next_coord, &self.frequencies, rng);
-            if !wave.propagate(&self.frequencies) {
-                return false;
-            }
             wave.uncollapsed_cells -= 1;
+            let mut consistent = wave.propagate(&self.frequencies);
+            while !consistent {
+                if backtracks >= self.max_backtracks {
+                    return None;
+                }
+                backtracks += 1;
+                consistent = wave.backtrack(&self.frequencies)?;
+            }
         }
-        true
+        Some(backtracks)
     }
 
     /// Translates the information on the wave to the map, thus generating the output.
-    fn generate_output(&mut self, wave: Wave, map: &mut Map) {
+    fn generate_output(&mut self, wave: Wave, map: &mut Map, region: &CustomRegion) {
         for (i, cell) in wave.cells.iter().enumerate() {
             let cell_x = i as i32 % wave.out_width;
             let cell_y = i as i32 / wave.out_width;
 
-            let x1 = self.region.x1 + cell_x * self.tile_size;
-            let x2 = self.region.x1 + (cell_x + 1) * self.tile_size;
-            let y1 = self.region.y1 + cell_y * self.tile_size;
-            let y2 = self.region.y1 + (cell_y + 1) * self.tile_size;
+            // With overlapping cells, the patterns of the neighbors agree on the shared tiles,
+            // so painting all of them gives the same result.
+            let x1 = region.x1 + cell_x * self.stride();
+            let x2 = x1 + self.tile_size;
+            let y1 = region.y1 + cell_y * self.stride();
+            let y2 = y1 + self.tile_size;
 
             let mut j: usize = 0;
             for y in y1..y2 {
                 for x in x1..x2 {
                     let map_idx = map.idx(x, y);
-                    let tile_idx = cell.possible_tiles[0]; // Only one because the cell is collapsed.
+                    // Only one because the cell is collapsed.
+                    let tile_idx = cell.possible.iter().next().unwrap();
                     let tile = self.constraints[tile_idx].pattern[j];
                     map.paint_tile(map_idx, tile);
                     j += 1;
@@ -146,18 +336,23 @@ impl<'a> WaveFunctionCollapse<'a> {
     }
 
     /// Builds tiles of size tile_size*tile_size from the given map cells.
+    /// Repeated patterns are only counted.
     fn build_patterns(&mut self, map: &Map, input_x: i32, input_y: i32) {
         self.patterns.clear();
+        self.pattern_counts.clear();
+        let mut index: HashMap<Vec<TileType>, usize> = HashMap::new();
         // Navigate the coordinates of each tile.
-        let y1 = if !self.mix_match {
-            input_y
+        let (y1, x1) = if self.mix_match {
+            (input_y / self.tile_size, input_x / self.tile_size)
+        } else if self.periodic_input {
+            (input_y, input_x)
         } else {
-            input_y / self.tile_size
+            (input_y - self.tile_size + 1, input_x - self.tile_size + 1)
         };
-        let x1 = if !self.mix_match {
-            input_x
+        let wrap = if self.periodic_input && !self.mix_match {
+            Some(Point::new(input_x, input_y))
         } else {
-            input_x / self.tile_size
+            None
         };
         for ty in 0..y1 {
             for tx in 0..x1 {
@@ -166,54 +361,56 @@ impl<'a> WaveFunctionCollapse<'a> {
                 } else {
                     Point::new(tx * self.tile_size, ty * self.tile_size)
                 };
-                let end = if !self.mix_match {
-                    Point::new(tx + self.tile_size, ty + self.tile_size)
+                //println!("Start: {:?}", start);
+                let syms = if self.rotations {
+                    REFLECTIONS.iter().chain(ROTATIONS.iter())
                 } else {
-                    Point::new((tx + 1) * self.tile_size, (ty + 1) * self.tile_size)
+                    REFLECTIONS.iter().chain([].iter())
                 };
-                //println!("Start: {:?}, End: {:?}", start, end);
-                let normal_pattern = self.get_pattern(map, start, end, "normal");
-                let vert_pattern = self.get_pattern(map, start, end, "vertical");
-                let horiz_pattern = self.get_pattern(map, start, end, "horizontal");
-                let verthoriz_pattern = self.get_pattern(map, start, end, "both");
-                //let inverted_pattern = self.get_pattern(map, start, end, "invert");
-                self.patterns.push(normal_pattern);
-                self.patterns.push(vert_pattern);
-                self.patterns.push(horiz_pattern);
-                self.patterns.push(verthoriz_pattern);
-                //self.patterns.push(inverted_pattern);
+                for sym in syms {
+                    let pattern = self.get_pattern(map, start, wrap, sym);
+                    match index.get(&pattern) {
+                        Some(i) => self.pattern_counts[*i] += 1.0,
+                        None => {
+                            index.insert(pattern.clone(), self.patterns.len());
+                            self.patterns.push(pattern);
+                            self.pattern_counts.push(1.0);
+                        }
+                    }
+                }
             }
         }
     }
 
-    /// Returns a pattern (reflected or not) taken from the input.
-    fn get_pattern(&mut self, map: &Map, start: Point, end: Point, rot: &str) -> Vec<TileType> {
+    /// Returns a pattern (reflected, rotated or not) taken from the input, starting at `start`.
+    /// If `wrap` is given, coordinates past it wrap around to the start of the input.
+    fn get_pattern(
+        &self,
+        map: &Map,
+        start: Point,
+        wrap: Option<Point>,
+        sym: &str,
+    ) -> Vec<TileType> {
+        let n = self.tile_size;
         let mut pattern: Vec<TileType> = Vec::new();
-        for y in start.y..end.y {
-            for x in start.x..end.x {
-                let idx;
-                match rot {
-                    "vertical" => {
-                        idx = map.idx(x, end.y - (y + 1));
-                    }
-                    "horizontal" => {
-                        idx = map.idx(end.x - (x + 1), y);
-                    }
-                    "both" => {
-                        idx = map.idx(end.x - (x + 1), end.y - (y + 1));
-                    }
-                    "invert" => {
-                        if map.in_map_bounds_xy(y, x) {
-                            idx = map.idx(y, x);
-                        } else {
-                            idx = map.idx(x, y);
-                        }
-                        //idx = map.idx(end.y - (y + 1), end.x - (x + 1));
-                    }
-                    _ => {
-                        idx = map.idx(x, y);
-                    }
+        for y in 0..n {
+            for x in 0..n {
+                // Where, in the original pattern, the tile (x, y) comes from.
+                let (sx, sy) = match sym {
+                    "vertical" => (x, n - (y + 1)),
+                    "horizontal" => (n - (x + 1), y),
+                    "both" => (n - (x + 1), n - (y + 1)),
+                    "rot90" => (y, n - (x + 1)),
+                    "rot270" => (n - (y + 1), x),
+                    "transpose" => (y, x),
+                    "antitranspose" => (n - (y + 1), n - (x + 1)),
+                    _ => (x, y),
+                };
+                let mut src = Point::new(start.x + sx, start.y + sy);
+                if let Some(w) = wrap {
+                    src = Point::new(src.x % w.x, src.y % w.y);
                 }
+                let idx = map.idx(src.x, src.y);
                 pattern.push(map.tiles[idx].ttype);
             }
         }
@@ -221,58 +418,69 @@ impl<'a> WaveFunctionCollapse<'a> {
     }
 
     /// Compute the relative frequencies of each tile.
-    fn compute_frequencies(&mut self, patterns: &Vec<Vec<TileType>>, constraints: &Vec<MapTile>) {
-        // Calculate absolute frequencies.
-        for tile in constraints.iter() {
-            for p in patterns.iter() {
-                if tile.pattern == *p {
-                    *self.frequencies.entry(tile.idx).or_insert(0.0) += 1.0;
-                }
+    fn compute_frequencies(&mut self) {
+        // Absolute frequencies (times each pattern appears) times the weights of its tile types.
+        self.frequencies = self.pattern_counts.clone();
+        if !self.tile_weights.is_empty() {
+            let weights = &self.tile_weights;
+            for (tile, f) in self.constraints.iter().zip(self.frequencies.iter_mut()) {
+                let mut ttypes = tile.pattern.clone();
+                ttypes.sort_by_key(|t| *t as usize);
+                ttypes.dedup();
+                let weight: f32 = ttypes
+                    .iter()
+                    .map(|t| *weights.get(t).unwrap_or(&1.0))
+                    .product();
+                *f *= weight;
             }
         }
         // Update absolute frequencies to relative frequencies.
-        let total: f32 = self.frequencies.values().sum();
-        for v in self.frequencies.values_mut() {
+        let total: f32 = self.frequencies.iter().sum();
+        for v in self.frequencies.iter_mut() {
             *v /= total;
         }
     }
 
     /// Build the "contraints", that is, the possible tiles and their compatibilities.
     /// In other other, we're building each "map tile" with its adjacency rules.
+    /// Two patterns are compatible in a direction if they overlap when one is shifted by it,
+    /// so instead of comparing every pair we group the patterns by the part that must overlap.
     fn build_constraints(&mut self) {
-        for (i, p1) in self.patterns.iter().enumerate() {
-            let mut map_tile = MapTile {
+        let mut constraints: Vec<MapTile> = self
+            .patterns
+            .iter()
+            .enumerate()
+            .map(|(i, p)| MapTile {
                 idx: i, // Each tile has an index
-                pattern: p1.to_vec(),
-                compatible: Vec::new(),
+                pattern: p.to_vec(),
+                compatible: [Vec::new(), Vec::new(), Vec::new(), Vec::new()],
                 size: self.tile_size,
-            };
-            //println!("{:?}", p1);
+            })
+            .collect();
+
+        for (d, dir) in DIRECTIONS.iter().enumerate() {
+            // p1 is compatible with p2 in dir if the part of p1 towards dir is the same as the
+            // part of p2 towards the opposite direction.
+            let opposite = DIRECTIONS[opposite_idx(d)];
+            let mut by_overlap: HashMap<Vec<TileType>, Vec<usize>> = HashMap::new();
             for (j, p2) in self.patterns.iter().enumerate() {
-                //if p1 == p2 { continue; }
-                if self.is_compatible(p1, p2, EAST) {
-                    map_tile.compatible.push((j, EAST));
-                    //println!("{} compat with {:?} NORTH", i, j);
-                }
-                if self.is_compatible(p1, p2, WEST) {
-                    map_tile.compatible.push((j, WEST));
-                    //println!("{} compat with {:?} SOUTH", i, j);
-                }
-                if self.is_compatible(p1, p2, NORTH) {
-                    map_tile.compatible.push((j, NORTH));
-                    //println!("{} compat with {:?} EAST", i, j);
-                }
-                if self.is_compatible(p1, p2, SOUTH) {
-                    map_tile.compatible.push((j, SOUTH));
-                    //println!("{} compat with {:?} WEST", i, j);
+                by_overlap
+                    .entry(self.overlap(p2, opposite))
+                    .or_default()
+                    .push(j);
+            }
+            for (i, p1) in self.patterns.iter().enumerate() {
+                if let Some(compatible) = by_overlap.get(&self.overlap(p1, *dir)) {
+                    constraints[i].compatible[d] = compatible.clone();
                 }
             }
-            self.constraints.push(map_tile);
         }
+
+        self.constraints = Rc::new(constraints);
     }
 
-    /// Checks if there is overlap between two patterns.
-    fn is_compatible(&self, p1: &Vec<TileType>, p2: &Vec<TileType>, dir: Direction) -> bool {
+    /// Returns the part of a pattern that overlaps with a neighbor pattern shifted by dir.
+    fn overlap(&self, pattern: &[TileType], dir: Direction) -> Vec<TileType> {
         let xmin = if dir.delta_x < 0 { 0 } else { dir.delta_x };
         let xmax = if dir.delta_x < 0 {
             dir.delta_x + self.tile_size as i8
@@ -286,19 +494,43 @@ impl<'a> WaveFunctionCollapse<'a> {
             self.tile_size as i8
         };
 
-        // Iterate on every symbol in the intersection of the two patterns.
-        // If any symbol is different, return false.
+        let mut part = Vec::new();
         for y in ymin..ymax {
             for x in xmin..xmax {
-                let p1_pos = Point::new(x, y);
-                let offset = p1_pos - dir;
-                if p1[tile_idx(self.tile_size, p1_pos.x, p1_pos.y)]
-                    != p2[tile_idx(self.tile_size, offset.x, offset.y)]
-                {
-                    return false;
-                }
+                part.push(pattern[tile_idx(self.tile_size, x as i32, y as i32)]);
             }
         }
-        true
+        part
+    }
+}
+
+/// Whether a tile can be walked through (doors included).
+fn passable(ttype: TileType) -> bool {
+    ttype == TileType::ClosedDoor || !get_tile_function(ttype).block
+}
+
+impl MapBuilder for WaveFunctionCollapse {
+    fn build(
+        &mut self,
+        map: &mut Map,
+        region: &CustomRegion,
+        rng: &mut RandomNumberGenerator,
+    ) -> BuildResult {
+        let (input, input_x, input_y) = match self.input.take() {
+            Some(input) => input,
+            None => {
+                println!("WFC has no input!");
+                return BuildResult::new();
+            }
+        };
+        let outcome = self.generate(map, region, &input, input_x, input_y, rng);
+        self.input = Some((input, input_x, input_y));
+        if !outcome.success {
+            return BuildResult::new();
+        }
+        BuildResult {
+            regions: floor_regions(map, region),
+            ..BuildResult::new()
+        }
     }
 }
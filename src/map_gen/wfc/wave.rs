@@ -1,23 +1,53 @@
-use super::{Cell, MapTile, Point};
+use super::{Cell, MapTile, Point, DIRECTIONS};
 use crate::utils::directions::*;
 use bracket_lib::prelude::RandomNumberGenerator;
 use std::cmp::Ordering;
-use std::collections::{BinaryHeap, HashMap};
+use std::collections::{BinaryHeap, HashSet, VecDeque};
+use std::rc::Rc;
 
 #[derive(Debug, Clone)]
 pub struct Wave {
     pub cells: Vec<Cell>,
     pub uncollapsed_cells: usize,
-    maptiles: Vec<MapTile>,
+    maptiles: Rc<Vec<MapTile>>, // shared adjacency rules
     entropy_queue: BinaryHeap<CoordEntropy>,
     tile_removals: Vec<RemovalUpdate>, // stack
+    trail: VecDeque<Change>,           // undo log, for backtracking
+    checkpoints: usize,                // collapses in the trail
+    max_checkpoints: usize,
     pub out_width: i32,
     pub out_height: i32,
 }
 
-#[allow(dead_code)]
+/// A change made to the wave, recorded so it can be undone.
+#[derive(Debug, Clone)]
+enum Change {
+    // A cell was collapsed into a tile. Everything after it in the trail follows from it.
+    Collapse {
+        coord: Point,
+        tile: usize,
+        uncollapsed_cells: usize,
+    },
+    Removal {
+        cell: usize,
+        tile: usize,
+    },
+    EnablerDecrement {
+        cell: usize,
+        tile: usize,
+        dir: usize,
+    },
+}
+
 impl Wave {
-    pub fn new(cells: Vec<Cell>, maptiles: Vec<MapTile>, out_width: i32, out_height: i32) -> Self {
+    /// Creates a wave that can backtrack over its last `max_checkpoints` collapses.
+    pub fn new(
+        cells: Vec<Cell>,
+        maptiles: Rc<Vec<MapTile>>,
+        out_width: i32,
+        out_height: i32,
+        max_checkpoints: usize,
+    ) -> Self {
         let cells_len = cells.len(); // or out_width * out_height
         Self {
             cells,
@@ -25,6 +55,9 @@ impl Wave {
             maptiles,
             entropy_queue: BinaryHeap::new(),
             tile_removals: Vec::new(),
+            trail: VecDeque::new(),
+            checkpoints: 0,
+            max_checkpoints,
             out_height,
             out_width,
         }
@@ -34,19 +67,23 @@ impl Wave {
     pub fn init_entropy_queue(&mut self) {
         for y in 0..self.out_height {
             for x in 0..self.out_width {
-                let idx = self.cell_at(x, y);
-                let cell = &self.cells[idx];
-                self.entropy_queue.push(CoordEntropy {
-                    entropy: Entropy {
-                        entropy: cell.entropy(),
-                        noise: cell.entropy_noise,
-                    },
-                    coord: Point::new(x, y),
-                });
+                self.push_entropy(Point::new(x, y));
             }
         }
     }
 
+    /// Adds the current entropy of a cell to the queue.
+    fn push_entropy(&mut self, coord: Point) {
+        let cell = &self.cells[self.cell_at(coord.x, coord.y)];
+        self.entropy_queue.push(CoordEntropy {
+            entropy: Entropy {
+                entropy: cell.entropy(),
+                noise: cell.entropy_noise,
+            },
+            coord,
+        });
+    }
+
     /// Returns the cell at (x, y) on the wave.
     fn cell_at(&self, x: i32, y: i32) -> usize {
         (y as usize * self.out_width as usize) + x as usize
@@ -57,9 +94,23 @@ impl Wave {
         x >= 0 && x < self.out_width && y >= 0 && y < self.out_height
     }
 
-    /// Given a tile index, returns all the compatible tiles it has.
-    pub fn get_compatible_dir(&self, idx: usize, dir: Direction) -> Vec<usize> {
-        self.maptiles[idx].get_compatible_dir(dir)
+    /// Records a change, if there's a collapse it can be undone back to.
+    fn record(&mut self, change: Change) {
+        if self.checkpoints > 0 {
+            self.trail.push_back(change);
+        }
+    }
+
+    /// Removes a tile from a cell, recording it and queueing the propagation.
+    /// Returns false if it caused a contradiction.
+    fn remove_tile(&mut self, coord: Point, tile: usize, freq: &[f32]) -> bool {
+        let idx = self.cell_at(coord.x, coord.y);
+        if !self.cells[idx].remove_tile(tile, freq) {
+            return true;
+        }
+        self.record(Change::Removal { cell: idx, tile });
+        self.tile_removals.push(RemovalUpdate { tile, coord });
+        !self.cells[idx].contradiction_check()
     }
 
     /// Select the next cell to collapse and return its coordinate in the wave.
@@ -71,46 +122,133 @@ impl Wave {
                 return entropy_coord.coord;
             }
         }
-        unreachable!("entropy_queue is empty!");
+        // Backtracking may leave some cell out of the queue.
+        let idx = self
+            .cells
+            .iter()
+            .position(|c| !c.collapsed)
+            .expect("there are no uncollapsed cells!");
+        Point::new(idx as i32 % self.out_width, idx as i32 / self.out_width)
     }
 
     /// Collapses a cell at a given point.
     /// That is, remove all the possibilities except the only possible one.
-    pub fn collapse_cell_at(
+    pub fn collapse_cell_at(&mut self, pt: Point, freq: &[f32], rng: &mut RandomNumberGenerator) {
+        let idx = self.cell_at(pt.x, pt.y);
+        let locked_tile = self.cells[idx].choose_tile(freq, rng);
+
+        if self.max_checkpoints > 0 {
+            // Forget the oldest collapse (and what followed from it) if there are too many.
+            if self.checkpoints == self.max_checkpoints {
+                self.trail.pop_front();
+                while let Some(Change::Removal { .. }) | Some(Change::EnablerDecrement { .. }) =
+                    self.trail.front()
+                {
+                    self.trail.pop_front();
+                }
+                self.checkpoints -= 1;
+            }
+            self.trail.push_back(Change::Collapse {
+                coord: pt,
+                tile: locked_tile,
+                uncollapsed_cells: self.uncollapsed_cells,
+            });
+            self.checkpoints += 1;
+        }
+
+        self.cells[idx].collapsed = true;
+
+        let possibles: Vec<usize> = self.cells[idx].possible.iter().collect();
+        for tile in possibles {
+            if tile != locked_tile {
+                self.remove_tile(pt, tile, freq);
+            }
+        }
+    }
+
+    /// Undoes everything since the last collapse and bans the tile chosen there, then
+    /// propagates the ban. Returns None if there's nothing left to backtrack to, or whether the
+    /// propagation succeeded.
+    pub fn backtrack(&mut self, freq: &[f32]) -> Option<bool> {
+        if self.checkpoints == 0 {
+            return None;
+        }
+        self.tile_removals.clear();
+
+        let mut touched: HashSet<usize> = HashSet::new();
+        let (pt, banned) = loop {
+            match self.trail.pop_back()? {
+                Change::Removal { cell, tile } => {
+                    self.cells[cell].restore_tile(tile, freq);
+                    touched.insert(cell);
+                }
+                Change::EnablerDecrement { cell, tile, dir } => {
+                    self.cells[cell].enabler_count[tile].by_direction[dir] += 1;
+                }
+                Change::Collapse {
+                    coord,
+                    tile,
+                    uncollapsed_cells,
+                } => {
+                    let idx = self.cell_at(coord.x, coord.y);
+                    self.cells[idx].collapsed = false;
+                    self.uncollapsed_cells = uncollapsed_cells;
+                    self.checkpoints -= 1;
+                    break (coord, tile);
+                }
+            }
+        };
+
+        for idx in touched {
+            if !self.cells[idx].collapsed {
+                let coord = Point::new(idx as i32 % self.out_width, idx as i32 / self.out_width);
+                self.push_entropy(coord);
+            }
+        }
+
+        // The ban follows from the previous collapses, so it's recorded as part of them.
+        if !self.remove_tile(pt, banned, freq) {
+            return Some(false);
+        }
+        self.push_entropy(pt);
+
+        Some(self.propagate(freq))
+    }
+
+    /// Removes from a cell all the tiles that aren't kept, without propagating.
+    /// Returns false if no tile is left.
+    pub fn restrict_cell<F: Fn(usize) -> bool>(
         &mut self,
         pt: Point,
-        freq: &HashMap<usize, f32>,
-        rng: &mut RandomNumberGenerator,
-    ) {
+        keep: F,
+        freq: &[f32],
+    ) -> bool {
         let idx = self.cell_at(pt.x, pt.y);
-        let mut cell = &mut self.cells[idx];
-        let locked_tile = cell.choose_tile(freq, rng);
-
-        cell.collapsed = true;
-
-        let possibles = cell.possible_tiles.clone();
-        for idx in possibles.iter() {
-            if *idx != locked_tile {
-                cell.remove_tile(*idx, freq);
-                self.tile_removals.push(RemovalUpdate {
-                    tile: *idx,
-                    coord: pt,
-                });
+        let removed: Vec<usize> = self.cells[idx]
+            .possible
+            .iter()
+            .filter(|t| !keep(*t))
+            .collect();
+        if removed.is_empty() {
+            return true;
+        }
+        for tile in removed {
+            if !self.remove_tile(pt, tile, freq) {
+                return false;
             }
         }
+        self.push_entropy(pt);
+        true
     }
 
     /// Keeps propagating consequences until there are none (think like it's a sudoku game).
-    pub fn propagate(&mut self, freq: &HashMap<usize, f32>) -> bool {
-        let directions = [EAST, WEST, NORTH, SOUTH];
+    pub fn propagate(&mut self, freq: &[f32]) -> bool {
+        let maptiles = Rc::clone(&self.maptiles);
 
         while let Some(removal_update) = self.tile_removals.pop() {
-            //println!("NEW REMOVAL");
             // Iterate through each adjacent tile to the current one.
-            for i in 0..4 {
-                let dir = directions[i];
-
-                let neighbor_coord = removal_update.coord + dir;
+            for (i, dir) in DIRECTIONS.iter().enumerate() {
+                let neighbor_coord = removal_update.coord + *dir;
 
                 // Skip if coordinates are outside the bounds.
                 if !self.in_bound(neighbor_coord.x, neighbor_coord.y) {
@@ -124,33 +262,22 @@ impl Wave {
                     continue;
                 }
 
-                let compatible_tiles = self.get_compatible_dir(removal_update.tile, dir);
-                let neighbor_cell = &mut self.cells[neighbor_idx];
-
-                for compat in compatible_tiles.iter() {
-                    let j = opposite_idx(i); // Opposite direction to i
-
-                    if neighbor_cell.enabler_count[*compat].by_direction[j] == 1 {
-                        if neighbor_cell.possible[*compat] {
-                            neighbor_cell.remove_tile(*compat, freq);
-                            if neighbor_cell.contradiction_check() {
-                                println!("Contradiction!");
-                                return false;
-                            }
-                            self.entropy_queue.push(CoordEntropy {
-                                entropy: Entropy {
-                                    entropy: neighbor_cell.entropy(),
-                                    noise: neighbor_cell.entropy_noise,
-                                },
-                                coord: neighbor_coord,
-                            });
-                            self.tile_removals.push(RemovalUpdate {
-                                tile: *compat,
-                                coord: neighbor_coord,
-                            });
+                let j = opposite_idx(i); // Opposite direction to i
+                for compat in maptiles[removal_update.tile].get_compatible_dir(i) {
+                    let count = self.cells[neighbor_idx].enabler_count[*compat].by_direction[j];
+                    if count == 1 && self.cells[neighbor_idx].possible.contains(*compat) {
+                        if !self.remove_tile(neighbor_coord, *compat, freq) {
+                            //println!("Contradiction!");
+                            return false;
                         }
+                        self.push_entropy(neighbor_coord);
                     }
-                    neighbor_cell.enabler_count[*compat].by_direction[j] -= 1;
+                    self.cells[neighbor_idx].enabler_count[*compat].by_direction[j] -= 1;
+                    self.record(Change::EnablerDecrement {
+                        cell: neighbor_idx,
+                        tile: *compat,
+                        dir: j,
+                    });
                 }
             }
         }
@@ -0,0 +1,129 @@
+use super::{Map, Point, TileType};
+use crate::raws::{MetaTag, REX_LEGEND};
+use bracket_lib::prelude::{to_char, to_cp437, XpColor, XpFile, XpLayer};
+use std::fs::File;
+use std::io;
+
+/*
+ *
+ * xp_export.rs
+ * ------------
+ * Saves a map as a .xp file, to be edited in REXPaint and loaded again with PrefabMap.
+ * Layers are the same PrefabMap reads: terrain, entities and metadata (spawn and exit).
+ *
+ */
+
+// REXPaint draws cells with this background as transparent.
+const TRANSPARENT: XpColor = XpColor {
+    r: 255,
+    g: 0,
+    b: 255,
+};
+
+fn blank_layer(width: usize, height: usize) -> XpLayer {
+    let mut layer = XpLayer::new(width, height);
+    for cell in layer.cells.iter_mut() {
+        cell.bg = TRANSPARENT;
+    }
+    layer
+}
+
+fn put(layer: &mut XpLayer, pt: Point, glyph: char, fg: (u8, u8, u8)) {
+    if let Some(cell) = layer.get_mut(pt.x as usize, pt.y as usize) {
+        cell.ch = to_cp437(glyph) as u32;
+        cell.fg = XpColor::new(fg.0, fg.1, fg.2);
+        cell.bg = XpColor::BLACK;
+    }
+}
+
+/// Builds a .xp image of the map, with its tiles as they're drawn in the game.
+/// Glyphs that don't tell the tile type apart (e.g. Floor2 looks like Floor) are replaced with
+/// the character PrefabMap reads as that type, so the file loads back to the same tiles.
+/// Entities are (map index, raw name), drawn with the legend in rex_legend.ron.
+pub fn map_to_xp(map: &Map, entities: Option<&[(usize, String)]>) -> XpFile {
+    let (width, height) = (map.width as usize, map.height as usize);
+    let mut xp = XpFile::new(width, height);
+
+    for (idx, tile) in map.tiles.iter().enumerate() {
+        let pt = map.idx_pos(idx);
+        let glyph = to_char(tile.glyph as u8);
+        let ch = if TileType::from_char(glyph) == tile.ttype {
+            glyph
+        } else {
+            tile.ttype.to_char()
+        };
+        if let Some(cell) = xp.layers[0].get_mut(pt.x as usize, pt.y as usize) {
+            cell.ch = to_cp437(ch) as u32;
+            cell.fg = XpColor::from(tile.color.fg.to_rgb());
+            cell.bg = XpColor::from(tile.color.bg.to_rgb());
+        }
+    }
+
+    let legend = REX_LEGEND.lock().unwrap();
+    let mut entity_layer = blank_layer(width, height);
+    for (idx, name) in entities.unwrap_or(&[]) {
+        match legend.get_glyph(name) {
+            Some((glyph, fg)) => put(&mut entity_layer, map.idx_pos(*idx), glyph, fg),
+            None => println!("No glyph for {} in the legend.", name),
+        }
+    }
+    xp.layers.push(entity_layer);
+
+    let mut meta_layer = blank_layer(width, height);
+    for (pt, meta) in [
+        (map.spawn_point, MetaTag::Spawn),
+        (map.exit_point, MetaTag::Exit),
+    ]
+    .iter()
+    {
+        if let Some(glyph) = legend.get_meta_glyph(meta) {
+            if map.in_map_bounds(*pt) {
+                put(&mut meta_layer, *pt, glyph, (255, 255, 255));
+            }
+        }
+    }
+    xp.layers.push(meta_layer);
+
+    xp
+}
+
+/// Writes the map (see map_to_xp) to a .xp file.
+pub fn export_map(map: &Map, entities: Option<&[(usize, String)]>, path: &str) -> io::Result<()> {
+    let mut file = File::create(path)?;
+    map_to_xp(map, entities).write(&mut file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{CustomRegion, MapBuilder, MapGenerator, PrefabMap};
+    use super::*;
+    use bracket_lib::prelude::RandomNumberGenerator;
+
+    #[test]
+    fn exported_map_loads_back() {
+        crate::rexloader::load_dungeons();
+        crate::raws::load_raws();
+        let mut gen = MapGenerator::new();
+        gen.push_map(80, 60);
+        gen.gen_map(0);
+        let map = &gen.maps[0];
+        let entities = vec![(map.idx_pt(map.spawn_point), "Med-Kit".to_string())];
+
+        let path = std::env::temp_dir().join("exported_map_loads_back.xp");
+        let path = path.to_str().unwrap();
+        export_map(map, Some(&entities), path).unwrap();
+        let mut prefab = PrefabMap::from_file(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        let mut loaded = Map::new(map.width, map.height, TileType::Wall, None);
+        let region = CustomRegion::new_rect(0, 0, map.width, map.height);
+        let result = prefab.build(&mut loaded, &region, &mut RandomNumberGenerator::new());
+
+        for (idx, tile) in map.tiles.iter().enumerate() {
+            assert_eq!(loaded.tiles[idx].ttype, tile.ttype, "tile {}", idx);
+        }
+        assert_eq!(loaded.spawn_point, map.spawn_point);
+        assert_eq!(loaded.exit_point, map.exit_point);
+        assert_eq!(result.spawns, entities);
+    }
+}
@@ -1,15 +1,22 @@
 use super::{
+    dialogue::talk,
+    furniture::use_furniture,
     map_gen::{common::count_neighbor_tile_entity, Map, TileType},
+    quests::reaction_to_player,
+    raws::Reaction,
     utils::directions::*,
-    ActiveWeapon, CollectItem, Container, EquipSlot, Equipable, Equipment, Fov, Item, MeleeAttack,
-    MissileAttack, MissileWeapon, Mob, Player, Position, RunState, SelectedPosition, Target,
-    TryReload,
+    ActiveWeapon, AutoExplore, BaseStats, CollectItem, Container, EmitNoise, EquipSlot, Equipable,
+    Equipment, Explosive, Fov, Interactive, Inventory, InventoryCapacity, Item, Key, Lock,
+    Lockpick, MeleeAttack, MissileAttack, MissileTarget, MissileWeapon, Mob, Name, Player,
+    Position, RunState, SelectedPosition, Slowed, Talker, Target, TryReload, AUTO_PICKUP,
 };
 use crate::log::Log;
+use crate::systems::noise::DOOR_NOISE;
 use crate::utils::colors::*;
 use bracket_lib::prelude::*;
 use specs::prelude::*;
 use std::cmp::Ordering;
+use std::collections::VecDeque;
 
 /*
  *
@@ -19,44 +26,212 @@ use std::cmp::Ordering;
  *
  */
 
-/// Tries to move the player, performing melee attacks if needed.
-pub fn move_player(dir: Direction, ecs: &mut World) {
-    let mut pos_ = ecs.write_storage::<Position>();
-    let mut player_ = ecs.write_storage::<Player>();
-    let mut fov = ecs.write_storage::<Fov>();
-    let map = ecs.fetch::<Map>();
-    //let stats = ecs.read_storage::<BaseStats>();
-    let mobs = ecs.read_storage::<Mob>();
-    let entities = ecs.entities();
+/// Tries to move the player, performing melee attacks if needed. Mobs that aren't hostile swap
+/// places with the player instead, or are talked to if they can talk.
+pub fn move_player(dir: Direction, ecs: &mut World) -> RunState {
+    let (entered, failed_swim, talk_to) = {
+        let mut pos_ = ecs.write_storage::<Position>();
+        let mut player_ = ecs.write_storage::<Player>();
+        let mut fov = ecs.write_storage::<Fov>();
+        let mut slowed = ecs.write_storage::<Slowed>();
+        let mut map = ecs.fetch_mut::<Map>();
+        //let stats = ecs.read_storage::<BaseStats>();
+        let mobs = ecs.read_storage::<Mob>();
+        let talkers = ecs.read_storage::<Talker>();
+        let entities = ecs.entities();
+        let mut entered = None;
+        let mut failed_swim = None;
+        let mut swapped = None;
+        let mut talk_to = None;
 
-    for (_player, pos, fov, entity) in (&mut player_, &mut pos_, &mut fov, &entities).join() {
-        let dir_x = dir.delta_x as i32;
-        let dir_y = dir.delta_y as i32;
-        let dest = map.idx(pos.x + dir_x, pos.y + dir_y);
+        for (_player, pos, fov, entity) in (&mut player_, &mut pos_, &mut fov, &entities).join() {
+            let dir_x = dir.delta_x as i32;
+            let dir_y = dir.delta_y as i32;
+            let dest = map.idx(pos.x + dir_x, pos.y + dir_y);
 
-        // Tries melee if you're trying to move into an occupied tile.
-        for ents in map.entities[dest].iter() {
-            for ent in ents.iter() {
-                let t = mobs.get(*ent);
-                if let Some(_t) = t {
-                    println!("Attacking enemy.");
-                    let mut melee_attack = ecs.write_storage::<MeleeAttack>();
-                    melee_attack
-                        .insert(entity, MeleeAttack { target: *ent })
-                        .expect("Melee attack insertion failed");
+            // Tries melee if you're trying to move into a hostile.
+            let mut swap_with = None;
+            for ents in map.entities[dest].iter() {
+                for ent in ents.iter() {
+                    if mobs.get(*ent).is_none() {
+                        continue;
+                    }
+                    if reaction_to_player(ecs, *ent) == Reaction::Hostile {
+                        println!("Attacking enemy.");
+                        let mut melee_attack = ecs.write_storage::<MeleeAttack>();
+                        melee_attack
+                            .insert(entity, MeleeAttack { target: *ent })
+                            .expect("Melee attack insertion failed");
+                    } else if talkers.get(*ent).is_some() {
+                        talk_to = Some(*ent);
+                    } else {
+                        swap_with = Some(*ent);
+                    }
+                }
+            }
+            if talk_to.is_some() {
+                continue;
+            }
+
+            if !map.tiles[dest].block || swap_with.is_some() {
+                // Still getting out of the water, etc.
+                if let Some(s) = slowed.get_mut(entity) {
+                    s.turns -= 1;
+                    if s.turns <= 0 {
+                        slowed.remove(entity);
+                    }
+                    let mut log = ecs.fetch_mut::<Log>();
+                    log.add(
+                        "You struggle through the water.".to_string(),
+                        color("BrightWhite", 1.0),
+                    );
+                    continue;
                 }
+                // Deep water can only be entered by swimming.
+                if map.tiles[dest].ttype == TileType::DeepWater && !swim_check(ecs, entity) {
+                    failed_swim = Some(dest);
+                    continue;
+                }
+                if let Some(npc) = swap_with {
+                    swapped = Some((npc, *pos, map.tiles[dest].block));
+                    map.clear_blocker(pos.x + dir_x, pos.y + dir_y);
+                }
+                pos.x = pos.x + dir_x;
+                pos.y = pos.y + dir_y;
+                let mut player_pos = ecs.write_resource::<Point>();
+                player_pos.x = pos.x;
+                player_pos.y = pos.y;
+                println!("New pos: {:?}", *player_pos);
+                fov.dirty = true;
+                entered = Some(dest);
             }
         }
 
-        if !map.tiles[dest].block {
-            pos.x = pos.x + dir_x;
-            pos.y = pos.y + dir_y;
-            let mut player_pos = ecs.write_resource::<Point>();
-            player_pos.x = pos.x;
-            player_pos.y = pos.y;
-            println!("New pos: {:?}", *player_pos);
-            fov.dirty = true;
+        // The bumped mob takes the player's old place.
+        if let Some((npc, old_pos, blocker)) = swapped {
+            if let Some(npc_pos) = pos_.get_mut(npc) {
+                *npc_pos = old_pos;
+            }
+            if let Some(npc_fov) = fov.get_mut(npc) {
+                npc_fov.dirty = true;
+            }
+            if blocker {
+                map.add_blocker(old_pos.x, old_pos.y);
+            }
         }
+        (entered, failed_swim, talk_to)
+    };
+
+    if let Some(npc) = talk_to {
+        return talk(ecs, npc);
+    }
+    if let Some(idx) = entered {
+        enter_tile(ecs, idx);
+    }
+    if let Some(idx) = failed_swim {
+        lose_item(ecs, idx);
+    }
+    RunState::PlayerTurn
+}
+
+// Minimum d20 roll to swim. Every two items carried make it one point harder.
+const SWIM_DIFFICULTY: i32 = 6;
+
+/// Rolls to swim into deep water, harder the more the player carries.
+fn swim_check(ecs: &World, player: Entity) -> bool {
+    let load = match ecs.read_storage::<InventoryCapacity>().get(player) {
+        Some(capacity) => capacity.curr as i32 / 2,
+        None => 0,
+    };
+    let mut rng = ecs.write_resource::<RandomNumberGenerator>();
+    rng.roll_dice(1, 20) >= SWIM_DIFFICULTY + load
+}
+
+/// After a failed swim check, a random item slips away and washes up on the shore nearest to the
+/// water tile.
+fn lose_item(ecs: &mut World, water_idx: usize) {
+    let player = *ecs.fetch::<Entity>();
+    let item = {
+        let inventory = ecs.read_storage::<Inventory>();
+        let entities = ecs.entities();
+        let items: Vec<Entity> = (&entities, &inventory)
+            .join()
+            .filter(|(_, inv)| inv.owner == player)
+            .map(|(e, _)| e)
+            .collect();
+        let mut rng = ecs.write_resource::<RandomNumberGenerator>();
+        rng.random_slice_entry(&items).copied()
+    };
+
+    let mut log = ecs.fetch_mut::<Log>();
+    let (item, shore) = match (item, nearest_shore(&ecs.fetch::<Map>(), water_idx)) {
+        (Some(item), Some(shore)) => (item, shore),
+        _ => {
+            log.add("You fail to swim.", color("BrightWhite", 1.0));
+            return;
+        }
+    };
+
+    let name = ecs.read_storage::<Name>().get(item).unwrap().name.clone();
+    ecs.write_storage::<Inventory>().remove(item);
+    ecs.write_storage::<Position>()
+        .insert(item, shore)
+        .expect("Unable to insert position");
+    if let Some(capacity) = ecs.write_storage::<InventoryCapacity>().get_mut(player) {
+        capacity.curr = capacity.curr.saturating_sub(1);
+    }
+    log.add(
+        format!(
+            "You fail to swim and your {} is carried away by the water to the shore!",
+            name
+        ),
+        color("BrightRed", 1.0),
+    );
+}
+
+/// Closest walkable tile out of the water, searching outwards from a water tile.
+fn nearest_shore(map: &Map, from: usize) -> Option<Position> {
+    let mut visited = vec![false; map.size as usize];
+    let mut queue = VecDeque::new();
+    visited[from] = true;
+    queue.push_back(map.idx_pos(from));
+
+    while let Some(pt) = queue.pop_front() {
+        let idx = map.idx_pt(pt);
+        if map.is_walkable(idx) && !map.is_water(idx) {
+            return Some(pt);
+        }
+        for i in 0..8 {
+            let next = pt + dir_idx(i);
+            if map.in_map_bounds(next) && !visited[map.idx_pt(next)] {
+                visited[map.idx_pt(next)] = true;
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+/// Effects of the terrain the player just walked into.
+fn enter_tile(ecs: &mut World, idx: usize) {
+    let player = *ecs.fetch::<Entity>();
+    let (cost, deep_water) = {
+        let map = ecs.fetch::<Map>();
+        (
+            map.move_cost(idx),
+            map.tiles[idx].ttype == TileType::DeepWater,
+        )
+    };
+
+    if cost > 1 {
+        ecs.write_storage::<Slowed>()
+            .insert(player, Slowed { turns: cost - 1 })
+            .expect("Slowed insertion failed");
+    }
+
+    if deep_water {
+        let mut log = ecs.fetch_mut::<Log>();
+        log.add("You swim.", color("BrightWhite", 1.0));
     }
 }
 
@@ -184,7 +359,12 @@ pub fn missile_attack(ecs: &mut World) {
         let t = targets.get(target);
         if !t.unwrap().covered {
             missile_attack
-                .insert(*player, MissileAttack { target })
+                .insert(
+                    *player,
+                    MissileAttack {
+                        target: MissileTarget::Mob(target),
+                    },
+                )
                 .expect("Missile attack insertion failed");
         }
     }
@@ -199,8 +379,81 @@ pub fn reset_targeting(ecs: &mut World) -> RunState {
     RunState::Waiting
 }
 
-/// Returns all the visible and/or hittable targets in the player's FOV ordered by distance to the player (cresc.).
-fn visible_targets(ecs: &World, hittable: bool) -> Vec<(Entity, f32, bool)> {
+/// Checks if the player's readied missile weapon is an explosive one, which can be aimed at tiles.
+pub fn explosive_readied(ecs: &World) -> bool {
+    let active_wpn = ecs.read_storage::<ActiveWeapon>();
+    let explosive = ecs.read_storage::<Explosive>();
+    let player = ecs.fetch::<Entity>();
+
+    match get_weapon(ecs, *player, EquipSlot::Weapon2) {
+        Some(w) => active_wpn.get(w).is_some() && explosive.get(w).is_some(),
+        None => false,
+    }
+}
+
+/// Starts aiming an explosive weapon, with the cursor on the closest hostile (or the player).
+pub fn choose_tile(ecs: &mut World) -> RunState {
+    let player = ecs.fetch::<Entity>();
+
+    if !can_shoot(ecs, *player) {
+        let mut log = ecs.fetch_mut::<Log>();
+        log.add(
+            "You can't use your ranged weapon.",
+            color("BrightWhite", 1.0),
+        );
+        return RunState::Waiting;
+    }
+
+    let ppos = *ecs.fetch::<Point>();
+    RunState::TargetTile {
+        cursor: next_hostile(ecs, ppos).unwrap_or(ppos),
+    }
+}
+
+/// Where a missile aimed at a tile lands: on the first mob or blocking tile in the line of fire,
+/// or on the tile itself.
+pub fn impact_point(ecs: &World, cursor: Point) -> Point {
+    let map = ecs.fetch::<Map>();
+    let mobs = ecs.read_storage::<Mob>();
+    let ppos = *ecs.fetch::<Point>();
+
+    for pt in line2d_vector(ppos, cursor).iter().skip(1) {
+        let idx = map.idx_pt(*pt);
+        let mob_hit = match &map.entities[idx] {
+            Some(ents) => ents.iter().any(|e| mobs.get(*e).is_some()),
+            None => false,
+        };
+        if mob_hit || map.tiles[idx].block {
+            return *pt;
+        }
+    }
+    cursor
+}
+
+/// Fires the readied explosive weapon at a tile. A mob in the way is shot as usual.
+pub fn tile_missile_attack(ecs: &mut World, cursor: Point) {
+    let pos = impact_point(ecs, cursor);
+    let map = ecs.fetch::<Map>();
+    let mobs = ecs.read_storage::<Mob>();
+    let player = ecs.fetch::<Entity>();
+    let mut missile_attack = ecs.write_storage::<MissileAttack>();
+
+    let mob = match &map.entities[map.idx_pt(pos)] {
+        Some(ents) => ents.iter().find(|e| mobs.get(**e).is_some()).copied(),
+        None => None,
+    };
+    let target = match mob {
+        Some(m) => MissileTarget::Mob(m),
+        None => MissileTarget::Tile(pos),
+    };
+    missile_attack
+        .insert(*player, MissileAttack { target })
+        .expect("Missile attack insertion failed");
+}
+
+/// Returns all the visible and/or hittable hostiles in the player's FOV ordered by distance to the player (cresc.).
+/// Only the mobs hostile to the player count (see reaction_to_player), not traders or critters.
+pub fn visible_targets(ecs: &World, hittable: bool) -> Vec<(Entity, f32, bool)> {
     let player = ecs.read_storage::<Player>();
     let fov = ecs.read_storage::<Fov>();
     let map = ecs.fetch::<Map>();
@@ -215,7 +468,7 @@ fn visible_targets(ecs: &World, hittable: bool) -> Vec<(Entity, f32, bool)> {
             for ents in map.entities[idx].iter() {
                 for ent in ents.iter() {
                     let t = mobs.get(*ent);
-                    if let Some(_t) = t {
+                    if t.is_some() && reaction_to_player(ecs, *ent) == Reaction::Hostile {
                         let mobpos = Point::new(pos.x, pos.y);
                         let player_pos = positions.get(*player_ent).unwrap();
                         let ppos = Point::new(player_pos.x, player_pos.y);
@@ -242,6 +495,25 @@ fn visible_targets(ecs: &World, hittable: bool) -> Vec<(Entity, f32, bool)> {
     visible_targets
 }
 
+/// Returns the position of the visible hostile that comes after the one at `curr` (ordered by
+/// distance to the player), or the closest one if there's no hostile at `curr`.
+pub fn next_hostile(ecs: &World, curr: Point) -> Option<Point> {
+    let positions = ecs.read_storage::<Position>();
+    let hostiles: Vec<Point> = visible_targets(ecs, false)
+        .iter()
+        .filter_map(|t| positions.get(t.0).copied())
+        .collect();
+
+    if hostiles.is_empty() {
+        return None;
+    }
+
+    match hostiles.iter().position(|p| *p == curr) {
+        Some(i) => Some(hostiles[(i + 1) % hostiles.len()]),
+        None => Some(hostiles[0]),
+    }
+}
+
 /// Switches between the two readied weapons.
 pub fn switch_weapon(ecs: &mut World) -> RunState {
     let mut active_wpn = ecs.write_storage::<ActiveWeapon>();
@@ -307,6 +579,7 @@ enum PossibleContexts {
     Nothing,
     Door,
     Container,
+    Talk(Entity),
     //ExitLevel,
 }
 
@@ -345,13 +618,34 @@ pub fn check_near(ecs: &World, pt: Point, map: &mut Map) -> RunState {
     let idx = map.idx_pt(pt);
     let tile = map.tiles[idx].ttype;
 
-    // Check for entities (e.g. containers).
+    // Check for furniture that can be used (e.g. terminals, beds).
+    let furniture = match &map.entities[idx] {
+        Some(ents) => {
+            let interactives = ecs.read_storage::<Interactive>();
+            ents.iter()
+                .find(|e| interactives.get(**e).is_some())
+                .copied()
+        }
+        None => None,
+    };
+    if let Some(furniture) = furniture {
+        return use_furniture(ecs, furniture, pt, map);
+    }
+
+    // Check for entities (e.g. containers, NPCs).
     match &map.entities[idx] {
         Some(ents) => {
             for ent in ents.iter() {
+                if ecs.read_storage::<Talker>().get(*ent).is_some() {
+                    context = PossibleContexts::Talk(*ent);
+                    continue;
+                }
                 let containers = ecs.read_storage::<Container>();
                 let c = containers.get(*ent);
                 if let Some(_c) = c {
+                    if !try_unlock_container(ecs, *ent) {
+                        return RunState::Waiting;
+                    }
                     let mut selected_pos = ecs.write_storage::<SelectedPosition>();
                     selected_pos
                         .insert(*ent, SelectedPosition { pos: pt })
@@ -366,6 +660,9 @@ pub fn check_near(ecs: &World, pt: Point, map: &mut Map) -> RunState {
     // Check for tiles (e.g. doors).
     match tile {
         TileType::ClosedDoor => {
+            if !try_unlock_door(ecs, map, idx) {
+                return RunState::Waiting;
+            }
             try_door(TileType::ClosedDoor, map, idx);
             context = PossibleContexts::Door;
         }
@@ -378,20 +675,28 @@ pub fn check_near(ecs: &World, pt: Point, map: &mut Map) -> RunState {
 
     match context {
         PossibleContexts::Door => {
-            let mut fov = ecs.write_storage::<Fov>();
-            let ents = ecs.entities();
-            for (_ent, fov) in (&ents, &mut fov).join() {
-                fov.dirty = true;
-            }
+            make_noise(ecs, DOOR_NOISE);
+            dirty_all_fovs(ecs);
             return RunState::PlayerTurn;
         }
         PossibleContexts::Container => {
             return RunState::AccessContainer;
         }
+        PossibleContexts::Talk(npc) => {
+            return talk(ecs, npc);
+        }
         _ => return RunState::Waiting,
     }
 }
 
+fn dirty_all_fovs(ecs: &World) {
+    let mut fov = ecs.write_storage::<Fov>();
+    let ents = ecs.entities();
+    for (_ent, fov) in (&ents, &mut fov).join() {
+        fov.dirty = true;
+    }
+}
+
 fn try_door(ttype: TileType, map: &mut Map, idx: usize) {
     if ttype == TileType::ClosedDoor {
         map.paint_tile(idx, TileType::OpenDoor);
@@ -402,6 +707,185 @@ fn try_door(ttype: TileType, map: &mut Map, idx: usize) {
     map.reveal(idx);
 }
 
+/// Checks if the player carries the key of a lock.
+fn has_key(ecs: &World, lock: &Lock) -> bool {
+    let player = ecs.fetch::<Entity>();
+    let keys = ecs.read_storage::<Key>();
+    let inventory = ecs.read_storage::<Inventory>();
+
+    (&keys, &inventory)
+        .join()
+        .any(|(key, inv)| inv.owner == *player && key.lock_id == lock.id)
+}
+
+/// Returns false if a locked door stays locked, that is, the player doesn't have its key.
+fn try_unlock_door(ecs: &World, map: &mut Map, idx: usize) -> bool {
+    let lock = match map.get_lock(idx) {
+        Some(lock) => lock,
+        None => return true,
+    };
+    let mut log = ecs.fetch_mut::<Log>();
+    if has_key(ecs, &lock) {
+        map.remove_lock(idx);
+        log.add(
+            "You unlock the door.".to_string(),
+            color("BrightWhite", 1.0),
+        );
+        true
+    } else {
+        log.add("The door is locked.".to_string(), color("BrightWhite", 1.0));
+        false
+    }
+}
+
+/// Returns false if a locked container stays locked, that is, the player doesn't have its key.
+fn try_unlock_container(ecs: &World, container: Entity) -> bool {
+    let lock = match ecs.read_storage::<Lock>().get(container) {
+        Some(lock) => *lock,
+        None => return true,
+    };
+    let name = container_name(ecs, container);
+    let mut log = ecs.fetch_mut::<Log>();
+    if has_key(ecs, &lock) {
+        ecs.write_storage::<Lock>().remove(container);
+        log.add(
+            format!("You unlock the {}.", name),
+            color("BrightWhite", 1.0),
+        );
+        true
+    } else {
+        log.add(
+            format!("The {} is locked.", name),
+            color("BrightWhite", 1.0),
+        );
+        false
+    }
+}
+
+fn container_name(ecs: &World, container: Entity) -> String {
+    match ecs.read_storage::<Name>().get(container) {
+        Some(name) => name.name.to_lowercase(),
+        None => "container".to_string(),
+    }
+}
+
+/// Returns the locked container on a tile, if any.
+fn locked_container_at(ecs: &World, map: &Map, idx: usize) -> Option<Entity> {
+    let locks = ecs.read_storage::<Lock>();
+    match &map.entities[idx] {
+        Some(ents) => ents.iter().find(|e| locks.get(**e).is_some()).copied(),
+        None => None,
+    }
+}
+
+/// Picks or forces a lock near the player, asking for a direction if there are many.
+pub fn lock_action(ecs: &mut World) -> RunState {
+    let ppos = *ecs.fetch::<Point>();
+    let mut map = ecs.fetch_mut::<Map>();
+
+    let locked: Vec<Point> = (0..9)
+        .map(|i| if i < 8 { ppos + dir_idx(i) } else { ppos })
+        .filter(|pt| map.in_map_bounds(*pt))
+        .filter(|pt| {
+            let idx = map.idx_pt(*pt);
+            map.is_locked(idx) || locked_container_at(ecs, &map, idx).is_some()
+        })
+        .collect();
+
+    match locked.len() {
+        0 => {
+            let mut log = ecs.fetch_mut::<Log>();
+            log.add(
+                "There's no lock nearby.".to_string(),
+                color("BrightWhite", 1.0),
+            );
+            RunState::Waiting
+        }
+        1 => pick_lock(ecs, locked[0], &mut map),
+        _ => RunState::ChooseLockDir,
+    }
+}
+
+// Picking and forcing succeed if 1d20 (+ the lockpicks' bonus) beats these plus twice the lock's
+// difficulty. Forcing is harder and always loud; picking only makes a bit of noise when it fails.
+const PICK_LOCK_TARGET: i32 = 4;
+const FORCE_LOCK_TARGET: i32 = 6;
+const PICK_NOISE: i32 = 4;
+const FORCE_NOISE: i32 = 12;
+
+/// Tries to open the lock at pt with lockpicks or, if the player has none, by force.
+pub fn pick_lock(ecs: &World, pt: Point, map: &mut Map) -> RunState {
+    let idx = map.idx_pt(pt);
+    let container = locked_container_at(ecs, map, idx);
+    let lock = match (map.get_lock(idx), container) {
+        (Some(lock), _) => lock,
+        (None, Some(c)) => *ecs.read_storage::<Lock>().get(c).unwrap(),
+        (None, None) => return RunState::Waiting,
+    };
+    let what = match container {
+        Some(c) if !map.is_locked(idx) => container_name(ecs, c),
+        _ => "door".to_string(),
+    };
+
+    let bonus = {
+        let player = ecs.fetch::<Entity>();
+        let lockpicks = ecs.read_storage::<Lockpick>();
+        let inventory = ecs.read_storage::<Inventory>();
+        (&lockpicks, &inventory)
+            .join()
+            .filter(|(_, inv)| inv.owner == *player)
+            .map(|(l, _)| l.bonus)
+            .max()
+    };
+    let roll = ecs
+        .write_resource::<RandomNumberGenerator>()
+        .roll_dice(1, 20);
+
+    let (success, noise) = match bonus {
+        Some(bonus) => {
+            let success = roll + bonus > PICK_LOCK_TARGET + lock.difficulty * 2;
+            (success, if success { 0 } else { PICK_NOISE })
+        }
+        None => (roll > FORCE_LOCK_TARGET + lock.difficulty * 2, FORCE_NOISE),
+    };
+
+    {
+        let mut log = ecs.fetch_mut::<Log>();
+        let msg = match (bonus.is_some(), success) {
+            (true, true) => format!("You pick the lock of the {}.", what),
+            (true, false) => format!("You fail to pick the lock of the {}.", what),
+            (false, true) => format!("You force the {} open!", what),
+            (false, false) => format!("You try to force the {}, but it holds.", what),
+        };
+        log.add(msg, color("BrightWhite", 1.0));
+    }
+
+    if success {
+        if map.is_locked(idx) {
+            map.remove_lock(idx);
+            if bonus.is_none() {
+                // The door is broken open.
+                try_door(TileType::ClosedDoor, map, idx);
+                dirty_all_fovs(ecs);
+            }
+        } else if let Some(c) = container {
+            ecs.write_storage::<Lock>().remove(c);
+        }
+    }
+    if noise > 0 {
+        make_noise(ecs, noise);
+    }
+
+    RunState::PlayerTurn
+}
+
+/// The player makes some noise, which may draw the mobs around (see noise.rs).
+pub fn make_noise(ecs: &World, volume: i32) {
+    let player = ecs.fetch::<Entity>();
+    let mut noises = ecs.write_storage::<EmitNoise>();
+    EmitNoise::add_noise(&mut noises, *player, volume);
+}
+
 /// Picks up item from the player's current position.
 pub fn collect_item(ecs: &mut World) -> RunState {
     let ents = ecs.entities();
@@ -429,3 +913,141 @@ pub fn collect_item(ecs: &mut World) -> RunState {
         None => RunState::Waiting,
     }
 }
+
+/// Starts exploring the map automatically, unless there are hostiles in sight.
+pub fn start_auto_explore(ecs: &mut World) -> RunState {
+    if !visible_targets(ecs, false).is_empty() {
+        let mut log = ecs.fetch_mut::<Log>();
+        log.add(
+            "You can't explore with hostiles nearby.".to_string(),
+            color("BrightWhite", 1.0),
+        );
+        return RunState::Waiting;
+    }
+
+    let player = *ecs.fetch::<Entity>();
+    let hp = ecs
+        .read_storage::<BaseStats>()
+        .get(player)
+        .unwrap()
+        .health
+        .hp;
+    let mut explore = ecs.write_storage::<AutoExplore>();
+    explore
+        .insert(player, AutoExplore { hp })
+        .expect("Auto-explore insertion failed");
+
+    RunState::AutoExplore
+}
+
+/// Checks if the player is currently auto-exploring.
+pub fn is_auto_exploring(ecs: &World) -> bool {
+    let player = ecs.fetch::<Entity>();
+    ecs.read_storage::<AutoExplore>().get(*player).is_some()
+}
+
+/// Stops auto-exploring, telling the player why.
+pub fn stop_auto_explore(ecs: &World, msg: &str) -> RunState {
+    let player = ecs.fetch::<Entity>();
+    let mut log = ecs.fetch_mut::<Log>();
+    ecs.write_storage::<AutoExplore>().remove(*player);
+    log.add(msg.to_string(), color("BrightWhite", 1.0));
+    RunState::Waiting
+}
+
+/// Returns the revealed tiles that border unrevealed ones and that the player could step on (or open).
+fn explore_frontier(map: &Map) -> Vec<usize> {
+    let mut frontier = Vec::new();
+    for (idx, tile) in map.tiles.iter().enumerate() {
+        let closed_door = tile.ttype == TileType::ClosedDoor && !map.is_locked(idx);
+        if !tile.revealed || (tile.block && !closed_door) {
+            continue;
+        }
+        let pt = map.idx_pos(idx);
+        let unrevealed = (0..8).any(|i| {
+            let neighbor = pt + dir_idx(i);
+            map.in_map_bounds(neighbor) && !map.tiles[map.idx_pt(neighbor)].revealed
+        });
+        if unrevealed {
+            frontier.push(idx);
+        }
+    }
+    frontier
+}
+
+/// Does a single step of the auto-exploration: picks up items, opens doors or walks towards the
+/// nearest unexplored part of the map. Returns Waiting when it stops.
+pub fn auto_explore(ecs: &mut World) -> RunState {
+    let player = *ecs.fetch::<Entity>();
+    let ppos = *ecs.fetch::<Point>();
+
+    if !visible_targets(ecs, false).is_empty() {
+        return stop_auto_explore(ecs, "You spot a hostile and stop exploring.");
+    }
+
+    let hurt = {
+        let stats = ecs.read_storage::<BaseStats>();
+        let mut explore = ecs.write_storage::<AutoExplore>();
+        let hp = stats.get(player).unwrap().health.hp;
+        let explore = explore.get_mut(player).unwrap();
+        let hurt = hp < explore.hp;
+        explore.hp = hp;
+        hurt
+    };
+    if hurt {
+        return stop_auto_explore(ecs, "You've been hurt!");
+    }
+
+    if AUTO_PICKUP {
+        let has_space = {
+            let capacity = ecs.read_storage::<InventoryCapacity>();
+            let cap = capacity.get(player).unwrap();
+            cap.curr < cap.max
+        };
+        if has_space && collect_item(ecs) == RunState::PlayerTurn {
+            return RunState::PlayerTurn;
+        }
+    }
+
+    let dest = {
+        let mut map = ecs.fetch_mut::<Map>();
+        let frontier = explore_frontier(&map);
+
+        // Open the doors that lead to unexplored areas.
+        for i in 0..8 {
+            let pt = ppos + dir_idx(i);
+            let idx = map.idx_pt(pt);
+            if map.tiles[idx].ttype == TileType::ClosedDoor && frontier.contains(&idx) {
+                return check_near(ecs, pt, &mut map);
+            }
+        }
+
+        let dijkstra = DijkstraMap::new(map.width, map.height, &frontier, &*map, 2000.0);
+        match DijkstraMap::find_lowest_exit(&dijkstra, map.idx_pt(ppos), &*map) {
+            Some(idx) if dijkstra.map[idx] < f32::MAX => Some(map.idx_pos(idx)),
+            _ => None,
+        }
+    };
+
+    match dest {
+        Some(dest) => {
+            let dir = Direction {
+                delta_x: (dest.x - ppos.x) as i8,
+                delta_y: (dest.y - ppos.y) as i8,
+            };
+            move_player(dir, ecs);
+
+            // The move may not have happened (e.g. when slowed by water).
+            let on_exit = {
+                let map = ecs.fetch::<Map>();
+                map.is_exit(map.idx_pt(*ecs.fetch::<Point>()))
+            };
+            if on_exit {
+                // Still let the mobs act on this turn.
+                stop_auto_explore(ecs, "You found the exit.");
+            }
+            RunState::PlayerTurn
+        }
+        None => stop_auto_explore(ecs, "There's nothing left to explore."),
+    }
+}
@@ -0,0 +1,197 @@
+use super::{
+    common::{give_item, has_item, take_item},
+    log::Log,
+    map_gen::Map,
+    raws::{Objective, Quest, RawMaster, Reaction, DIALOGUES, RAWS},
+    Faction, Grudge,
+};
+use crate::utils::colors::*;
+use specs::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+/*
+ *
+ * quests.rs
+ * ---------
+ * The quests given by NPCs (see dialogues.ron): their progress, completion and rewards.
+ * Also keeps the flags set through dialogues, so NPCs remember what was said, and how the
+ * factions regard the player after what they did for them.
+ *
+ */
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum QuestStatus {
+    Active,
+    Done,
+}
+
+#[derive(Debug, Clone)]
+pub struct QuestProgress {
+    pub name: String,
+    pub status: QuestStatus,
+    pub kills: i32,
+}
+
+pub struct QuestLog {
+    pub quests: Vec<QuestProgress>, // In the order they were started.
+    pub flags: HashSet<String>,
+    pub reputation: HashMap<String, Reaction>, // Faction -> how it regards the player now.
+}
+
+impl QuestLog {
+    pub fn new() -> Self {
+        QuestLog {
+            quests: Vec::new(),
+            flags: HashSet::new(),
+            reputation: HashMap::new(),
+        }
+    }
+
+    /// How a faction regards the player: as changed through dialogues, or as defined in the raws.
+    pub fn standing(&self, faction: &str, raws: &RawMaster) -> Reaction {
+        match self.reputation.get(faction) {
+            Some(reaction) => *reaction,
+            None => raws.faction_reaction(faction, "Player"),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&QuestProgress> {
+        self.quests.iter().find(|q| q.name == name)
+    }
+
+    pub fn status(&self, name: &str) -> Option<QuestStatus> {
+        self.get(name).map(|q| q.status)
+    }
+
+    /// Counts a mob killed by the player for the active quests that ask for it.
+    pub fn count_kill(&mut self, mob: &str) {
+        let dialogues = DIALOGUES.lock().unwrap();
+        for progress in self.quests.iter_mut() {
+            if progress.status != QuestStatus::Active {
+                continue;
+            }
+            if let Some(Objective::Kill { mob: target, .. }) =
+                dialogues.get_quest(&progress.name).map(|q| &q.objective)
+            {
+                if target == mob {
+                    progress.kills += 1;
+                }
+            }
+        }
+    }
+}
+
+/// How an NPC regards the player: as its faction does (see QuestLog::standing), unless it holds
+/// a grudge.
+pub fn reaction_to_player(ecs: &World, npc: Entity) -> Reaction {
+    let player = ecs.fetch::<Entity>();
+    if ecs
+        .read_storage::<Grudge>()
+        .get(npc)
+        .is_some_and(|grudge| grudge.against.contains(&player))
+    {
+        return Reaction::Hostile;
+    }
+    match ecs.read_storage::<Faction>().get(npc) {
+        Some(faction) => ecs
+            .fetch::<QuestLog>()
+            .standing(&faction.name, &RAWS.lock().unwrap()),
+        None => Reaction::Neutral,
+    }
+}
+
+fn get_quest(name: &str) -> Option<Quest> {
+    DIALOGUES.lock().unwrap().get_quest(name).cloned()
+}
+
+/// Checks if the objective of an active quest was met.
+pub fn quest_ready(ecs: &World, name: &str) -> bool {
+    let kills = match ecs.fetch::<QuestLog>().get(name) {
+        Some(progress) if progress.status == QuestStatus::Active => progress.kills,
+        _ => return false,
+    };
+    match get_quest(name).map(|q| q.objective) {
+        Some(Objective::Kill { count, .. }) => kills >= count,
+        Some(Objective::Fetch { item }) => has_item(ecs, &item),
+        Some(Objective::Reach { depth }) => ecs.fetch::<Map>().depth >= depth,
+        None => false,
+    }
+}
+
+pub fn start_quest(ecs: &World, name: &str) {
+    let quest = match get_quest(name) {
+        Some(quest) => quest,
+        None => return,
+    };
+    let mut quest_log = ecs.fetch_mut::<QuestLog>();
+    if quest_log.get(name).is_some() {
+        return;
+    }
+    quest_log.quests.push(QuestProgress {
+        name: quest.name.clone(),
+        status: QuestStatus::Active,
+        kills: 0,
+    });
+    ecs.fetch_mut::<Log>().add(
+        format!("New quest: {}.", quest.name),
+        color("BrightYellow", 1.0),
+    );
+}
+
+/// Completes a quest, handing over the fetched item and giving the rewards.
+pub fn complete_quest(ecs: &World, name: &str) {
+    if !quest_ready(ecs, name) {
+        return;
+    }
+    let quest = match get_quest(name) {
+        Some(quest) => quest,
+        None => return,
+    };
+    if let Objective::Fetch { item } = &quest.objective {
+        take_item(ecs, item);
+    }
+    if let Some(progress) = ecs
+        .fetch_mut::<QuestLog>()
+        .quests
+        .iter_mut()
+        .find(|q| q.name == name)
+    {
+        progress.status = QuestStatus::Done;
+    }
+    for item in quest.rewards.iter() {
+        give_item(ecs, item);
+    }
+
+    let mut log = ecs.fetch_mut::<Log>();
+    log.add(
+        format!("Quest completed: {}!", quest.name),
+        color("BrightYellow", 1.0),
+    );
+    if !quest.rewards.is_empty() {
+        log.add(
+            format!("You receive: {}.", quest.rewards.join(", ")),
+            color("BrightWhite", 1.0),
+        );
+    }
+}
+
+/// Completes the quests whose objectives were met, except fetching (items are handed over
+/// through a dialogue).
+pub fn update_quests(ecs: &World) {
+    let active = ecs
+        .fetch::<QuestLog>()
+        .quests
+        .iter()
+        .filter(|q| q.status == QuestStatus::Active)
+        .map(|q| q.name.clone())
+        .collect::<Vec<_>>();
+    for name in active {
+        let fetch = matches!(
+            get_quest(&name).map(|q| q.objective),
+            Some(Objective::Fetch { .. })
+        );
+        if !fetch && quest_ready(ecs, &name) {
+            complete_quest(ecs, &name);
+        }
+    }
+}
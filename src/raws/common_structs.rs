@@ -8,3 +8,9 @@ pub struct Renderable {
     pub bg: String,
     pub layer: i32,
 }
+
+#[derive(Deserialize, Debug)]
+pub struct Light {
+    pub radius: i32,
+    pub color: String,
+}
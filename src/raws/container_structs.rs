@@ -8,4 +8,5 @@ pub struct Container {
     pub renderable: Option<Renderable>,
     pub max_items: u8,
     pub tiers: Vec<u8>,
+    pub loot: Option<String>, // Loot table; if there's one, the tiers aren't used.
 }
@@ -0,0 +1,66 @@
+use super::Reaction;
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Dialogue {
+    pub name: String,
+    pub nodes: Vec<DialogueNode>, // The first node starts the conversation.
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct DialogueNode {
+    pub id: String,
+    pub text: String,
+    pub choices: Vec<DialogueChoice>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct DialogueChoice {
+    pub text: String,
+    pub next: Option<String>, // None -> Ends the conversation.
+    #[serde(default)]
+    pub conditions: Vec<DialogueCondition>, // All of them, for the choice to be shown.
+    #[serde(default)]
+    pub effects: Vec<DialogueEffect>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub enum DialogueCondition {
+    HasItem(String),
+    Flag(String),
+    NotFlag(String),
+    QuestNotStarted(String),
+    QuestActive(String),
+    QuestReady(String), // Active, with its objective already met.
+    QuestDone(String),
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub enum DialogueEffect {
+    GiveItem(String),
+    TakeItem(String),
+    SetFlag(String),
+    RevealMap,
+    StartQuest(String),
+    CompleteQuest(String),
+    Trade,
+    Standing(String, Reaction), // How a faction regards the player from now on.
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Quest {
+    pub name: String,
+    pub descr: String,
+    pub objective: Objective,
+    #[serde(default)]
+    pub rewards: Vec<String>, // Items.
+}
+
+/// Kill and Reach quests are completed as soon as the objective is met; fetched items have to
+/// be handed over through a dialogue (see CompleteQuest).
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub enum Objective {
+    Kill { mob: String, count: i32 },
+    Fetch { item: String },
+    Reach { depth: i32 },
+}
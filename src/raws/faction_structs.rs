@@ -0,0 +1,17 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+
+#[derive(Deserialize, Debug)]
+pub struct Faction {
+    pub name: String,
+    pub reactions: HashMap<String, Reaction>, // Other faction -> reaction; "Default" for the rest.
+}
+
+/// How the members of a faction behave towards someone.
+#[derive(Deserialize, Debug, PartialEq, Copy, Clone)]
+pub enum Reaction {
+    Hostile,
+    Neutral,
+    Friendly,
+    Fearful,
+}
@@ -1,4 +1,4 @@
-use super::Renderable;
+use super::{Light, Renderable};
 use serde::Deserialize;
 
 #[derive(Deserialize, Debug)]
@@ -7,4 +7,24 @@ pub struct Furniture {
     pub descr: String,
     pub blocker: Option<bool>,
     pub renderable: Option<Renderable>,
+    pub interact: Option<Interaction>,
+    pub light: Option<Light>,
+}
+
+/// What happens when the player uses a piece of furniture (see furniture.rs).
+#[derive(Deserialize, Debug)]
+pub enum Interaction {
+    Read {
+        lore: Vec<String>, // One of these is shown each time it's read.
+        #[serde(default)]
+        reveal_map: bool,
+    },
+    Rest {
+        heal: i32, // Per turn.
+        turns: i32,
+    },
+    Push,
+    Search {
+        loot: String, // Loot table.
+    },
 }
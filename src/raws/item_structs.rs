@@ -1,4 +1,4 @@
-use super::Renderable;
+use super::{Light, Renderable};
 use serde::Deserialize;
 use std::collections::HashMap;
 
@@ -7,6 +7,8 @@ pub struct Item {
     pub name: String,
     pub descr: String,
     pub tier: u8,
+    pub price: Option<i32>, // None -> Based on the tier (see RawMaster::item_price).
+    pub currency: Option<i32>, // How much one of it is worth, if it's money.
     pub renderable: Option<Renderable>,
     pub consumable: Option<Consumable>,
     pub equipable: Option<Equipable>,
@@ -14,6 +16,8 @@ pub struct Item {
     pub missile: Option<Missile>,
     pub ammunition: Option<Ammunition>,
     pub armor: Option<Armor>,
+    pub lockpick: Option<Lockpick>,
+    pub light: Option<Light>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -34,6 +38,9 @@ pub struct Missile {
     pub class: String,
     pub ammo_type: String,
     pub max_ammo: i32,
+    #[serde(default)]
+    pub incendiary: bool, // Sets the terrain on fire where it hits.
+    pub blast: Option<i32>, // Radius of the explosion (destroys walls) where it hits.
 }
 
 #[derive(Deserialize, Debug)]
@@ -51,3 +58,8 @@ pub struct Armor {
 pub struct Equipable {
     pub slot: String,
 }
+
+#[derive(Deserialize, Debug)]
+pub struct Lockpick {
+    pub bonus: i32, // Added to the rolls for picking locks.
+}
@@ -0,0 +1,71 @@
+use serde::Deserialize;
+
+/// Every named command the player can bind to a key.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub enum Action {
+    MoveEast,
+    MoveWest,
+    MoveNorth,
+    MoveSouth,
+    MoveNortheast,
+    MoveNorthwest,
+    MoveSoutheast,
+    MoveSouthwest,
+    Wait,
+    Fire,
+    SwitchWeapon,
+    Pickup,
+    Inventory,
+    Equipment,
+    Reload,
+    Interact,
+    PickLock,
+    AutoExplore,
+    MapOverview,
+    Look,
+    QuestLog,
+    NextHostile,
+    Cancel,
+    Help,
+    ToggleScanlines,
+    Colorscheme(String),
+}
+
+impl Action {
+    pub fn get_name(&self) -> String {
+        match self {
+            Action::MoveEast => "Move east".to_string(),
+            Action::MoveWest => "Move west".to_string(),
+            Action::MoveNorth => "Move north / previous target".to_string(),
+            Action::MoveSouth => "Move south / next target".to_string(),
+            Action::MoveNortheast => "Move northeast".to_string(),
+            Action::MoveNorthwest => "Move northwest".to_string(),
+            Action::MoveSoutheast => "Move southeast".to_string(),
+            Action::MoveSouthwest => "Move southwest".to_string(),
+            Action::Wait => "Wait / here".to_string(),
+            Action::Fire => "Target and fire".to_string(),
+            Action::SwitchWeapon => "Switch weapon".to_string(),
+            Action::Pickup => "Pick up item".to_string(),
+            Action::Inventory => "Inventory".to_string(),
+            Action::Equipment => "Equipment".to_string(),
+            Action::Reload => "Reload".to_string(),
+            Action::Interact => "Contextual action".to_string(),
+            Action::PickLock => "Pick/force a lock".to_string(),
+            Action::AutoExplore => "Auto-explore".to_string(),
+            Action::MapOverview => "Map overview".to_string(),
+            Action::Look => "Look".to_string(),
+            Action::QuestLog => "Quest log".to_string(),
+            Action::NextHostile => "Next hostile (look)".to_string(),
+            Action::Cancel => "Cancel".to_string(),
+            Action::Help => "Help".to_string(),
+            Action::ToggleScanlines => "Toggle scanlines".to_string(),
+            Action::Colorscheme(name) => format!("Colorscheme: {}", name),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Binding {
+    pub keys: Vec<String>,
+    pub action: Action,
+}
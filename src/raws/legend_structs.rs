@@ -0,0 +1,23 @@
+use serde::Deserialize;
+
+/// An entity drawn on the entities layer of a .xp map.
+#[derive(Deserialize, Debug, Clone)]
+pub struct EntityGlyph {
+    pub glyph: char,
+    pub fg: Option<(u8, u8, u8)>, // None -> Any colour
+    pub name: String,             // Raw name of a mob, item, container or furniture.
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub enum MetaTag {
+    Spawn,
+    Exit,
+    Region(String), // Tiles with the same tag make a named region.
+}
+
+/// A tag drawn on the metadata layer of a .xp map.
+#[derive(Deserialize, Debug, Clone)]
+pub struct MetaGlyph {
+    pub glyph: char,
+    pub meta: MetaTag,
+}
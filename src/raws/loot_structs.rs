@@ -0,0 +1,24 @@
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct LootTable {
+    pub name: String,
+    pub rolls: (i32, i32), // How many times (min, max) the entries are rolled.
+    #[serde(default)]
+    pub nothing: i32, // Weight of getting nothing on a roll.
+    pub entries: Vec<LootEntry>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct LootEntry {
+    pub item: Option<String>,  // An item...
+    pub table: Option<String>, // ... or another loot table, rolled in turn.
+    #[serde(default)]
+    pub weight: i32,
+    #[serde(default)]
+    pub depth_bonus: i32, // Added to the weight on each level of depth.
+    pub min_max_depth: Option<(i32, i32)>, // None -> Any
+    pub quantity: Option<(i32, i32)>,      // None -> 1
+    #[serde(default)]
+    pub guaranteed: bool, // Always dropped, besides the rolls.
+}
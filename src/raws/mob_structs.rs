@@ -7,11 +7,15 @@ pub struct Mob {
     pub name: String,
     pub descr: String,
     pub mob_type: String,
+    pub faction: Option<String>, // None -> the mob type.
     pub renderable: Option<Renderable>,
     pub fov_range: i32,
     pub blocker: bool,
     pub stats: Stats,
     pub equips: Option<Equipment>,
+    pub loot: Option<String>,     // Loot table, dropped on death.
+    pub dialogue: Option<String>, // See dialogues.ron.
+    pub trader: Option<String>,   // Loot table of what it sells.
 }
 
 #[derive(Deserialize, Debug)]
@@ -20,13 +20,44 @@ mod furniture_structs;
 pub use furniture_structs::*;
 mod spawn_structs;
 pub use spawn_structs::*;
+mod loot_structs;
+pub use loot_structs::*;
+mod faction_structs;
+pub use faction_structs::*;
+mod keybinding_structs;
+pub use keybinding_structs::*;
+mod rawkeys;
+pub use rawkeys::*;
+mod vault_structs;
+pub use vault_structs::*;
+mod rawvaults;
+pub use rawvaults::*;
+mod legend_structs;
+pub use legend_structs::*;
+mod rawlegend;
+pub use rawlegend::*;
+mod dialogue_structs;
+pub use dialogue_structs::*;
+mod rawdialogues;
+pub use rawdialogues::*;
 
 embedded_resource!(RAW_COLORS, "../../resources/raws/colors.ron");
 embedded_resource!(RAW, "../../resources/raws/raws.ron");
+embedded_resource!(RAW_KEYS, "../../resources/raws/keybindings.ron");
+embedded_resource!(RAW_VAULTS, "../../resources/raws/vaults.ron");
+embedded_resource!(RAW_LEGEND, "../../resources/raws/rex_legend.ron");
+embedded_resource!(RAW_DIALOGUES, "../../resources/raws/dialogues.ron");
+
+// A keybindings file in the working directory overrides the default one.
+const KEYBINDINGS_FILE: &str = "keybindings.ron";
 
 lazy_static! {
     pub static ref COLORS: Mutex<RawColors> = Mutex::new(RawColors::empty());
     pub static ref RAWS: Mutex<RawMaster> = Mutex::new(RawMaster::empty());
+    pub static ref KEYBINDINGS: Mutex<RawKeys> = Mutex::new(RawKeys::empty());
+    pub static ref VAULTS: Mutex<RawVaults> = Mutex::new(RawVaults::empty());
+    pub static ref REX_LEGEND: Mutex<RawLegend> = Mutex::new(RawLegend::empty());
+    pub static ref DIALOGUES: Mutex<RawDialogues> = Mutex::new(RawDialogues::empty());
 }
 
 #[derive(Deserialize, Debug)]
@@ -36,6 +67,8 @@ pub struct Raws {
     pub containers: Vec<Container>,
     pub furnitures: Vec<Furniture>,
     pub spawn_table: Vec<SpawnTable>,
+    pub loot_tables: Vec<LootTable>,
+    pub factions: Vec<Faction>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -43,9 +76,35 @@ pub struct Colors {
     pub colorschemes: Vec<Colorscheme>,
 }
 
+#[derive(Deserialize, Debug)]
+pub struct Keybindings {
+    pub bindings: Vec<Binding>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Vaults {
+    pub vaults: Vec<Vault>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RexLegend {
+    pub entities: Vec<EntityGlyph>,
+    pub metadata: Vec<MetaGlyph>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Dialogues {
+    pub dialogues: Vec<Dialogue>,
+    pub quests: Vec<Quest>,
+}
+
 pub fn load_raws() {
     link_resource!(RAW_COLORS, "resources/colors.ron");
     link_resource!(RAW, "resources/raws.ron");
+    link_resource!(RAW_KEYS, "resources/keybindings.ron");
+    link_resource!(RAW_VAULTS, "resources/vaults.ron");
+    link_resource!(RAW_LEGEND, "resources/rex_legend.ron");
+    link_resource!(RAW_DIALOGUES, "resources/dialogues.ron");
 
     let raw_string_colors = get_raw_string("resources/colors.ron".to_string());
     let raw_string_etc = get_raw_string("resources/raws.ron".to_string());
@@ -64,6 +123,37 @@ pub fn load_raws() {
 
     let decoder: Raws = ron::de::from_str(&raw_string_etc).expect("Unable to parse RON.");
     RAWS.lock().unwrap().load(decoder);
+
+    let raw_string_vaults = get_raw_string("resources/vaults.ron".to_string());
+    let decoder_vaults: Vaults =
+        ron::de::from_str(raw_string_vaults).expect("Unable to parse RON.");
+    VAULTS.lock().unwrap().load(decoder_vaults);
+
+    let raw_string_legend = get_raw_string("resources/rex_legend.ron".to_string());
+    let decoder_legend: RexLegend =
+        ron::de::from_str(raw_string_legend).expect("Unable to parse RON.");
+    REX_LEGEND.lock().unwrap().load(decoder_legend);
+
+    let raw_string_dialogues = get_raw_string("resources/dialogues.ron".to_string());
+    let decoder_dialogues: Dialogues =
+        ron::de::from_str(raw_string_dialogues).expect("Unable to parse RON.");
+    DIALOGUES.lock().unwrap().load(decoder_dialogues);
+
+    load_keybindings();
+}
+
+fn load_keybindings() {
+    let raw_string_keys = match std::fs::read_to_string(KEYBINDINGS_FILE) {
+        Ok(s) => {
+            println!("Using key bindings from {}.", KEYBINDINGS_FILE);
+            s
+        }
+        Err(_) => get_raw_string("resources/keybindings.ron".to_string()).to_string(),
+    };
+
+    let decoder_keys: Keybindings =
+        ron::de::from_str(&raw_string_keys).expect("Unable to parse RON.");
+    KEYBINDINGS.lock().unwrap().load(decoder_keys);
 }
 
 fn get_raw_string(path: String) -> &'static str {
@@ -26,7 +26,10 @@ impl RawColors {
     }
 
     pub fn set_curr_colorscheme(&mut self, colorscheme: &str) {
-        self.curr_colorscheme = self.color_index[colorscheme];
+        match self.color_index.get(colorscheme) {
+            Some(idx) => self.curr_colorscheme = *idx,
+            None => println!("Colorscheme {} not found.", colorscheme),
+        }
     }
 
     pub fn get_curr_colorscheme(&self) -> &Colorscheme {
@@ -0,0 +1,51 @@
+use super::{Dialogue, Dialogues, Quest};
+use std::collections::HashMap;
+
+pub struct RawDialogues {
+    pub raws: Dialogues,
+    dialogue_index: HashMap<String, usize>,
+    quest_index: HashMap<String, usize>,
+}
+
+impl RawDialogues {
+    pub fn empty() -> Self {
+        RawDialogues {
+            raws: Dialogues {
+                dialogues: Vec::new(),
+                quests: Vec::new(),
+            },
+            dialogue_index: HashMap::new(),
+            quest_index: HashMap::new(),
+        }
+    }
+
+    pub fn load(&mut self, raws: Dialogues) {
+        self.raws = raws;
+        for (i, dialogue) in self.raws.dialogues.iter().enumerate() {
+            self.dialogue_index.insert(dialogue.name.clone(), i);
+        }
+        for (i, quest) in self.raws.quests.iter().enumerate() {
+            self.quest_index.insert(quest.name.clone(), i);
+        }
+    }
+
+    pub fn dialogue_idx(&self, name: &str) -> Option<usize> {
+        self.dialogue_index.get(name).copied()
+    }
+
+    pub fn get_dialogue(&self, idx: usize) -> &Dialogue {
+        &self.raws.dialogues[idx]
+    }
+
+    /// Index of a node of a dialogue, by its id.
+    pub fn node_idx(&self, dialogue: usize, id: &str) -> Option<usize> {
+        self.raws.dialogues[dialogue]
+            .nodes
+            .iter()
+            .position(|node| node.id == id)
+    }
+
+    pub fn get_quest(&self, name: &str) -> Option<&Quest> {
+        self.quest_index.get(name).map(|i| &self.raws.quests[*i])
+    }
+}
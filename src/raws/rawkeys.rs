@@ -0,0 +1,91 @@
+use super::{Action, Keybindings};
+use bracket_lib::prelude::{BTerm, VirtualKeyCode};
+use std::collections::HashMap;
+
+pub struct RawKeys {
+    pub raws: Keybindings,
+    pub key_index: HashMap<String, usize>,
+}
+
+impl RawKeys {
+    pub fn empty() -> Self {
+        RawKeys {
+            raws: Keybindings {
+                bindings: Vec::new(),
+            },
+            key_index: HashMap::new(),
+        }
+    }
+
+    pub fn load(&mut self, raws: Keybindings) {
+        self.raws = raws;
+        self.key_index.clear();
+        for (i, binding) in self.raws.bindings.iter().enumerate() {
+            for key in binding.keys.iter() {
+                let key = normalize_key(key);
+                if self.key_index.contains_key(&key) {
+                    println!(
+                        "Key {} is bound more than once; using the last binding.",
+                        key
+                    );
+                }
+                self.key_index.insert(key, i);
+            }
+        }
+    }
+
+    pub fn get_action(&self, key: &str) -> Option<Action> {
+        self.key_index
+            .get(key)
+            .map(|i| self.raws.bindings[*i].action.clone())
+    }
+}
+
+/// Builds the key string (e.g. "Shift+F") of a pressed key with its modifiers.
+pub fn key_string(key: VirtualKeyCode, shift: bool, control: bool, alt: bool) -> String {
+    let mut s = String::new();
+    if control {
+        s.push_str("Ctrl+");
+    }
+    if alt {
+        s.push_str("Alt+");
+    }
+    if shift {
+        s.push_str("Shift+");
+    }
+    s.push_str(&format!("{:?}", key));
+    s
+}
+
+/// Puts the modifiers of a key string from the keybindings file in the same order used by
+/// key_string(), so "shift+ctrl+F" and "Ctrl+Shift+F" are the same binding.
+fn normalize_key(key: &str) -> String {
+    let mut parts: Vec<&str> = key.split('+').map(|p| p.trim()).collect();
+    let name = parts.pop().unwrap_or("");
+    let mods: Vec<String> = parts.iter().map(|p| p.to_lowercase()).collect();
+    let has = |m: &[&str]| mods.iter().any(|p| m.contains(&p.as_str()));
+    let mut s = String::new();
+    if has(&["ctrl", "control"]) {
+        s.push_str("Ctrl+");
+    }
+    if has(&["alt"]) {
+        s.push_str("Alt+");
+    }
+    if has(&["shift"]) {
+        s.push_str("Shift+");
+    }
+    s.push_str(name);
+    s
+}
+
+/// Returns the action bound to the key pressed in this tick, if any.
+pub fn get_action(term: &BTerm) -> Option<Action> {
+    match term.key {
+        None => None,
+        Some(key) => {
+            let keys = super::KEYBINDINGS.lock().unwrap();
+            let pressed = key_string(key, term.shift, term.control, term.alt);
+            keys.get_action(&pressed)
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use super::{MetaTag, RexLegend};
+
+pub struct RawLegend {
+    pub raws: RexLegend,
+}
+
+impl RawLegend {
+    pub fn empty() -> Self {
+        RawLegend {
+            raws: RexLegend {
+                entities: Vec::new(),
+                metadata: Vec::new(),
+            },
+        }
+    }
+
+    pub fn load(&mut self, raws: RexLegend) {
+        self.raws = raws;
+    }
+
+    /// Raw name of the entity drawn with this glyph and colour.
+    /// An entry with the exact colour wins over one that accepts any colour.
+    pub fn get_entity(&self, glyph: char, fg: (u8, u8, u8)) -> Option<&str> {
+        let mut any_colour = None;
+        for entry in self.raws.entities.iter().filter(|e| e.glyph == glyph) {
+            match entry.fg {
+                Some(color) if color == fg => return Some(&entry.name),
+                None if any_colour.is_none() => any_colour = Some(entry.name.as_str()),
+                _ => {}
+            }
+        }
+        any_colour
+    }
+
+    /// Glyph and colour to draw an entity with (the reverse of get_entity).
+    pub fn get_glyph(&self, name: &str) -> Option<(char, (u8, u8, u8))> {
+        self.raws
+            .entities
+            .iter()
+            .find(|e| e.name == name)
+            .map(|e| (e.glyph, e.fg.unwrap_or((255, 255, 255))))
+    }
+
+    /// Glyph of the first metadata entry with this tag.
+    pub fn get_meta_glyph(&self, meta: &MetaTag) -> Option<char> {
+        self.raws
+            .metadata
+            .iter()
+            .find(|m| m.meta == *meta)
+            .map(|m| m.glyph)
+    }
+
+    pub fn get_meta(&self, glyph: char) -> Option<&MetaTag> {
+        self.raws
+            .metadata
+            .iter()
+            .find(|m| m.glyph == glyph)
+            .map(|m| &m.meta)
+    }
+}
@@ -1,8 +1,9 @@
-use super::{common_structs, Raws};
+use super::{common_structs, Interaction, LootEntry, Raws, Reaction};
 use crate::components::{
-    AmmoType, Ammunition, Armor, Attack, BaseStats, Blocker, Consumable, Container, Description,
-    EquipSlot, Equipable, Fov, Health, Item, MeleeWeapon, MeleeWeaponClass, MissileWeapon,
-    MissileWeaponClass, Mob, Name, Position, Renderable,
+    AmmoType, Ammunition, Armor, Attack, Awareness, AwarenessState, BaseStats, Blocker, Consumable,
+    Container, Currency, Description, EquipSlot, Equipable, Explosive, Faction, Fov, FurnitureUse,
+    Health, Incendiary, Interactive, Item, LightSource, Lockpick, MeleeWeapon, MeleeWeaponClass,
+    MissileWeapon, MissileWeaponClass, Mob, Name, Position, Renderable, Talker, Trader,
 };
 use crate::map_gen::map::MapType;
 use crate::spawner::SpawnTable;
@@ -12,6 +13,9 @@ use specs::prelude::*;
 use std::collections::HashMap;
 use std::str::FromStr;
 
+/// Price of a tier 0 item; it doubles with each tier.
+const BASE_PRICE: i32 = 5;
+
 #[derive(Debug)]
 pub struct RawMaster {
     pub raws: Raws,
@@ -20,6 +24,8 @@ pub struct RawMaster {
     furniture_index: HashMap<String, usize>,
     mob_index: HashMap<String, usize>,
     spawn_index: HashMap<String, usize>,
+    loot_index: HashMap<String, usize>,
+    faction_index: HashMap<String, HashMap<String, Reaction>>,
 }
 
 impl RawMaster {
@@ -31,12 +37,16 @@ impl RawMaster {
                 furnitures: Vec::new(),
                 mobs: Vec::new(),
                 spawn_table: Vec::new(),
+                loot_tables: Vec::new(),
+                factions: Vec::new(),
             },
             item_index: HashMap::new(),
             container_index: HashMap::new(),
             furniture_index: HashMap::new(),
             mob_index: HashMap::new(),
             spawn_index: HashMap::new(),
+            loot_index: HashMap::new(),
+            faction_index: HashMap::new(),
         }
     }
 
@@ -58,6 +68,77 @@ impl RawMaster {
         for (i, spawn) in self.raws.spawn_table.iter().enumerate() {
             self.spawn_index.insert(spawn.name.clone(), i);
         }
+        for (i, table) in self.raws.loot_tables.iter().enumerate() {
+            self.loot_index.insert(table.name.clone(), i);
+        }
+        for faction in self.raws.factions.iter() {
+            self.faction_index
+                .insert(faction.name.clone(), faction.reactions.clone());
+        }
+
+        if let Err(e) = self.check_loot_tables() {
+            panic!("Bad loot table: {}", e);
+        }
+    }
+
+    /// Makes sure every loot table can be rolled: its ranges go from min to max and every item
+    /// and table it (or anything else) names exists.
+    fn check_loot_tables(&self) -> Result<(), String> {
+        let check_range = |table: &str, what: &str, (min, max): (i32, i32)| {
+            if min < 0 || min > max {
+                return Err(format!("{} has {} ({}, {}).", table, what, min, max));
+            }
+            Ok(())
+        };
+        let check_table = |from: &str, table: &str| {
+            if !self.loot_index.contains_key(table) {
+                return Err(format!("{} refers to a missing table, {}.", from, table));
+            }
+            Ok(())
+        };
+
+        for table in self.raws.loot_tables.iter() {
+            check_range(&table.name, "rolls", table.rolls)?;
+            for entry in table.entries.iter() {
+                match (&entry.item, &entry.table) {
+                    (Some(item), None) => {
+                        if !self.item_index.contains_key(item) {
+                            return Err(format!("{} has an unknown item, {}.", table.name, item));
+                        }
+                    }
+                    (None, Some(other)) => check_table(&table.name, other)?,
+                    _ => {
+                        return Err(format!(
+                            "{} has an entry that isn't either an item or a table.",
+                            table.name
+                        ))
+                    }
+                }
+                if let Some(quantity) = entry.quantity {
+                    check_range(&table.name, "quantity", quantity)?;
+                }
+                if let Some(depth) = entry.min_max_depth {
+                    check_range(&table.name, "min_max_depth", depth)?;
+                }
+            }
+        }
+
+        for mob in self.raws.mobs.iter() {
+            for table in mob.loot.iter().chain(mob.trader.iter()) {
+                check_table(&mob.name, table)?;
+            }
+        }
+        for container in self.raws.containers.iter() {
+            if let Some(table) = &container.loot {
+                check_table(&container.name, table)?;
+            }
+        }
+        for furniture in self.raws.furnitures.iter() {
+            if let Some(Interaction::Search { loot }) = &furniture.interact {
+                check_table(&furniture.name, loot)?;
+            }
+        }
+        Ok(())
     }
 
     pub fn get_renderable(&self, name: &str) -> &Option<common_structs::Renderable> {
@@ -76,6 +157,33 @@ impl RawMaster {
 
         &None
     }
+
+    /// What an item is worth; pricier the higher its tier, unless its raw says otherwise.
+    pub fn item_price(&self, name: &str) -> i32 {
+        match self.item_index.get(name) {
+            Some(i) => {
+                let item = &self.raws.items[*i];
+                item.price
+                    .unwrap_or(BASE_PRICE * 2_i32.pow(item.tier as u32))
+            }
+            None => 0,
+        }
+    }
+
+    pub fn is_loot_table(&self, name: &str) -> bool {
+        self.loot_index.contains_key(name)
+    }
+
+    /// How the members of a faction react to someone of another faction.
+    pub fn faction_reaction(&self, faction: &str, other: &str) -> Reaction {
+        match self.faction_index.get(faction) {
+            Some(reactions) => match reactions.get(other) {
+                Some(reaction) => *reaction,
+                None => *reactions.get("Default").unwrap_or(&Reaction::Neutral),
+            },
+            None => Reaction::Neutral,
+        }
+    }
 }
 
 fn set_renderable(render: &common_structs::Renderable) -> Renderable {
@@ -86,6 +194,13 @@ fn set_renderable(render: &common_structs::Renderable) -> Renderable {
     }
 }
 
+fn set_light(light: &common_structs::Light) -> LightSource {
+    LightSource {
+        radius: light.radius,
+        color: color(&light.color, 1.0).to_rgb(),
+    }
+}
+
 pub fn get_random_possible_equips(
     name: &str,
     raws: &RawMaster,
@@ -127,6 +242,100 @@ pub fn get_random_possible_equips(
     None
 }
 
+// Nested loot tables deeper than this are ignored, in case a table ends up containing itself.
+const MAX_LOOT_NESTING: i32 = 5;
+
+/// The loot table a mob drops when it dies, if any.
+pub fn get_mob_loot(name: &str, raws: &RawMaster) -> Option<String> {
+    if raws.mob_index.contains_key(name) {
+        return raws.raws.mobs[raws.mob_index[name]].loot.clone();
+    }
+    None
+}
+
+/// Rolls a loot table, returning the names of the items dropped (one per unit).
+pub fn roll_loot(
+    table: &str,
+    depth: i32,
+    raws: &RawMaster,
+    rng: &mut RandomNumberGenerator,
+) -> Vec<String> {
+    roll_loot_nested(table, depth, 0, raws, rng)
+}
+
+fn roll_loot_nested(
+    table: &str,
+    depth: i32,
+    nesting: i32,
+    raws: &RawMaster,
+    rng: &mut RandomNumberGenerator,
+) -> Vec<String> {
+    let mut loot = Vec::new();
+    if nesting > MAX_LOOT_NESTING {
+        return loot;
+    }
+    // Every table named in the raws exists (see check_loot_tables).
+    let table = &raws.raws.loot_tables[raws.loot_index[table]];
+
+    let mut candidates: Vec<(&LootEntry, i32)> = Vec::new();
+    for entry in table.entries.iter() {
+        if !entry
+            .min_max_depth
+            .is_none_or(|(min, max)| depth >= min && depth <= max)
+        {
+            continue;
+        }
+        if entry.guaranteed {
+            loot.extend(roll_loot_entry(entry, depth, nesting, raws, rng));
+        } else {
+            let weight = entry.weight + entry.depth_bonus * depth;
+            if weight > 0 {
+                candidates.push((entry, weight));
+            }
+        }
+    }
+
+    let total_weight = table.nothing + candidates.iter().map(|(_, w)| w).sum::<i32>();
+    if total_weight <= 0 {
+        return loot;
+    }
+    let n_rolls = rng.range(table.rolls.0, table.rolls.1 + 1);
+    for _ in 0..n_rolls {
+        let mut roll = rng.range(0, total_weight) - table.nothing;
+        if roll < 0 {
+            continue;
+        }
+        for (entry, weight) in candidates.iter() {
+            if roll < *weight {
+                loot.extend(roll_loot_entry(entry, depth, nesting, raws, rng));
+                break;
+            }
+            roll -= weight;
+        }
+    }
+
+    loot
+}
+
+fn roll_loot_entry(
+    entry: &LootEntry,
+    depth: i32,
+    nesting: i32,
+    raws: &RawMaster,
+    rng: &mut RandomNumberGenerator,
+) -> Vec<String> {
+    let mut loot = Vec::new();
+    let (min, max) = entry.quantity.unwrap_or((1, 1));
+    for _ in 0..rng.range(min, max + 1) {
+        if let Some(item) = &entry.item {
+            loot.push(item.to_string());
+        } else if let Some(table) = &entry.table {
+            loot.extend(roll_loot_nested(table, depth, nesting + 1, raws, rng));
+        }
+    }
+    loot
+}
+
 pub fn get_items_tier(tier: u8, raws: &RawMaster) -> Vec<String> {
     let items = &raws.raws.items;
     items
@@ -197,6 +406,7 @@ pub fn spawn_container(
         ent = ent.with(Container {
             tiers: container.tiers.clone(),
             max_items: container.max_items,
+            loot: container.loot.clone(),
         });
 
         if let Some(renderable) = &container.renderable {
@@ -330,22 +540,31 @@ pub fn spawn_item(
                     range: 0,
                 };
 
-                match missile.class.as_str() {
-                    "pistol" => {
-                        ent = ent.with(MissileWeapon {
-                            stats: weapon_stats,
-                            class: MissileWeaponClass::Pistol,
-                            ammo: Ammunition {
-                                max_ammo: missile.max_ammo,
-                                ammo: missile.max_ammo,
-                                ammo_type: AmmoType::from_str(&missile.ammo_type).unwrap(),
-                            },
-                        })
-                    }
+                let class = match missile.class.as_str() {
+                    "pistol" => MissileWeaponClass::Pistol,
+                    "heavy" => MissileWeaponClass::Heavy,
                     _ => return None,
+                };
+                ent = ent.with(MissileWeapon {
+                    stats: weapon_stats,
+                    class,
+                    ammo: Ammunition {
+                        max_ammo: missile.max_ammo,
+                        ammo: missile.max_ammo,
+                        ammo_type: AmmoType::from_str(&missile.ammo_type).unwrap(),
+                    },
+                });
+                if missile.incendiary {
+                    ent = ent.with(Incendiary {});
+                }
+                if let Some(radius) = missile.blast {
+                    ent = ent.with(Explosive { radius });
                 }
             }
         }
+        if let Some(amount) = item.currency {
+            ent = ent.with(Currency { amount });
+        }
         if let Some(ammo) = &item.ammunition {
             ent = ent.with(Ammunition {
                 max_ammo: ammo.ammo,
@@ -358,6 +577,14 @@ pub fn spawn_item(
                 defense: armor.defense,
             })
         }
+        if let Some(lockpick) = &item.lockpick {
+            ent = ent.with(Lockpick {
+                bonus: lockpick.bonus,
+            })
+        }
+        if let Some(light) = &item.light {
+            ent = ent.with(set_light(light));
+        }
 
         return Some(ent.build());
     }
@@ -390,6 +617,29 @@ pub fn spawn_furniture(
             ent = ent.with(set_renderable(renderable));
         }
 
+        if let Some(interaction) = &furniture.interact {
+            let action = match interaction {
+                Interaction::Read { lore, reveal_map } => FurnitureUse::Read {
+                    lore: lore.clone(),
+                    reveal_map: *reveal_map,
+                },
+                Interaction::Rest { heal, turns } => FurnitureUse::Rest {
+                    heal: *heal,
+                    turns: *turns,
+                },
+                Interaction::Push => FurnitureUse::Push,
+                Interaction::Search { loot } => FurnitureUse::Search { loot: loot.clone() },
+            };
+            ent = ent.with(Interactive {
+                action,
+                used: false,
+            });
+        }
+
+        if let Some(light) = &furniture.light {
+            ent = ent.with(set_light(light));
+        }
+
         Some(ent.build());
     }
     None
@@ -420,9 +670,28 @@ pub fn spawn_mob(
             dirty: true,
             visible_pos: Vec::new(),
         });
+        ent = ent.with(Awareness {
+            state: AwarenessState::Wandering,
+        });
+        ent = ent.with(Faction {
+            name: match &mob.faction {
+                Some(faction) => faction.clone(),
+                None => mob.mob_type.clone(),
+            },
+        });
         if mob.blocker {
             ent = ent.with(Blocker {});
         }
+        if let Some(dialogue) = &mob.dialogue {
+            ent = ent.with(Talker {
+                dialogue: dialogue.clone(),
+            });
+        }
+        if let Some(stock) = &mob.trader {
+            ent = ent.with(Trader {
+                stock: stock.clone(),
+            });
+        }
 
         let mut attack_stats = Attack {
             base_damage: "1d3".to_string(),
@@ -0,0 +1,36 @@
+use super::{Vault, Vaults};
+use crate::map_gen::map::MapType;
+
+pub struct RawVaults {
+    pub raws: Vaults,
+}
+
+impl RawVaults {
+    pub fn empty() -> Self {
+        RawVaults {
+            raws: Vaults { vaults: Vec::new() },
+        }
+    }
+
+    pub fn load(&mut self, raws: Vaults) {
+        self.raws = raws;
+    }
+
+    /// Vaults that can be placed on a map of this type at this depth.
+    pub fn get_vaults(&self, maptype: MapType, depth: i32) -> Vec<Vault> {
+        self.raws
+            .vaults
+            .iter()
+            .filter(|v| {
+                v.min_max_depth
+                    .is_none_or(|(min, max)| depth >= min && depth <= max)
+            })
+            .filter(|v| {
+                v.map_types
+                    .as_ref()
+                    .is_none_or(|types| types.contains(&maptype.to_string()))
+            })
+            .cloned()
+            .collect()
+    }
+}
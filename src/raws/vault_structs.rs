@@ -0,0 +1,12 @@
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Vault {
+    pub name: String,
+    pub layout: Option<Vec<String>>, // Rows of map characters (see Map::paint_tile_char).
+    pub xp: Option<String>,          // Or a .xp file with the layout on its first layer.
+    #[serde(default)]
+    pub legend: Vec<(char, String)>, // Characters that spawn an entity (raw name) on the floor.
+    pub map_types: Option<Vec<String>>, // None -> Any
+    pub min_max_depth: Option<(i32, i32)>, // None -> Any
+}
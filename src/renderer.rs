@@ -1,6 +1,7 @@
 use super::{
-    map_gen::Map, raws::*, ui::*, utils::colors::*, Name, Position, Remains, Renderable, RunState,
-    Target, WINDOW_HEIGHT, WINDOW_WIDTH, X_OFFSET, Y_OFFSET,
+    map_gen::Map, player::impact_point, raws::*, settings::Settings, ui::*, utils::colors::*, Name,
+    Position, Remains, Renderable, RunState, Target, WINDOW_HEIGHT, WINDOW_WIDTH, X_OFFSET,
+    Y_OFFSET,
 };
 use bracket_lib::prelude::*;
 use specs::prelude::*;
@@ -27,6 +28,15 @@ pub fn reload_colors(ecs: &World, term: &mut BTerm, state: RunState) {
     Renderer { ecs, term, state }.reload_colors()
 }
 
+/// Checks if a map position is inside the portion of the map shown on screen, which is centered
+/// on the player.
+pub fn in_viewport(ppos: Point, pos: Point) -> bool {
+    let (cam_x, cam_y) = (WINDOW_WIDTH - X_OFFSET, WINDOW_HEIGHT + Y_OFFSET);
+    let x = pos.x - (ppos.x - cam_x / 2);
+    let y = pos.y - (ppos.y - cam_y / 2) - Y_OFFSET;
+    (0..cam_x).contains(&x) && (0..WINDOW_HEIGHT - Y_OFFSET).contains(&y)
+}
+
 impl<'a> Renderer<'a> {
     /*pub fn new(ecs: &'a World, term: &'a mut BTerm) -> Self {
         Self { ecs, term }
@@ -48,7 +58,9 @@ impl<'a> Renderer<'a> {
 
         self.clear_draw_batch(&mut draw_batch, bg, 0);
 
-        if !in_menu {
+        if !in_menu && self.state == RunState::MapOverview {
+            minimap::draw_map_scaled(self.ecs, &mut draw_batch, 0, 0, WINDOW_WIDTH, WINDOW_HEIGHT);
+        } else if !in_menu {
             self.render_map(
                 &mut draw_batch,
                 show_map,
@@ -60,6 +72,9 @@ impl<'a> Renderer<'a> {
                 y_offset,
             );
             self.render_entitites(&mut draw_batch, show_map, min_x, min_y, x_offset, y_offset);
+            if let RunState::TargetTile { cursor } = self.state {
+                self.render_tile_target(&mut draw_batch, cursor, min_x, min_y, x_offset, y_offset);
+            }
         }
 
         draw_batch.submit(0).expect("Batch error.");
@@ -131,6 +146,32 @@ impl<'a> Renderer<'a> {
         }
     }
 
+    /// Renders the line of fire of an explosive weapon aimed at a tile, up to where it lands.
+    fn render_tile_target(
+        &mut self,
+        draw_batch: &mut DrawBatch,
+        cursor: Point,
+        min_x: i32,
+        min_y: i32,
+        x_offset: i32,
+        y_offset: i32,
+    ) {
+        let ppos = *self.ecs.fetch::<Point>();
+        let impact = impact_point(self.ecs, cursor);
+        let screen = |pt: Point| Point::new(pt.x - min_x + x_offset, pt.y - min_y + y_offset);
+
+        let points = line2d_vector(ppos, impact);
+        for pt in points.iter().take(points.len() - 1).skip(1) {
+            draw_batch.set(
+                screen(*pt),
+                ColorPair::new(color("BrightCyan", 1.0), color("Background", 1.0)),
+                to_cp437('∙'),
+            );
+        }
+        draw_batch.set_bg(screen(cursor), color("Cyan", 0.5));
+        draw_batch.set_bg(screen(impact), color("BrightRed", 0.7));
+    }
+
     fn render_map(
         &mut self,
         draw_batch: &mut DrawBatch,
@@ -160,6 +201,8 @@ impl<'a> Renderer<'a> {
                     let mut tile = map.tiles[idx];
                     if !tile.visible {
                         tile.shadowed();
+                    } else {
+                        tile.lit(map.light[idx]);
                     }
                     if tile.revealed {
                         draw_batch.set(
@@ -248,10 +291,50 @@ impl<'a> Renderer<'a> {
                             // TODO
                             *write_state = RunState::Start;
                         }
+                        menu::MenuSelection::Options => {
+                            *write_state = RunState::Settings { selection: 0 };
+                        }
                         menu::MenuSelection::Quit => {
                             ::std::process::exit(0);
                         }
                     },
+                    menu::MenuResult::Help => {
+                        *write_state = RunState::Help { from_menu: true };
+                    }
+                }
+            }
+            RunState::Settings { selection } => {
+                match settings::show_settings(self.ecs, self.term, draw_batch, selection) {
+                    settings::SettingsResult::Idle { selected } => {
+                        *write_state = RunState::Settings {
+                            selection: selected,
+                        };
+                    }
+                    settings::SettingsResult::Changed { selected } => {
+                        self.apply_settings();
+                        *write_state = RunState::Settings {
+                            selection: selected,
+                        };
+                    }
+                    settings::SettingsResult::Cancel => {
+                        *write_state = RunState::Menu {
+                            menu_selection: menu::MenuSelection::Options,
+                        };
+                    }
+                }
+            }
+            RunState::MapOverview => {
+                draw_batch.print_color_centered(
+                    0,
+                    "MAP · Press ESC to return",
+                    ColorPair::new(color("BrightWhite", 1.0), color("Background", 1.0)),
+                );
+            }
+            RunState::Help { from_menu: true } => {
+                if help::show_help(self.term, draw_batch) == help::HelpResult::Cancel {
+                    *write_state = RunState::Menu {
+                        menu_selection: menu::MenuSelection::NewGame,
+                    };
                 }
             }
             _ => {
@@ -259,6 +342,7 @@ impl<'a> Renderer<'a> {
                 hud::name_stats(self.ecs, draw_batch);
                 hud::show_equipped(self.ecs, draw_batch);
                 hud::game_log(self.ecs, draw_batch);
+                minimap::minimap(self.ecs, draw_batch);
                 let mouse_pos = self.term.mouse_pos();
 
                 if mouse_pos.0 > X_OFFSET
@@ -271,9 +355,18 @@ impl<'a> Renderer<'a> {
                 }
 
                 match self.state {
-                    RunState::ChooseActionDir => {
+                    RunState::ChooseActionDir | RunState::ChooseLockDir => {
                         popup::show_context_dir(draw_batch);
                     }
+                    RunState::Look { cursor } => {
+                        look::show_look(self.ecs, draw_batch, cursor, min_x, min_y);
+                    }
+                    RunState::Help { .. } => {
+                        let help_result = help::show_help(self.term, draw_batch);
+                        if help_result == help::HelpResult::Cancel {
+                            *write_state = RunState::Waiting;
+                        }
+                    }
                     RunState::Inventory => {
                         let inventory_result =
                             inventory::show_inventory(self.ecs, self.term, draw_batch);
@@ -303,6 +396,43 @@ impl<'a> Renderer<'a> {
                             *write_state = RunState::ItemUse;
                         }
                     }
+                    RunState::Dialogue {
+                        npc,
+                        dialogue,
+                        node,
+                    } => {
+                        match dialogue::show_dialogue(
+                            self.ecs, self.term, draw_batch, npc, dialogue, node,
+                        ) {
+                            dialogue::DialogueResult::Chosen { next } => {
+                                *write_state = next;
+                            }
+                            dialogue::DialogueResult::End => {
+                                *write_state = RunState::PlayerTurn;
+                            }
+                            dialogue::DialogueResult::Idle => {}
+                        }
+                    }
+                    RunState::Trade { trader, selling } => {
+                        match trade::show_trade(self.ecs, self.term, draw_batch, trader, selling) {
+                            trade::TradeResult::Switch => {
+                                *write_state = RunState::Trade {
+                                    trader,
+                                    selling: !selling,
+                                };
+                            }
+                            trade::TradeResult::Cancel => {
+                                *write_state = RunState::PlayerTurn;
+                            }
+                            trade::TradeResult::Idle => {}
+                        }
+                    }
+                    RunState::QuestLog => {
+                        let quests_result = quests::show_quests(self.ecs, self.term, draw_batch);
+                        if quests_result == quests::QuestLogResult::Cancel {
+                            *write_state = RunState::Waiting;
+                        }
+                    }
                     RunState::AccessContainer => {
                         let container_result =
                             container::show_container(self.ecs, self.term, draw_batch);
@@ -318,6 +448,19 @@ impl<'a> Renderer<'a> {
         }
     }
 
+    /// Applies the settings that don't need a restart (colorscheme and scanlines).
+    fn apply_settings(&mut self) {
+        {
+            let settings = self.ecs.fetch::<Settings>();
+            COLORS
+                .lock()
+                .unwrap()
+                .set_curr_colorscheme(&settings.colorscheme);
+            self.term.post_scanlines = settings.scanlines;
+        }
+        self.reload_colors();
+    }
+
     pub fn reload_colors(&mut self) {
         let mut map = self.ecs.fetch_mut::<Map>();
 
@@ -36,6 +36,7 @@ embedded_resource!(WFC16, "../resources/rex/wfc_5x5.xp");
 embedded_resource!(WFC17, "../resources/rex/wfc_8x8_1.xp");
 embedded_resource!(WFC18, "../resources/rex/wfc_20x20_6.xp");
 embedded_resource!(WFC19, "../resources/rex/wfc_4x4.xp");
+embedded_resource!(VAULT01, "../resources/rex/vault_shrine_9x9.xp");
 
 pub fn load_dungeons() {
     link_resource!(LEVEL01, "resources/level01_80x60.xp");
@@ -61,4 +62,5 @@ pub fn load_dungeons() {
     link_resource!(WFC17, "resources/wfc_8x8_1.xp");
     link_resource!(WFC18, "resources/wfc_20x20_6.xp");
     link_resource!(WFC19, "resources/wfc_4x4.xp");
+    link_resource!(VAULT01, "resources/vault_shrine_9x9.xp");
 }
@@ -0,0 +1,120 @@
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/*
+ *
+ * settings.rs
+ * -----------
+ * User options saved to (and loaded from) settings.ron.
+ * Fonts, tile size and FPS cap only take effect after restarting the game.
+ *
+ */
+
+const SETTINGS_FILE: &str = "settings.ron";
+const FONTS_DIR: &str = "resources/fonts";
+
+pub const DEFAULT_FONT: &str = "Sapphos-square-16x16.png";
+pub const DEFAULT_UI_FONT: &str = "Anikki-square-16x16.png";
+pub const TILE_SIZES: [i32; 8] = [10, 12, 14, 16, 18, 20, 24, 32];
+pub const FPS_CAPS: [u32; 5] = [30, 60, 75, 120, 144];
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Settings {
+    pub font: String,
+    pub ui_font: String,
+    pub tile_size: i32,
+    pub colorscheme: String,
+    pub scanlines: bool,
+    pub fps_cap: u32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            font: DEFAULT_FONT.to_string(),
+            ui_font: DEFAULT_UI_FONT.to_string(),
+            tile_size: super::TILE_SIZE,
+            colorscheme: "vherid_dusk".to_string(),
+            scanlines: false,
+            fps_cap: 60,
+        }
+    }
+}
+
+impl Settings {
+    /// Loads the settings file, falling back to the default settings if there's none (or if it's
+    /// broken).
+    pub fn load() -> Self {
+        match std::fs::read_to_string(SETTINGS_FILE) {
+            Ok(s) => match ron::de::from_str(&s) {
+                Ok(settings) => settings,
+                Err(e) => {
+                    println!("Unable to parse {}: {}", SETTINGS_FILE, e);
+                    Settings::default()
+                }
+            },
+            Err(_) => Settings::default(),
+        }
+    }
+
+    pub fn save(&self) {
+        let pretty = ron::ser::PrettyConfig::default();
+        match ron::ser::to_string_pretty(self, pretty) {
+            Ok(s) => {
+                if let Err(e) = std::fs::write(SETTINGS_FILE, s) {
+                    println!("Unable to save {}: {}", SETTINGS_FILE, e);
+                }
+            }
+            Err(e) => println!("Unable to serialize settings: {}", e),
+        }
+    }
+
+    /// Returns the path (relative to the resources directory) and the glyph size of a font,
+    /// or of the default font if the chosen one can't be used.
+    pub fn font_resource(font: &str, default: &str) -> (String, u32, u32) {
+        // The default fonts are embedded in the binary.
+        if font == DEFAULT_FONT || font == DEFAULT_UI_FONT {
+            return (font.to_string(), 16, 16);
+        }
+        match png_dimensions(&format!("{}/{}", FONTS_DIR, font)) {
+            // Fonts are 16x16 glyph sheets.
+            Some((w, h)) if w >= 16 && h >= 16 => (format!("fonts/{}", font), w / 16, h / 16),
+            _ => {
+                println!("Unable to load font {}; using {}.", font, default);
+                (default.to_string(), 16, 16)
+            }
+        }
+    }
+}
+
+/// Lists all the fonts in resources/fonts.
+pub fn available_fonts() -> Vec<String> {
+    let mut fonts = vec![DEFAULT_FONT.to_string(), DEFAULT_UI_FONT.to_string()];
+    if let Ok(dir) = std::fs::read_dir(FONTS_DIR) {
+        for entry in dir.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.ends_with(".png") && !fonts.contains(&name) {
+                fonts.push(name);
+            }
+        }
+    }
+    fonts.sort();
+    fonts
+}
+
+/// Reads the width and height of a PNG image from its header.
+fn png_dimensions(path: &str) -> Option<(u32, u32)> {
+    if !Path::new(path).exists() {
+        return None;
+    }
+    let mut header = [0u8; 24];
+    File::open(path).ok()?.read_exact(&mut header).ok()?;
+    if &header[1..4] != b"PNG" {
+        return None;
+    }
+    let w = u32::from_be_bytes([header[16], header[17], header[18], header[19]]);
+    let h = u32::from_be_bytes([header[20], header[21], header[22], header[23]]);
+    Some((w, h))
+}
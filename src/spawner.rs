@@ -1,10 +1,11 @@
 use super::{
     common::is_weapon,
-    map_gen::{Map, MapType},
+    map_gen::{key_spots, lock_difficulty, Map, MapType},
     raws::*,
     utils::colors::*,
-    ActiveWeapon, Attack, BaseStats, Contained, Container, Description, Equipment, Fov, Health,
-    Inventory, InventoryCapacity, Mob, Name, Player, Position, Remains, Renderable,
+    ActiveWeapon, Attack, Awareness, AwarenessState, BaseStats, Contained, Container, Description,
+    Equipment, Faction, Fov, Health, Inventory, InventoryCapacity, Key, Lock, Mob, Name, Player,
+    Position, Remains, Renderable, Trader,
 };
 use bracket_lib::prelude::{to_cp437, ColorPair, Point, RandomNumberGenerator};
 use specs::prelude::*;
@@ -21,6 +22,7 @@ use std::collections::HashMap;
 // Some of this stuff is based on https://github.com/tylervipond/apprentice/blob/master/src/spawner.rs
 
 const MAX_MOBS_AREA: i32 = 6;
+const CONTAINER_LOCK_CHANCE: i32 = 3; // 1/x
 
 #[derive(Debug)]
 pub struct Spawn {
@@ -85,6 +87,9 @@ pub fn create_player(ecs: &mut World) -> Entity {
             layer: 1,
         })
         .with(Player {})
+        .with(Faction {
+            name: "Player".to_string(),
+        })
         .with(Name {
             name: "Severian".to_string(),
         })
@@ -200,14 +205,14 @@ pub fn equip_player(ecs: &mut World) {
         .expect("FAILED to insert item in inventory.");
 }
 
-fn get_all_tiered_containers(ecs: &World) -> Vec<(Entity, Vec<u8>)> {
+fn get_all_tiered_containers(ecs: &World) -> Vec<(Entity, Vec<u8>, Option<String>, u8)> {
     let entities = ecs.entities();
     let pos = ecs.read_storage::<Position>();
     let containers = ecs.read_storage::<Container>();
 
     (&pos, &entities, &containers)
         .join()
-        .map(|(_p, e, c)| (e, c.tiers.clone()))
+        .map(|(_p, e, c)| (e, c.tiers.clone(), c.loot.clone(), c.max_items))
         .collect()
 }
 
@@ -222,10 +227,23 @@ fn get_all_named_mobs(ecs: &World) -> Vec<(Entity, String)> {
         .collect()
 }
 
-fn populate_containers(ecs: &mut World, raws: &RawMaster, rng: &mut RandomNumberGenerator) {
+fn populate_containers(
+    ecs: &mut World,
+    raws: &RawMaster,
+    depth: i32,
+    rng: &mut RandomNumberGenerator,
+) {
     let containers = get_all_tiered_containers(ecs);
 
     for c in containers {
+        if let Some(table) = &c.2 {
+            let mut loot = roll_loot(table, depth, raws, rng);
+            loot.truncate(c.3 as usize);
+            for item in loot.iter() {
+                spawn_item(item, None, entity_in_container(ecs, c.0), raws);
+            }
+            continue;
+        }
         for tier in c.1 {
             let items = get_items_tier(tier, raws);
             if rng.range(0, 4) < 3 {
@@ -236,6 +254,50 @@ fn populate_containers(ecs: &mut World, raws: &RawMaster, rng: &mut RandomNumber
     }
 }
 
+/// Locks some of the containers on the map, returning the ids of the new locks.
+pub fn lock_containers(
+    ecs: &mut World,
+    depth: i32,
+    next_id: &mut u32,
+    rng: &mut RandomNumberGenerator,
+) -> Vec<u32> {
+    let containers = get_all_tiered_containers(ecs);
+    let mut locks = ecs.write_storage::<Lock>();
+    let mut ids = Vec::new();
+
+    for c in containers {
+        if rng.range(0, CONTAINER_LOCK_CHANCE) == 0 {
+            let lock = Lock {
+                id: *next_id,
+                difficulty: lock_difficulty(depth, rng),
+            };
+            locks.insert(c.0, lock).expect("Lock insertion failed");
+            ids.push(*next_id);
+            *next_id += 1;
+        }
+    }
+    ids
+}
+
+/// Leaves a key for each lock where the player can get it without opening other locks.
+pub fn spawn_keys(
+    ecs: &mut World,
+    map: &Map,
+    lock_ids: Vec<u32>,
+    raws: &RawMaster,
+    rng: &mut RandomNumberGenerator,
+) {
+    let spots = key_spots(map);
+    if spots.is_empty() {
+        return;
+    }
+    for id in lock_ids {
+        let pos = map.idx_pos(spots[rng.range(0, spots.len() as i32) as usize]);
+        let builder = ecs.create_entity().with(Key { lock_id: id });
+        spawn_item("Key", Some(pos), builder, raws);
+    }
+}
+
 fn equip_mobs(ecs: &mut World, raws: &RawMaster, rng: &mut RandomNumberGenerator) {
     let mobs = get_all_named_mobs(ecs);
 
@@ -279,6 +341,24 @@ fn equip_mobs(ecs: &mut World, raws: &RawMaster, rng: &mut RandomNumberGenerator
     }
 }
 
+fn stock_traders(ecs: &mut World, raws: &RawMaster, depth: i32, rng: &mut RandomNumberGenerator) {
+    let traders = {
+        let entities = ecs.entities();
+        let traders = ecs.read_storage::<Trader>();
+        (&entities, &traders)
+            .join()
+            .map(|(ent, trader)| (ent, trader.stock.clone()))
+            .collect::<Vec<_>>()
+    };
+
+    for (trader, stock) in traders {
+        for item in roll_loot(&stock, depth, raws, rng) {
+            let builder = ecs.create_entity().with(Inventory { owner: trader });
+            spawn_item(&item, None, builder, raws);
+        }
+    }
+}
+
 pub fn spawn_remains(ecs: &mut World, mut items: Vec<Entity>, ent_name: String, pos: Position) {
     // Check if there're already remains in this spot. If there are, then insert the content
     // of previous remains into the new remains.
@@ -313,6 +393,7 @@ pub fn spawn_remains(ecs: &mut World, mut items: Vec<Entity>, ent_name: String,
         .with(Container {
             tiers: vec![0],
             max_items: 15,
+            loot: None,
         })
         .with(Name {
             name: format!("Remains of {}", ent_name),
@@ -383,14 +464,37 @@ pub fn spawn_from_list(
         let pos = map.idx_pos(spawn.0);
         if pos != map.spawn_point {
             let name = &spawn.1;
-            spawn_entity(name, Some(pos), ecs.create_entity(), raws)
+            // A loot table (e.g. on a vault) leaves its items on the floor.
+            if raws.is_loot_table(name) {
+                for item in roll_loot(name, map.depth, raws, rng) {
+                    spawn_entity(&item, Some(pos), ecs.create_entity(), raws);
+                }
+            } else {
+                spawn_entity(name, Some(pos), ecs.create_entity(), raws)
+            }
         }
     }
 
     // Insert items in chests.
-    populate_containers(ecs, raws, rng);
+    populate_containers(ecs, raws, map.depth, rng);
     // Equip mobs with equipment.
     equip_mobs(ecs, raws, rng);
+    // Give traders something to sell.
+    stock_traders(ecs, raws, map.depth, rng);
+    // Some of them are sleeping.
+    put_mobs_to_sleep(ecs, rng);
+}
+
+/// Chance (in 1/x) of a mob being asleep when the level starts.
+const SLEEP_CHANCE: i32 = 3;
+
+fn put_mobs_to_sleep(ecs: &mut World, rng: &mut RandomNumberGenerator) {
+    let mut awareness = ecs.write_storage::<Awareness>();
+    for aware in (&mut awareness).join() {
+        if aware.state == AwarenessState::Wandering && rng.range(0, SLEEP_CHANCE) == 0 {
+            aware.state = AwarenessState::Asleep;
+        }
+    }
 }
 
 pub fn spawn_player(ecs: &mut World, map: &Map) {
@@ -1,15 +1,20 @@
 use super::{
     components::*,
+    furniture::{is_resting, rest, stop_resting},
     input::*,
     killer::remove_dead_entities,
     map_gen::*,
+    player::{auto_explore, is_auto_exploring, stop_auto_explore},
+    quests::update_quests,
     raws::*,
     renderer::{reload_colors, render_all},
+    settings::Settings,
     systems::{
         ai::HostileAISystem, consumable::ConsumableSystem, damage::DamageSystem,
-        equipment::EquipmentSystem, fov::FOVSystem, item_collect::ItemCollectSystem,
-        item_drop::ItemDropSystem, mapping::MappingSystem, melee::MeleeSystem,
-        missile::MissileSystem, weapon_reload::WeaponReloadSystem,
+        equipment::EquipmentSystem, fire::FireSystem, fov::FOVSystem,
+        item_collect::ItemCollectSystem, item_drop::ItemDropSystem, lighting::LightingSystem,
+        mapping::MappingSystem, melee::MeleeSystem, missile::MissileSystem, noise::NoiseSystem,
+        weapon_reload::WeaponReloadSystem,
     },
     ui::menu::MenuSelection,
     SHOW_MAP,
@@ -33,14 +38,42 @@ pub enum RunState {
     PlayerTurn,
     MobTurn,
     Targeting,
+    TargetTile {
+        cursor: Point,
+    },
     ChooseActionDir,
+    ChooseLockDir,
     Inventory,
     Equipment,
     ItemUse,
     AccessContainer,
     Mapgen,
-    Menu { menu_selection: MenuSelection },
+    Menu {
+        menu_selection: MenuSelection,
+    },
     NextLevel,
+    AutoExplore,
+    Resting,
+    Help {
+        from_menu: bool,
+    },
+    Settings {
+        selection: usize,
+    },
+    MapOverview,
+    Look {
+        cursor: Point,
+    },
+    Dialogue {
+        npc: Entity,
+        dialogue: usize,
+        node: usize,
+    },
+    Trade {
+        trader: Entity,
+        selling: bool,
+    },
+    QuestLog,
 }
 
 pub struct State {
@@ -63,6 +96,9 @@ impl State {
     }
 
     fn run_systems(&mut self) {
+        let mut lighting = LightingSystem {};
+        lighting.run_now(&self.ecs);
+
         let mut vis = FOVSystem {};
         vis.run_now(&self.ecs);
 
@@ -81,6 +117,12 @@ impl State {
         let mut missile = MissileSystem {};
         missile.run_now(&self.ecs);
 
+        let mut fire = FireSystem {};
+        fire.run_now(&self.ecs);
+
+        let mut noise = NoiseSystem {};
+        noise.run_now(&self.ecs);
+
         let mut damage = DamageSystem {};
         damage.run_now(&self.ecs);
 
@@ -159,6 +201,9 @@ impl State {
     pub fn set_colorscheme(&mut self, colorscheme: &str, term: &mut BTerm, runstate: RunState) {
         &COLORS.lock().unwrap().set_curr_colorscheme(colorscheme);
         reload_colors(&self.ecs, term, runstate);
+        let mut settings = self.ecs.write_resource::<Settings>();
+        settings.colorscheme = colorscheme.to_string();
+        settings.save();
     }
 }
 
@@ -174,6 +219,8 @@ impl GameState for State {
         // State machine.
         match curr_state {
             RunState::Menu { .. } => {}
+            RunState::Help { .. } => {}
+            RunState::Settings { .. } => {}
             RunState::Start => {
                 self.in_menu = false;
                 if self.show_map {
@@ -195,14 +242,51 @@ impl GameState for State {
             }
             RunState::MobTurn => {
                 self.run_systems();
-                curr_state = RunState::Waiting;
+                update_quests(&self.ecs);
+                if is_auto_exploring(&self.ecs) {
+                    curr_state = RunState::AutoExplore;
+                } else if is_resting(&self.ecs) {
+                    curr_state = RunState::Resting;
+                } else {
+                    curr_state = RunState::Waiting;
+                }
+            }
+            RunState::AutoExplore => {
+                // Any key interrupts the exploration.
+                if term.key.is_some() {
+                    curr_state = stop_auto_explore(&self.ecs, "You stop exploring.");
+                    term.key = None;
+                } else {
+                    curr_state = auto_explore(&mut self.ecs);
+                }
+            }
+            RunState::Resting => {
+                // Any key wakes the player up.
+                if term.key.is_some() {
+                    curr_state = stop_resting(&self.ecs, "You get up.");
+                    term.key = None;
+                } else {
+                    curr_state = rest(&mut self.ecs);
+                }
             }
             RunState::Targeting => {
                 curr_state = targeting_input(self, term);
             }
+            RunState::TargetTile { cursor } => {
+                curr_state = tile_targeting_input(self, term, cursor);
+            }
             RunState::ChooseActionDir => {
                 curr_state = action_dir_input(self, term);
             }
+            RunState::ChooseLockDir => {
+                curr_state = lock_dir_input(self, term);
+            }
+            RunState::MapOverview => {
+                curr_state = overview_input(term);
+            }
+            RunState::Look { cursor } => {
+                curr_state = look_input(self, term, cursor);
+            }
             RunState::Inventory => {
                 curr_state = RunState::Inventory;
                 // Will change state on rendering (messy, but sometimes we just need things to work).
@@ -213,6 +297,12 @@ impl GameState for State {
             RunState::ItemUse => {
                 curr_state = RunState::ItemUse;
             }
+            RunState::Dialogue { .. } => {}
+            RunState::Trade { .. } => {
+                // Money received for sold items.
+                self.run_collect_system();
+            }
+            RunState::QuestLog => {}
             RunState::AccessContainer => {
                 self.run_collect_system();
                 curr_state = RunState::AccessContainer;
@@ -249,26 +339,18 @@ impl GameState for State {
             }
         }
 
-        // F3 to enable/disable post-processing effects.
-        match term.key {
-            None => {}
-            Some(key) => {
-                if let VirtualKeyCode::F3 = key {
-                    term.with_post_scanlines(false);
-                }
-                if let VirtualKeyCode::F5 = key {
-                    self.set_colorscheme("wryan", term, curr_state);
-                }
-                if let VirtualKeyCode::F6 = key {
-                    self.set_colorscheme("vherid_dusk", term, curr_state);
-                }
-                if let VirtualKeyCode::F7 = key {
-                    self.set_colorscheme("spacegray_80s", term, curr_state);
-                }
-                if let VirtualKeyCode::F8 = key {
-                    self.set_colorscheme("tango_dark", term, curr_state);
-                }
+        // Actions available in any state (post-processing effects, colorschemes).
+        match get_action(term) {
+            Some(Action::ToggleScanlines) => {
+                term.post_scanlines = !term.post_scanlines;
+                let mut settings = self.ecs.write_resource::<Settings>();
+                settings.scanlines = term.post_scanlines;
+                settings.save();
+            }
+            Some(Action::Colorscheme(name)) => {
+                self.set_colorscheme(&name, term, curr_state);
             }
+            _ => {}
         }
 
         {
@@ -1,6 +1,13 @@
-use crate::components::{Fov, MeleeAttack, Mob, Position};
-use crate::map_gen::Map;
+use crate::components::{
+    Armor, Awareness, AwarenessState, Equipment, Faction, Fov, Grudge, HeardNoise, MeleeAttack,
+    Mob, Name, Position, Slowed,
+};
+use crate::log::Log;
+use crate::map_gen::{Map, TileType};
+use crate::quests::QuestLog;
+use crate::raws::{Reaction, RAWS};
 use crate::state::RunState;
+use crate::utils::colors::*;
 use bracket_lib::prelude::*;
 use specs::prelude::*;
 
@@ -8,10 +15,24 @@ use specs::prelude::*;
  *
  * ai.rs
  * -----
- * Manages the mobs' AI.
+ * Manages the mobs' AI. Mobs may be asleep, wandering around, suspicious of something they heard
+ * (see noise.rs) or hunting the player, once they notice them.
+ * Mobs go after the nearest entity their faction is hostile to (the player or other mobs) and run
+ * away from the ones they fear; the reactions between factions are defined in the raws.
  *
  */
 
+// How hard it is to notice the player, before armor, darkness and tall grass.
+const BASE_STEALTH: i32 = 8;
+const DARKNESS_STEALTH: i32 = 5;
+const TALL_GRASS_STEALTH: i32 = 5;
+// Bonus to notice the player for mobs that are already suspicious.
+const SUSPICIOUS_BONUS: i32 = 5;
+// Chance (in 1/x) of a wandering mob taking a step.
+const WANDER_CHANCE: i32 = 3;
+// Damage multiplier of attacks on mobs that didn't notice the player.
+pub const SNEAK_ATTACK_MULTIPLIER: i32 = 2;
+
 pub struct HostileAISystem {}
 
 impl<'a> System<'a> for HostileAISystem {
@@ -25,11 +46,42 @@ impl<'a> System<'a> for HostileAISystem {
         ReadExpect<'a, RunState>,
         Entities<'a>,
         WriteStorage<'a, MeleeAttack>,
+        WriteStorage<'a, HeardNoise>,
+        WriteStorage<'a, Slowed>,
+        WriteStorage<'a, Awareness>,
+        ReadStorage<'a, Armor>,
+        ReadStorage<'a, Equipment>,
+        ReadStorage<'a, Name>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        WriteExpect<'a, Log>,
+        ReadStorage<'a, Faction>,
+        ReadStorage<'a, Grudge>,
+        ReadExpect<'a, QuestLog>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mob, pt, player, mut map, mut fov, mut pos, runstate, entities, mut melee_attack) =
-            data;
+        let (
+            mob,
+            pt,
+            player,
+            mut map,
+            mut fov,
+            mut pos,
+            runstate,
+            entities,
+            mut melee_attack,
+            mut heard_noise,
+            mut slowed,
+            mut awareness,
+            armor,
+            equipment,
+            names,
+            mut rng,
+            mut log,
+            factions,
+            grudges,
+            quest_log,
+        ) = data;
         let ppos = *pt;
         let map = &mut *map;
 
@@ -37,29 +89,255 @@ impl<'a> System<'a> for HostileAISystem {
             return;
         }
 
-        for (_mob, mut fov, mut pos, ent) in (&mob, &mut fov, &mut pos, &entities).join() {
-            let d = DistanceAlg::Pythagoras.distance2d(Point::new(pos.x, pos.y), ppos);
-            if d < 1.5 {
+        let stealth = player_stealth(map, ppos, *player, &armor, &equipment);
+        let raws = &RAWS.lock().unwrap();
+        // Everyone a mob may hunt or run away from, the player included.
+        let others: Vec<(Entity, Point, String)> = (&entities, &pos, &factions)
+            .join()
+            .map(|(ent, pos, faction)| (ent, *pos, faction.name.clone()))
+            .collect();
+
+        for (_mob, fov, pos, ent, aware, faction) in (
+            &mob,
+            &mut fov,
+            &mut pos,
+            &entities,
+            &mut awareness,
+            &factions,
+        )
+            .join()
+        {
+            if aware.state == AwarenessState::Asleep {
+                continue;
+            }
+
+            // The nearest visible entities it hates and fears.
+            let mut target: Option<(Entity, Point)> = None;
+            let mut threat: Option<Point> = None;
+            let (mut target_d, mut threat_d) = (f32::MAX, f32::MAX);
+            let mut sees_player = false;
+            for (other, other_pos, other_faction) in others.iter() {
+                if *other == ent || !fov.visible_pos.contains(other_pos) {
+                    continue;
+                }
+                let reaction = if grudges
+                    .get(ent)
+                    .is_some_and(|grudge| grudge.against.contains(other))
+                {
+                    Reaction::Hostile
+                } else if *other == *player {
+                    quest_log.standing(&faction.name, raws)
+                } else {
+                    raws.faction_reaction(&faction.name, other_faction)
+                };
+                let d = DistanceAlg::Pythagoras.distance2d(*pos, *other_pos);
+
+                // The player has to be noticed before being hunted.
+                if *other == *player && reaction == Reaction::Hostile {
+                    if aware.state != AwarenessState::Hunting
+                        && notices(&mut rng, aware.state, d, stealth)
+                    {
+                        aware.state = AwarenessState::Hunting;
+                        if map.tiles[map.idx_pt(*pos)].visible {
+                            if let Some(name) = names.get(ent) {
+                                log.add(
+                                    format!("{} notices you!", name.name),
+                                    color("BrightRed", 1.0),
+                                );
+                            }
+                        }
+                    }
+                    if aware.state != AwarenessState::Hunting {
+                        continue;
+                    }
+                    sees_player = true;
+                }
+
+                if reaction == Reaction::Hostile && d < target_d {
+                    target = Some((*other, *other_pos));
+                    target_d = d;
+                } else if reaction == Reaction::Fearful && d < threat_d {
+                    threat = Some(*other_pos);
+                    threat_d = d;
+                }
+            }
+
+            if aware.state == AwarenessState::Hunting {
+                if sees_player {
+                    // Where the player was last seen.
+                    heard_noise
+                        .insert(ent, HeardNoise { pos: ppos })
+                        .expect("Noise insertion failed");
+                } else {
+                    aware.state = AwarenessState::Suspicious;
+                    if heard_noise.get(ent).is_none() {
+                        heard_noise
+                            .insert(ent, HeardNoise { pos: ppos })
+                            .expect("Noise insertion failed");
+                    }
+                }
+            }
+            // Running away comes first, unless there's something to fight even closer.
+            let threat = threat.filter(|_| threat_d <= target_d);
+
+            if threat.is_none() && target_d < 1.5 {
+                let (target, _) = target.unwrap();
                 melee_attack
-                    .insert(ent, MeleeAttack { target: *player })
+                    .insert(ent, MeleeAttack { target })
                     .expect("Melee attack insertion failed");
             }
+            // Still getting out of the water.
+            else if let Some(s) = slowed.get_mut(ent) {
+                s.turns -= 1;
+                if s.turns <= 0 {
+                    slowed.remove(ent);
+                }
+            } else if let Some(threat_pos) = threat {
+                if flee(map, pos, fov, threat_pos) {
+                    slow_down(map, pos, ent, &mut slowed);
+                }
+            }
             // https://github.com/thebracket/bracket-lib/blob/master/bracket-pathfinding/examples/astar/main.rs
-            else if fov.visible_pos.contains(&ppos) {
-                // TODO: if has missile weapon w/ ammo, first try missile attack while fleeing; else chase player.
-                let mob_location = map.idx(pos.x, pos.y);
-                let player_location = map.idx(ppos.x, ppos.y);
-                let a_star = a_star_search(mob_location, player_location, map);
-
-                if a_star.success && a_star.steps.len() > 1 {
-                    // Previous position is now unblocked.
-                    map.clear_blocker(pos.x, pos.y);
-                    pos.x = a_star.steps[1] as i32 % map.width;
-                    pos.y = a_star.steps[1] as i32 / map.width;
-                    map.add_blocker(pos.x, pos.y);
-                    fov.dirty = true;
+            else if let Some((_, target_pos)) = target {
+                // TODO: if has missile weapon w/ ammo, first try missile attack while fleeing; else chase target.
+                if step_towards(map, pos, fov, target_pos) {
+                    slow_down(map, pos, ent, &mut slowed);
                 }
             }
+            // Go check where a noise came from (or where the player was last seen).
+            else if aware.state == AwarenessState::Suspicious {
+                let noise_pos = heard_noise.get(ent).map_or(*pos, |noise| noise.pos);
+                let moved = step_towards(map, pos, fov, noise_pos);
+                if moved {
+                    slow_down(map, pos, ent, &mut slowed);
+                }
+                if !moved || DistanceAlg::Pythagoras.distance2d(*pos, noise_pos) < 1.5 {
+                    heard_noise.remove(ent);
+                    aware.state = AwarenessState::Wandering;
+                }
+            } else if rng.range(0, WANDER_CHANCE) == 0 && wander(map, pos, fov, &mut rng) {
+                slow_down(map, pos, ent, &mut slowed);
+            }
+        }
+    }
+}
+
+/// How hard it is for the mobs to notice the player: armor makes it easier, while darkness and
+/// tall grass make it harder.
+fn player_stealth(
+    map: &Map,
+    ppos: Point,
+    player: Entity,
+    armor: &ReadStorage<Armor>,
+    equipment: &ReadStorage<Equipment>,
+) -> i32 {
+    let mut stealth = BASE_STEALTH;
+    for (armor, equip) in (armor, equipment).join() {
+        if equip.user == player {
+            stealth -= armor.defense;
+        }
+    }
+    let idx = map.idx_pt(ppos);
+    if !map.is_lit(idx) {
+        stealth += DARKNESS_STEALTH;
+    }
+    if map.tiles[idx].ttype == TileType::TallGrass {
+        stealth += TALL_GRASS_STEALTH;
+    }
+    stealth
+}
+
+/// Rolls for a mob noticing the player it can see; the farther, the harder.
+fn notices(
+    rng: &mut RandomNumberGenerator,
+    state: AwarenessState,
+    dist: f32,
+    stealth: i32,
+) -> bool {
+    let bonus = if state == AwarenessState::Suspicious {
+        SUSPICIOUS_BONUS
+    } else {
+        0
+    };
+    rng.roll_dice(1, 20) + bonus >= stealth + dist as i32 / 2
+}
+
+/// Moves a mob to the neighbour tile farthest from what it fears. Returns false if cornered.
+fn flee(map: &mut Map, pos: &mut Position, fov: &mut Fov, from: Point) -> bool {
+    let curr_d = DistanceAlg::Pythagoras.distance2d(*pos, from);
+    let farthest = map
+        .get_available_exits(map.idx_pt(*pos))
+        .iter()
+        .map(|(idx, _cost)| {
+            (
+                *idx,
+                DistanceAlg::Pythagoras.distance2d(map.idx_pos(*idx), from),
+            )
+        })
+        .filter(|(_idx, d)| *d > curr_d)
+        .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+
+    match farthest {
+        Some((idx, _d)) => {
+            move_to(map, pos, fov, idx);
+            true
         }
+        None => false,
+    }
+}
+
+/// Moves a mob to a random neighbour tile. Returns false if it has nowhere to go.
+fn wander(
+    map: &mut Map,
+    pos: &mut Position,
+    fov: &mut Fov,
+    rng: &mut RandomNumberGenerator,
+) -> bool {
+    let exits = map.get_available_exits(map.idx_pt(*pos));
+    match rng.random_slice_entry(&exits) {
+        Some((idx, _cost)) => {
+            move_to(map, pos, fov, *idx);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Moves a mob one step (A*) towards a point. Returns false if there's no way there.
+fn step_towards(map: &mut Map, pos: &mut Position, fov: &mut Fov, dest: Point) -> bool {
+    let mob_location = map.idx(pos.x, pos.y);
+    let dest_location = map.idx(dest.x, dest.y);
+    // The destination may be taken by another mob (e.g. the one being hunted).
+    let dest_blocked = map.tiles[dest_location].block;
+    map.tiles[dest_location].block = false;
+    let a_star = a_star_search(mob_location, dest_location, map);
+    map.tiles[dest_location].block = dest_blocked;
+
+    if a_star.success
+        && a_star.steps.len() > 1
+        && !(dest_blocked && a_star.steps[1] == dest_location)
+    {
+        move_to(map, pos, fov, a_star.steps[1]);
+        return true;
+    }
+    false
+}
+
+fn move_to(map: &mut Map, pos: &mut Position, fov: &mut Fov, idx: usize) {
+    // Previous position is now unblocked.
+    map.clear_blocker(pos.x, pos.y);
+    pos.x = idx as i32 % map.width;
+    pos.y = idx as i32 / map.width;
+    map.add_blocker(pos.x, pos.y);
+    fov.dirty = true;
+}
+
+/// Slow terrain (e.g. water) makes a mob lose turns after walking into it.
+fn slow_down(map: &Map, pos: &Position, ent: Entity, slowed: &mut WriteStorage<Slowed>) {
+    let cost = map.move_cost(map.idx_pt(*pos));
+    if cost > 1 {
+        slowed
+            .insert(ent, Slowed { turns: cost - 1 })
+            .expect("Slowed insertion failed");
     }
 }
@@ -1,5 +1,5 @@
 //use bracket_lib::prelude::*;
-use crate::components::{BaseStats, Position, SufferDamage};
+use crate::components::{BaseStats, Position, SlainByPlayer, SufferDamage};
 use crate::map_gen::Map;
 use specs::prelude::*;
 
@@ -21,17 +21,24 @@ impl<'a> System<'a> for DamageSystem {
         WriteStorage<'a, BaseStats>,
         WriteExpect<'a, Map>,
         ReadStorage<'a, Position>,
+        WriteStorage<'a, SlainByPlayer>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut damage, entities, _player, mut stats, mut map, position) = data;
+        let (mut damage, entities, _player, mut stats, mut map, position, mut slain) = data;
 
-        for (damage, _ent, victim_stats, pos) in (&damage, &entities, &mut stats, &position).join()
-        {
+        for (damage, ent, victim_stats, pos) in (&damage, &entities, &mut stats, &position).join() {
             if !victim_stats.god {
                 for dmg in damage.amount.iter() {
                     //println!("{}", victim_stats.health.hp);
+                    let was_alive = victim_stats.health.hp > 0;
                     victim_stats.health.hp -= dmg.0;
+                    // Whoever deals the killing blow gets the credit for it.
+                    if was_alive && victim_stats.health.hp <= 0 && dmg.1 {
+                        slain
+                            .insert(ent, SlainByPlayer {})
+                            .expect("Unable to insert slain by player");
+                    }
                 }
             }
             // Victim is dead, so clear blocker.
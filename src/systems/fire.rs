@@ -0,0 +1,158 @@
+use crate::components::{BaseStats, Burning, Fov, Position, SufferDamage};
+use crate::log::Log;
+use crate::map_gen::{Map, TileType};
+use crate::state::RunState;
+use crate::utils::{colors::*, directions::dir_idx};
+use bracket_lib::prelude::*;
+use specs::prelude::*;
+
+/*
+ *
+ * fire.rs
+ * -------
+ * Burning terrain: every turn, fire spreads to flammable neighbours, sets whoever stands in it
+ * on fire and, once there's nothing left to burn, becomes ash. Water puts burning entities out.
+ * Also has the terrain effects of explosions and incendiary weapons.
+ *
+ */
+
+const FIRE_DAMAGE: i32 = 2;
+const SPREAD_CHANCE: i32 = 3; // 1/x, for each flammable neighbour.
+const BURNING_TURNS: i32 = 3; // Turns an entity keeps burning after leaving the fire.
+
+pub struct FireSystem {}
+
+impl<'a> System<'a> for FireSystem {
+    type SystemData = (
+        Entities<'a>,
+        WriteExpect<'a, Map>,
+        ReadExpect<'a, RunState>,
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        WriteExpect<'a, Log>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, BaseStats>,
+        WriteStorage<'a, SufferDamage>,
+        WriteStorage<'a, Fov>,
+        WriteStorage<'a, Burning>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            mut map,
+            runstate,
+            player,
+            mut rng,
+            mut log,
+            positions,
+            stats,
+            mut do_damage,
+            mut fov,
+            mut burning,
+        ) = data;
+        let map = &mut *map;
+
+        // Once per turn.
+        if *runstate != RunState::MobTurn {
+            return;
+        }
+
+        for (ent, pos, _stats) in (&entities, &positions, &stats).join() {
+            if let Some((_, lit_by_player)) = map.fires.get(&map.idx_pt(*pos)) {
+                // Still the player's doing if they set it on fire before.
+                let by_player = *lit_by_player || burning.get(ent).is_some_and(|b| b.by_player);
+                burning
+                    .insert(
+                        ent,
+                        Burning {
+                            turns: BURNING_TURNS,
+                            by_player,
+                        },
+                    )
+                    .expect("Burning insertion failed");
+            }
+        }
+
+        let mut extinguished: Vec<Entity> = Vec::new();
+        for (ent, pos, burn) in (&entities, &positions, &mut burning).join() {
+            if map.is_water(map.idx_pt(*pos)) {
+                extinguished.push(ent);
+                if ent == *player {
+                    log.add("The water puts out the flames.", color("BrightWhite", 1.0));
+                }
+                continue;
+            }
+            SufferDamage::add_damage(&mut do_damage, ent, FIRE_DAMAGE, burn.by_player);
+            if ent == *player {
+                log.add("You are burning!".to_string(), color("BrightRed", 1.0));
+            }
+            burn.turns -= 1;
+            if burn.turns <= 0 {
+                extinguished.push(ent);
+            }
+        }
+        for ent in extinguished {
+            burning.remove(ent);
+        }
+
+        if map.fires.is_empty() {
+            return;
+        }
+
+        let fires: Vec<(usize, (i32, bool))> = map.fires.iter().map(|(i, f)| (*i, *f)).collect();
+        for (idx, (turns, by_player)) in fires {
+            let pt = map.idx_pos(idx);
+            for i in 0..4 {
+                let neighbor = pt + dir_idx(i);
+                if map.in_map_bounds(neighbor) && rng.range(0, SPREAD_CHANCE) == 0 {
+                    let n = map.idx_pt(neighbor);
+                    map.ignite(n, by_player);
+                }
+            }
+            if turns <= 1 {
+                map.fires.remove(&idx);
+                map.change_tile(idx, TileType::Ash);
+            } else {
+                map.fires.insert(idx, (turns - 1, by_player));
+            }
+        }
+
+        // Burning trees no longer block the view.
+        for fov in (&mut fov).join() {
+            fov.dirty = true;
+        }
+    }
+}
+
+/// Sets on fire the flammable tiles around a point. Returns true if anything caught fire.
+pub fn set_fire(map: &mut Map, center: Point, radius: i32, by_player: bool) -> bool {
+    let mut burning = false;
+    for_tiles_around(map, center, radius, |map, idx| {
+        burning |= map.ignite(idx, by_player);
+    });
+    burning
+}
+
+/// Brings down the walls around a point. Returns true if any wall was destroyed.
+pub fn explosion(map: &mut Map, center: Point, radius: i32) -> bool {
+    let mut destroyed = false;
+    for_tiles_around(map, center, radius, |map, idx| {
+        destroyed |= map.destroy_wall(idx);
+    });
+    destroyed
+}
+
+fn for_tiles_around(map: &mut Map, center: Point, radius: i32, mut f: impl FnMut(&mut Map, usize)) {
+    for y in center.y - radius..=center.y + radius {
+        for x in center.x - radius..=center.x + radius {
+            let pt = Point::new(x, y);
+            if map.in_map_bounds(pt)
+                && DistanceAlg::Pythagoras.distance2d(center, pt) <= radius as f32 + 0.5
+            {
+                let idx = map.idx_pt(pt);
+                f(map, idx);
+            }
+        }
+    }
+}
@@ -13,6 +13,9 @@ use specs::prelude::*;
 
 // See: https://github.com/thebracket/bracket-lib/blob/master/rltk/examples/ex04-fov.rs
 
+// Up to where one can see in the dark.
+const DARK_VISION: f32 = 2.5;
+
 pub struct FOVSystem {}
 
 impl<'a> System<'a> for FOVSystem {
@@ -32,7 +35,12 @@ impl<'a> System<'a> for FOVSystem {
                 fov.dirty = false;
                 fov.visible_pos.clear();
                 fov.visible_pos = field_of_view(Point::new(pos.x, pos.y), fov.range, &*map);
-                fov.visible_pos.retain(|p| map.in_map_bounds(*p));
+                let origin = *pos;
+                fov.visible_pos.retain(|p| {
+                    map.in_map_bounds(*p)
+                        && (map.is_lit(map.idx_pt(*p))
+                            || DistanceAlg::Pythagoras.distance2d(origin, *p) <= DARK_VISION)
+                });
 
                 let p: Option<&Player> = player.get(e);
                 if let Some(_p) = p {
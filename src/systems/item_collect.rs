@@ -1,4 +1,6 @@
-use crate::components::{CollectItem, Contained, Inventory, InventoryCapacity, Name, Position};
+use crate::components::{
+    CollectItem, Contained, Currency, Inventory, InventoryCapacity, Name, Position,
+};
 use crate::log::Log;
 use crate::utils::colors::*;
 use specs::prelude::*;
@@ -8,6 +10,7 @@ use specs::prelude::*;
  * item_collect.rs
  * ---------------
  * Manages the acquiring of items on the map, inserting them in the player's backpack.
+ * Money is added to the pile the player already carries, taking no extra room.
  *
  */
 
@@ -15,6 +18,7 @@ pub struct ItemCollectSystem {}
 
 impl<'a> System<'a> for ItemCollectSystem {
     type SystemData = (
+        Entities<'a>,
         ReadExpect<'a, Entity>,
         ReadStorage<'a, Name>,
         WriteExpect<'a, Log>,
@@ -23,10 +27,12 @@ impl<'a> System<'a> for ItemCollectSystem {
         WriteStorage<'a, CollectItem>,
         WriteStorage<'a, Inventory>,
         WriteStorage<'a, Contained>,
+        WriteStorage<'a, Currency>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
         let (
+            entities,
             player,
             name,
             mut log,
@@ -35,6 +41,7 @@ impl<'a> System<'a> for ItemCollectSystem {
             mut collect,
             mut inventory,
             mut contained,
+            mut currency,
         ) = data;
 
         let white = color("BrightWhite", 1.0);
@@ -43,6 +50,23 @@ impl<'a> System<'a> for ItemCollectSystem {
         let mut inventory_cap = capacity.get_mut(*player).unwrap();
         for p in collect.join() {
             for c in p.collects.iter() {
+                if c.1 == *player {
+                    if let Some(amount) = currency.get(c.0).map(|money| money.amount) {
+                        let pile = (&entities, &inventory, &currency)
+                            .join()
+                            .find(|(ent, inv, _money)| inv.owner == *player && *ent != c.0)
+                            .map(|(ent, _inv, _money)| ent);
+                        if let Some(pile) = pile {
+                            currency.get_mut(pile).unwrap().amount += amount;
+                            log.add(
+                                format!("You pick up {} {}.", amount, name.get(c.0).unwrap().name),
+                                white,
+                            );
+                            entities.delete(c.0).expect("Unable to delete the money");
+                            continue;
+                        }
+                    }
+                }
                 if inventory_cap.curr == inventory_cap.max && c.1 == *player {
                     log.add(format!("Your inventory is full!"), magenta);
                     break;
@@ -0,0 +1,79 @@
+use crate::components::{Equipment, Fov, LightSource, Position};
+use crate::map_gen::Map;
+use bracket_lib::prelude::*;
+use specs::prelude::*;
+
+/*
+ *
+ * lighting.rs
+ * -----------
+ * Computes the light over each tile of the map: the ambient light of the map type plus the
+ * light emitted by torches, lamps, glowing furniture and fire. What isn't lit can only be seen
+ * from up close (see fov.rs).
+ *
+ */
+
+const FIRE_LIGHT_RADIUS: i32 = 3;
+
+pub struct LightingSystem {}
+
+impl<'a> System<'a> for LightingSystem {
+    type SystemData = (
+        WriteExpect<'a, Map>,
+        ReadStorage<'a, LightSource>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, Equipment>,
+        WriteStorage<'a, Fov>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (mut map, lights, positions, equipment, mut fov) = data;
+
+        let ambient = map.ambient_light();
+        let mut light = vec![RGB::from_f32(ambient, ambient, ambient); map.size as usize];
+
+        for (source, ent_pos, equip) in (&lights, positions.maybe(), equipment.maybe()).join() {
+            // Equipped lights go with whoever is using them.
+            let pos = match (ent_pos, equip) {
+                (Some(pos), _) => *pos,
+                (None, Some(equip)) => match positions.get(equip.user) {
+                    Some(pos) => *pos,
+                    None => continue,
+                },
+                _ => continue,
+            };
+            add_light(&map, &mut light, pos, source);
+        }
+
+        let fire = LightSource {
+            radius: FIRE_LIGHT_RADIUS,
+            color: RGB::named(ORANGE),
+        };
+        for idx in map.fires.keys() {
+            add_light(&map, &mut light, map.idx_pos(*idx), &fire);
+        }
+
+        // Whoever sees something has to look again.
+        if light != map.light {
+            map.light = light;
+            for fov in (&mut fov).join() {
+                fov.dirty = true;
+            }
+        }
+    }
+}
+
+/// Light fades with the distance to the source and doesn't go through walls.
+fn add_light(map: &Map, light: &mut [RGB], pos: Position, source: &LightSource) {
+    for pt in field_of_view(pos, source.radius, map).iter() {
+        if !map.in_map_bounds(*pt) {
+            continue;
+        }
+        let dist = DistanceAlg::Pythagoras.distance2d(pos, *pt);
+        let strength = f32::max(0.0, 1.0 - dist / (source.radius as f32 + 1.0));
+        let tile = &mut light[map.idx_pt(*pt)];
+        tile.r = f32::min(1.0, tile.r + source.color.r * strength);
+        tile.g = f32::min(1.0, tile.g + source.color.g * strength);
+        tile.b = f32::min(1.0, tile.b + source.color.b * strength);
+    }
+}
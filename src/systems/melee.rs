@@ -1,7 +1,10 @@
+use super::{ai::SNEAK_ATTACK_MULTIPLIER, noise::COMBAT_NOISE};
 use crate::components::{
-    ActiveWeapon, BaseStats, Equipment, MeleeAttack, MeleeWeapon, Name, SufferDamage,
+    ActiveWeapon, Awareness, BaseStats, EmitNoise, Equipment, Grudge, MeleeAttack, MeleeWeapon,
+    Name, Position, SufferDamage,
 };
 use crate::log::Log;
+use crate::map_gen::Map;
 use crate::utils::colors::*;
 use bracket_lib::prelude::RandomNumberGenerator;
 use specs::prelude::*;
@@ -29,6 +32,11 @@ impl<'a> System<'a> for MeleeSystem {
         WriteExpect<'a, Log>,
         WriteExpect<'a, RandomNumberGenerator>,
         ReadStorage<'a, Name>,
+        WriteStorage<'a, Awareness>,
+        WriteStorage<'a, EmitNoise>,
+        WriteStorage<'a, Grudge>,
+        ReadStorage<'a, Position>,
+        ReadExpect<'a, Map>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -44,6 +52,11 @@ impl<'a> System<'a> for MeleeSystem {
             mut log,
             mut rng,
             names,
+            mut awareness,
+            mut noises,
+            mut grudges,
+            positions,
+            map,
         ) = data;
         let white = color("BrightWhite", 1.0);
 
@@ -58,6 +71,31 @@ impl<'a> System<'a> for MeleeSystem {
             let mut has_weapon_equipped = false;
 
             if attacker_hp > 0 && victim_hp > 0 {
+                EmitNoise::add_noise(&mut noises, entity, COMBAT_NOISE);
+                if melee.target != *player {
+                    Grudge::add_grudge(&mut grudges, melee.target, entity);
+                }
+                // Fights between mobs are only told if the player sees them.
+                let seen = entity == *player
+                    || melee.target == *player
+                    || positions
+                        .get(entity)
+                        .is_some_and(|pos| map.is_visible(map.idx_pt(*pos)));
+                // Mobs that didn't notice the player take more damage.
+                let sneak = entity == *player
+                    && awareness
+                        .get_mut(melee.target)
+                        .is_some_and(|aware| aware.alert());
+                let multiplier = if sneak {
+                    log.add(
+                        format!("You sneak attack {}!", &victim_name.name),
+                        color("BrightGreen", 1.0),
+                    );
+                    SNEAK_ATTACK_MULTIPLIER
+                } else {
+                    1
+                };
+
                 for (_active_wpn, melee_wpn, equip, name_wpn) in
                     (&active_wpn, &melee_wpns, &equipment, &names).join()
                 {
@@ -67,14 +105,17 @@ impl<'a> System<'a> for MeleeSystem {
                         let total_intended_damage = rng
                             .roll_dice(wpn_stats.dice_n, wpn_stats.dice_faces)
                             + wpn_stats.dice_bonus;
-                        let damage = i32::max(0, total_intended_damage - victim_stats.defense);
-                        log.add(
-                            format!(
-                                "{} hits {} with {} for {} hp!",
-                                &name.name, &victim_name.name, &name_wpn.name, damage
-                            ),
-                            white,
-                        );
+                        let damage =
+                            i32::max(0, total_intended_damage * multiplier - victim_stats.defense);
+                        if seen {
+                            log.add(
+                                format!(
+                                    "{} hits {} with {} for {} hp!",
+                                    &name.name, &victim_name.name, &name_wpn.name, damage
+                                ),
+                                white,
+                            );
+                        }
                         SufferDamage::add_damage(
                             &mut do_damage,
                             melee.target,
@@ -88,7 +129,8 @@ impl<'a> System<'a> for MeleeSystem {
                     let attack = &attacker_stats.attack;
                     let total_intended_damage =
                         rng.roll_dice(attack.dice_n, attack.dice_faces) + attack.dice_bonus;
-                    let damage = i32::max(0, total_intended_damage - victim_stats.defense);
+                    let damage =
+                        i32::max(0, total_intended_damage * multiplier - victim_stats.defense);
                     let physical_attack_names = vec![
                         "hits",
                         "bumps into",
@@ -106,16 +148,18 @@ impl<'a> System<'a> for MeleeSystem {
                         "slaps",
                         "jumps on",
                     ];
-                    log.add(
-                        format!(
-                            "{} {} {} for {} hp!",
-                            &name.name,
-                            rng.random_slice_entry(&physical_attack_names).unwrap(),
-                            &victim_name.name,
-                            damage
-                        ),
-                        white,
-                    );
+                    if seen {
+                        log.add(
+                            format!(
+                                "{} {} {} for {} hp!",
+                                &name.name,
+                                rng.random_slice_entry(&physical_attack_names).unwrap(),
+                                &victim_name.name,
+                                damage
+                            ),
+                            white,
+                        );
+                    }
                     SufferDamage::add_damage(
                         &mut do_damage,
                         melee.target,
@@ -1,9 +1,16 @@
+use super::{
+    ai::SNEAK_ATTACK_MULTIPLIER,
+    fire::{explosion, set_fire},
+    noise::{EXPLOSION_NOISE, GUNSHOT_NOISE},
+};
 use crate::components::{
-    ActiveWeapon, BaseStats, Equipment, MissileAttack, MissileWeapon, Name, SufferDamage,
+    ActiveWeapon, Awareness, BaseStats, EmitNoise, Equipment, Explosive, Fov, Grudge, Incendiary,
+    MissileAttack, MissileTarget, MissileWeapon, Name, Position, SufferDamage,
 };
 use crate::log::Log;
+use crate::map_gen::{Map, TileType};
 use crate::utils::colors::*;
-use bracket_lib::prelude::RandomNumberGenerator;
+use bracket_lib::prelude::{DistanceAlg, Point, RandomNumberGenerator};
 use specs::prelude::*;
 
 /*
@@ -14,6 +21,9 @@ use specs::prelude::*;
  *
  */
 
+/// Extra defense of a target behind a tree.
+const TREE_COVER_DEFENSE: i32 = 2;
+
 pub struct MissileSystem {}
 
 impl<'a> System<'a> for MissileSystem {
@@ -29,6 +39,14 @@ impl<'a> System<'a> for MissileSystem {
         WriteExpect<'a, Log>,
         WriteExpect<'a, RandomNumberGenerator>,
         ReadStorage<'a, Name>,
+        WriteExpect<'a, Map>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, Incendiary>,
+        ReadStorage<'a, Explosive>,
+        WriteStorage<'a, Fov>,
+        WriteStorage<'a, Awareness>,
+        WriteStorage<'a, EmitNoise>,
+        WriteStorage<'a, Grudge>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -44,6 +62,14 @@ impl<'a> System<'a> for MissileSystem {
             mut log,
             mut rng,
             names,
+            mut map,
+            positions,
+            incendiary,
+            explosive,
+            mut fov,
+            mut awareness,
+            mut noises,
+            mut grudges,
         ) = data;
         let white = color("BrightWhite", 1.0);
 
@@ -51,34 +77,101 @@ impl<'a> System<'a> for MissileSystem {
             (&entities, &missile_attack, &base_stats, &names).join()
         {
             let attacker_hp = attacker_stats.health.hp;
-            let victim_stats = base_stats.get(missile.target).unwrap();
-            let victim_hp = victim_stats.health.hp;
+            // The mob being shot, if any, and where the missile lands.
+            let (victim, target_pos) = match missile.target {
+                MissileTarget::Mob(target) => (Some(target), *positions.get(target).unwrap()),
+                MissileTarget::Tile(pos) => (None, pos),
+            };
+            let victim_alive = match victim {
+                Some(target) => base_stats.get(target).unwrap().health.hp > 0,
+                None => true,
+            };
 
-            if attacker_hp > 0 && victim_hp > 0 {
-                for (_active_wpn, missile_wpn, equip, name_wpn) in
-                    (&active_wpn, &mut missile_wpns, &equipment, &names).join()
+            if attacker_hp > 0 && victim_alive {
+                for (wpn, _active_wpn, missile_wpn, equip, name_wpn) in (
+                    &entities,
+                    &active_wpn,
+                    &mut missile_wpns,
+                    &equipment,
+                    &names,
+                )
+                    .join()
                 {
                     if equip.user == entity && missile_wpn.ammo.ammo > 0 {
-                        let wpn_stats = &missile_wpn.stats;
-                        let total_intended_damage = rng
-                            .roll_dice(wpn_stats.dice_n, wpn_stats.dice_faces)
-                            + wpn_stats.dice_bonus;
-                        let damage = i32::max(0, total_intended_damage - victim_stats.defense);
                         missile_wpn.ammo.ammo -= 1;
-                        let victim_name = names.get(missile.target).unwrap();
-                        log.add(
-                            format!(
-                                "{} shoots {} with a {} for {} hp!",
-                                &name.name, &victim_name.name, &name_wpn.name, damage
-                            ),
-                            white,
-                        );
-                        SufferDamage::add_damage(
-                            &mut do_damage,
-                            missile.target,
-                            damage,
-                            entity == *player,
-                        );
+                        EmitNoise::add_noise(&mut noises, entity, GUNSHOT_NOISE);
+
+                        if let Some(target) = victim {
+                            let wpn_stats = &missile_wpn.stats;
+                            let total_intended_damage = rng
+                                .roll_dice(wpn_stats.dice_n, wpn_stats.dice_faces)
+                                + wpn_stats.dice_bonus;
+                            let mut defense = base_stats.get(target).unwrap().defense;
+                            let victim_name = names.get(target).unwrap();
+                            if let Some(shooter_pos) = positions.get(entity) {
+                                if tree_cover(&map, *shooter_pos, target_pos) {
+                                    defense += TREE_COVER_DEFENSE;
+                                    log.add(
+                                        format!("{} is covered by a tree.", &victim_name.name),
+                                        white,
+                                    );
+                                }
+                            }
+                            // Mobs that didn't notice the player take more damage.
+                            let sneak = entity == *player
+                                && awareness.get_mut(target).is_some_and(|aware| aware.alert());
+                            let multiplier = if sneak {
+                                log.add(
+                                    format!("You sneak attack {}!", &victim_name.name),
+                                    color("BrightGreen", 1.0),
+                                );
+                                SNEAK_ATTACK_MULTIPLIER
+                            } else {
+                                1
+                            };
+                            let damage = i32::max(0, total_intended_damage * multiplier - defense);
+                            if target != *player {
+                                Grudge::add_grudge(&mut grudges, target, entity);
+                            }
+                            log.add(
+                                format!(
+                                    "{} shoots {} with a {} for {} hp!",
+                                    &name.name, &victim_name.name, &name_wpn.name, damage
+                                ),
+                                white,
+                            );
+                            SufferDamage::add_damage(
+                                &mut do_damage,
+                                target,
+                                damage,
+                                entity == *player,
+                            );
+                        } else {
+                            log.add(
+                                format!("{} fires the {}.", &name.name, &name_wpn.name),
+                                white,
+                            );
+                        }
+
+                        // Effects on the terrain around the target.
+                        if let Some(e) = explosive.get(wpn) {
+                            // A tile can't make noise by itself, so a blast on the ground is
+                            // heard from the shooter.
+                            let source = victim.unwrap_or(entity);
+                            EmitNoise::add_noise(&mut noises, source, EXPLOSION_NOISE);
+                            if explosion(&mut map, target_pos, e.radius) {
+                                log.add("The explosion brings the walls down!", white);
+                                for fov in (&mut fov).join() {
+                                    fov.dirty = true;
+                                }
+                            }
+                            set_fire(&mut map, target_pos, e.radius, entity == *player);
+                        }
+                        if incendiary.get(wpn).is_some()
+                            && set_fire(&mut map, target_pos, 1, entity == *player)
+                        {
+                            log.add("Flames spread around the target!", white);
+                        }
                         break;
                     } else {
                         if entity == *player {
@@ -91,3 +184,21 @@ impl<'a> System<'a> for MissileSystem {
         missile_attack.clear();
     }
 }
+
+/// A target has cover if there's a tree next to it, between it and the shooter.
+fn tree_cover(map: &Map, shooter: Point, target: Point) -> bool {
+    let dist = DistanceAlg::Pythagoras.distance2d(shooter, target);
+    for y in target.y - 1..=target.y + 1 {
+        for x in target.x - 1..=target.x + 1 {
+            let pt = Point::new(x, y);
+            if pt != target
+                && map.in_map_bounds(pt)
+                && map.tiles[map.idx_pt(pt)].ttype == TileType::Tree
+                && DistanceAlg::Pythagoras.distance2d(shooter, pt) < dist
+            {
+                return true;
+            }
+        }
+    }
+    false
+}
@@ -2,10 +2,13 @@ pub mod ai;
 pub mod consumable;
 pub mod damage;
 pub mod equipment;
+pub mod fire;
 pub mod fov;
 pub mod item_collect;
 pub mod item_drop;
+pub mod lighting;
 pub mod mapping;
 pub mod melee;
 pub mod missile;
+pub mod noise;
 pub mod weapon_reload;
@@ -0,0 +1,86 @@
+use crate::components::{Awareness, AwarenessState, EmitNoise, HeardNoise, Mob, Position};
+use crate::map_gen::{Map, TileType};
+use crate::utils::directions::dir_idx;
+use specs::prelude::*;
+use std::collections::VecDeque;
+
+/*
+ *
+ * noise.rs
+ * --------
+ * Noise (gunshots, fights, doors...) travels over the map, losing volume with each tile. Walls
+ * stop it and closed doors muffle it. Mobs that hear it wake up and go check what happened.
+ *
+ */
+
+pub const DOOR_NOISE: i32 = 5;
+pub const COMBAT_NOISE: i32 = 8;
+pub const GUNSHOT_NOISE: i32 = 18;
+pub const EXPLOSION_NOISE: i32 = 30;
+
+const DOOR_MUFFLE: i32 = 4;
+
+pub struct NoiseSystem {}
+
+impl<'a> System<'a> for NoiseSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, Map>,
+        WriteStorage<'a, EmitNoise>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, Mob>,
+        WriteStorage<'a, Awareness>,
+        WriteStorage<'a, HeardNoise>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (entities, map, mut noises, positions, mobs, mut awareness, mut heard_noise) = data;
+
+        for (source, noise, source_pos) in (&entities, &noises, &positions).join() {
+            let heard = propagate(&map, *source_pos, noise.volume);
+            for (ent, _mob, pos, aware) in (&entities, &mobs, &positions, &mut awareness).join() {
+                if ent == source || heard[map.idx_pt(*pos)] < 0 {
+                    continue;
+                }
+                // Hunting mobs already know where the player is.
+                if aware.state != AwarenessState::Hunting {
+                    aware.state = AwarenessState::Suspicious;
+                    heard_noise
+                        .insert(ent, HeardNoise { pos: *source_pos })
+                        .expect("Noise insertion failed");
+                }
+            }
+        }
+        noises.clear();
+    }
+}
+
+/// Volume left of a noise on each tile; negative where it can't be heard.
+fn propagate(map: &Map, origin: Position, volume: i32) -> Vec<i32> {
+    let mut heard = vec![-1; map.size as usize];
+    let mut queue: VecDeque<Position> = VecDeque::new();
+    heard[map.idx_pt(origin)] = volume;
+    queue.push_back(origin);
+
+    while let Some(pt) = queue.pop_front() {
+        let left = heard[map.idx_pt(pt)];
+        for i in 0..8 {
+            let next = pt + dir_idx(i);
+            if !map.in_map_bounds(next) {
+                continue;
+            }
+            let idx = map.idx_pt(next);
+            let muffle = match map.tiles[idx].ttype {
+                TileType::Wall | TileType::InvisibleWall => continue,
+                TileType::ClosedDoor => DOOR_MUFFLE,
+                _ => 0,
+            };
+            let next_left = left - 1 - muffle;
+            if next_left > heard[idx] {
+                heard[idx] = next_left;
+                queue.push_back(next);
+            }
+        }
+    }
+    heard
+}
@@ -0,0 +1,193 @@
+use super::{
+    common::give_item,
+    log::Log,
+    quests::reaction_to_player,
+    raws::{Reaction, RAWS},
+    Currency, Equipment, Inventory, InventoryCapacity, Name,
+};
+use crate::utils::colors::*;
+use specs::prelude::*;
+
+/*
+ *
+ * trade.rs
+ * --------
+ * Buying from and selling to traders, paying with scrap. The prices depend on how the trader's
+ * faction regards the player.
+ *
+ */
+
+pub const CURRENCY: &str = "Scrap";
+
+/// Percentages of the price the player pays when buying and gets when selling.
+fn price_rates(standing: Reaction) -> (i32, i32) {
+    match standing {
+        Reaction::Friendly => (80, 70),
+        Reaction::Neutral => (100, 50),
+        Reaction::Fearful => (120, 40),
+        Reaction::Hostile => (150, 25),
+    }
+}
+
+pub fn buy_price(ecs: &World, trader: Entity, item: &str) -> i32 {
+    let price = RAWS.lock().unwrap().item_price(item);
+    i32::max(
+        1,
+        price * price_rates(reaction_to_player(ecs, trader)).0 / 100,
+    )
+}
+
+pub fn sell_price(ecs: &World, trader: Entity, item: &str) -> i32 {
+    let price = RAWS.lock().unwrap().item_price(item);
+    price * price_rates(reaction_to_player(ecs, trader)).1 / 100
+}
+
+/// Items someone can put up for trade, with their names: neither equipped nor money.
+pub fn tradeable_items(ecs: &World, owner: Entity) -> Vec<(Entity, String)> {
+    let entities = ecs.entities();
+    let inventory = ecs.read_storage::<Inventory>();
+    let names = ecs.read_storage::<Name>();
+    let equipment = ecs.read_storage::<Equipment>();
+    let currency = ecs.read_storage::<Currency>();
+
+    (&entities, &inventory, &names, !&equipment, !&currency)
+        .join()
+        .filter(|(_ent, inv, _name, _, _)| inv.owner == owner)
+        .map(|(ent, _inv, name, _, _)| (ent, name.name.clone()))
+        .collect()
+}
+
+/// All the money the player carries.
+pub fn wealth(ecs: &World) -> i32 {
+    let player = ecs.fetch::<Entity>();
+    let inventory = ecs.read_storage::<Inventory>();
+    let currency = ecs.read_storage::<Currency>();
+    (&inventory, &currency)
+        .join()
+        .filter(|(inv, _money)| inv.owner == *player)
+        .map(|(_inv, money)| money.amount)
+        .sum()
+}
+
+/// Takes money from the player's piles, throwing away the ones left empty.
+fn pay(ecs: &World, amount: i32) {
+    let player = ecs.fetch::<Entity>();
+    let entities = ecs.entities();
+    let mut inventory = ecs.write_storage::<Inventory>();
+    let mut currency = ecs.write_storage::<Currency>();
+    let mut capacity = ecs.write_storage::<InventoryCapacity>();
+
+    let mut left = amount;
+    let mut empty = Vec::new();
+    for (ent, inv, money) in (&entities, &inventory, &mut currency).join() {
+        if left <= 0 {
+            break;
+        }
+        if inv.owner != *player {
+            continue;
+        }
+        let paid = i32::min(left, money.amount);
+        money.amount -= paid;
+        left -= paid;
+        if money.amount <= 0 {
+            empty.push(ent);
+        }
+    }
+    for ent in empty {
+        inventory.remove(ent);
+        entities.delete(ent).expect("Unable to delete the money");
+        if let Some(cap) = capacity.get_mut(*player) {
+            cap.curr = cap.curr.saturating_sub(1);
+        }
+    }
+}
+
+/// Gives money to the player, adding it to the pile they already have.
+fn receive(ecs: &World, amount: i32) {
+    let player = *ecs.fetch::<Entity>();
+    {
+        let inventory = ecs.read_storage::<Inventory>();
+        let mut currency = ecs.write_storage::<Currency>();
+        if let Some((_inv, money)) = (&inventory, &mut currency)
+            .join()
+            .find(|(inv, _money)| inv.owner == player)
+        {
+            money.amount += amount;
+            return;
+        }
+    }
+    if let Some(pile) = give_item(ecs, CURRENCY) {
+        ecs.write_storage::<Currency>()
+            .insert(pile, Currency { amount })
+            .expect("Unable to insert money");
+    }
+}
+
+/// The player buys an item from a trader. Returns true if they could afford it.
+pub fn buy(ecs: &World, trader: Entity, item: Entity, name: &str) -> bool {
+    let player = *ecs.fetch::<Entity>();
+    let price = buy_price(ecs, trader, name);
+    let magenta = color("Magenta", 1.0);
+
+    if wealth(ecs) < price {
+        ecs.fetch_mut::<Log>()
+            .add(format!("You can't afford the {}.", name), magenta);
+        return false;
+    }
+    {
+        let mut capacity = ecs.write_storage::<InventoryCapacity>();
+        let cap = capacity.get_mut(player).unwrap();
+        if cap.curr >= cap.max {
+            ecs.fetch_mut::<Log>()
+                .add("Your inventory is full!".to_string(), magenta);
+            return false;
+        }
+        cap.curr += 1;
+    }
+
+    pay(ecs, price);
+    ecs.write_storage::<Inventory>()
+        .insert(item, Inventory { owner: player })
+        .expect("Unable to insert item in backpack");
+    ecs.fetch_mut::<Log>().add(
+        format!(
+            "You buy the {} for {} {}.",
+            name,
+            price,
+            CURRENCY.to_lowercase()
+        ),
+        color("BrightWhite", 1.0),
+    );
+    true
+}
+
+/// The player sells an item to a trader. Returns true if the trader took it.
+pub fn sell(ecs: &World, trader: Entity, item: Entity, name: &str) -> bool {
+    let player = *ecs.fetch::<Entity>();
+    let price = sell_price(ecs, trader, name);
+
+    if price <= 0 {
+        ecs.fetch_mut::<Log>().add(
+            format!("Nobody would pay for the {}.", name),
+            color("Magenta", 1.0),
+        );
+        return false;
+    }
+    if let Some(cap) = ecs.write_storage::<InventoryCapacity>().get_mut(player) {
+        cap.curr = cap.curr.saturating_sub(1);
+    }
+    ecs.write_storage::<Inventory>()
+        .insert(item, Inventory { owner: trader })
+        .expect("Unable to insert item in the trader's stock");
+    receive(ecs, price);
+    ecs.fetch_mut::<Log>().add(
+        format!(
+            "You sell the {} for {} {}.",
+            name,
+            price,
+            CURRENCY.to_lowercase()
+        ),
+        color("BrightWhite", 1.0),
+    );
+    true
+}
@@ -0,0 +1,96 @@
+use super::{
+    common::{draw_list, draw_named_box},
+    WINDOW_WIDTH, X_OFFSET,
+};
+use crate::components::Name;
+use crate::dialogue::{available_choices, choose};
+use crate::raws::DIALOGUES;
+use crate::state::RunState;
+use crate::utils::colors::*;
+use bracket_lib::prelude::*;
+use specs::prelude::*;
+
+/*
+ *
+ * dialogue.rs
+ * -----------
+ * Shows what an NPC says and the choices the player has to answer.
+ *
+ */
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum DialogueResult {
+    Chosen { next: RunState },
+    End,
+    Idle,
+}
+
+pub fn show_dialogue(
+    ecs: &World,
+    term: &mut BTerm,
+    draw_batch: &mut DrawBatch,
+    npc: Entity,
+    dialogue: usize,
+    node: usize,
+) -> DialogueResult {
+    let black = color("Background", 1.0);
+    let white = color("BrightWhite", 1.0);
+
+    let name = match ecs.read_storage::<Name>().get(npc) {
+        Some(name) => name.name.clone(),
+        None => DIALOGUES
+            .lock()
+            .unwrap()
+            .get_dialogue(dialogue)
+            .name
+            .clone(),
+    };
+    let text = DIALOGUES.lock().unwrap().get_dialogue(dialogue).nodes[node]
+        .text
+        .clone();
+    let choices = available_choices(ecs, dialogue, node);
+    let lines = text.lines().collect::<Vec<_>>();
+
+    let x1 = X_OFFSET + 5;
+    let y1 = 5;
+    let w = WINDOW_WIDTH - X_OFFSET - 10;
+    let h = (lines.len() + choices.len()) as i32 + 4;
+
+    draw_named_box(
+        &format!("·{}·", name.to_uppercase()),
+        x1,
+        y1,
+        w,
+        h,
+        draw_batch,
+    );
+    for (i, line) in lines.iter().enumerate() {
+        draw_batch.print_color(
+            Point::new(x1 + 2, y1 + 1 + i as i32),
+            line,
+            ColorPair::new(white, black),
+        );
+    }
+    draw_list(
+        choices.iter().map(|c| c.text.clone()).collect(),
+        x1,
+        y1 + lines.len() as i32 + 1,
+        draw_batch,
+    );
+
+    match term.key {
+        None => DialogueResult::Idle,
+        Some(VirtualKeyCode::Escape) => DialogueResult::End,
+        Some(key) => {
+            let select = letter_to_option(key);
+            if select >= 0 && select < choices.len() as i32 {
+                term.key = None;
+                DialogueResult::Chosen {
+                    next: choose(ecs, npc, dialogue, &choices[select as usize]),
+                }
+            } else {
+                DialogueResult::Idle
+            }
+        }
+    }
+}
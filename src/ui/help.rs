@@ -0,0 +1,62 @@
+use super::{common::draw_named_box, WINDOW_WIDTH, X_OFFSET};
+use crate::raws::KEYBINDINGS;
+use crate::utils::colors::*;
+use bracket_lib::prelude::*;
+
+/*
+ *
+ * help.rs
+ * -------
+ * Lists the current key bindings.
+ *
+ */
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum HelpResult {
+    Cancel,
+    Idle,
+}
+
+pub fn show_help(term: &mut BTerm, draw_batch: &mut DrawBatch) -> HelpResult {
+    let black = color("Background", 1.0);
+    let white = color("BrightWhite", 1.0);
+    let gray = color("BrightBlack", 1.0);
+
+    let keys = KEYBINDINGS.lock().unwrap();
+    let bindings = &keys.raws.bindings;
+
+    let x1 = X_OFFSET + 5;
+    let y1 = 5;
+    let w = WINDOW_WIDTH - X_OFFSET - 10;
+    let h = bindings.len() as i32 + 4;
+
+    draw_named_box("·HELP·", x1, y1, w, h, draw_batch);
+
+    for (i, binding) in bindings.iter().enumerate() {
+        let y = y1 + 1 + i as i32;
+        draw_batch.print_color(
+            Point::new(x1 + 2, y),
+            binding.action.get_name(),
+            ColorPair::new(white, black),
+        );
+        draw_batch.print_color(
+            Point::new(x1 + 32, y),
+            binding.keys.join(", "),
+            ColorPair::new(gray, black),
+        );
+    }
+
+    draw_batch.print_color(
+        Point::new(x1 + 2, y1 + h - 1),
+        "Press any key to return.",
+        ColorPair::new(gray, black),
+    );
+
+    match term.key {
+        None => HelpResult::Idle,
+        Some(_) => {
+            term.key = None;
+            HelpResult::Cancel
+        }
+    }
+}
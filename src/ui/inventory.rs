@@ -3,7 +3,7 @@ use super::{
     X_OFFSET, Y_OFFSET,
 };
 use crate::components::{
-    Ammunition, Consumable, ConsumeItem, DropItem, Equipable, Equipment, Inventory,
+    Ammunition, Consumable, ConsumeItem, Currency, DropItem, Equipable, Equipment, Inventory,
     InventoryCapacity, Name, SelectedItem, TryEquip, TryUnequip,
 };
 use crate::player::reload_weapon;
@@ -42,6 +42,7 @@ pub fn show_inventory(
     let backpack = ecs.read_storage::<Inventory>();
     let inventory_cap = ecs.read_storage::<InventoryCapacity>();
     let ammunition = ecs.read_storage::<Ammunition>();
+    let currency = ecs.read_storage::<Currency>();
     let entities = ecs.entities();
 
     let black = color("Background", 1.0);
@@ -66,6 +67,9 @@ pub fn show_inventory(
         if let Some(a) = ammunition.get(ent) {
             quant_to_add = a.ammo as u32;
         }
+        if let Some(money) = currency.get(ent) {
+            quant_to_add = money.amount as u32;
+        }
         *items.entry(item_name).or_insert(0) += quant_to_add;
 
         if !items_vec.contains(&name.name.to_string()) {
@@ -0,0 +1,81 @@
+use super::{
+    common::Popup,
+    tooltips::{entity_info, lock_info},
+    WINDOW_WIDTH, X_OFFSET, Y_OFFSET,
+};
+use crate::components::{Item, Position};
+use crate::map_gen::Map;
+use crate::utils::colors::*;
+use bracket_lib::prelude::*;
+use specs::prelude::*;
+
+/*
+ *
+ * look.rs
+ * -------
+ * The keyboard-based (l)ook mode: a cursor that shows what's on visible and remembered tiles.
+ *
+ */
+
+/// Returns every entity on the tile: the ones indexed in Map::entities (blockers and remains)
+/// and the items lying on it.
+fn entities_at(ecs: &World, map: &Map, pos: Point) -> Vec<Entity> {
+    let idx = map.idx_pt(pos);
+    let mut ents: Vec<Entity> = match &map.entities[idx] {
+        Some(e) => e.clone(),
+        None => Vec::new(),
+    };
+
+    let items = ecs.read_storage::<Item>();
+    let positions = ecs.read_storage::<Position>();
+    let entities = ecs.entities();
+    for (ent, _item, p) in (&entities, &items, &positions).join() {
+        if p.x == pos.x && p.y == pos.y && !ents.contains(&ent) {
+            ents.push(ent);
+        }
+    }
+    ents
+}
+
+pub fn show_look(ecs: &World, draw_batch: &mut DrawBatch, cursor: Point, min_x: i32, min_y: i32) {
+    let map = ecs.fetch::<Map>();
+    let idx = map.idx_pt(cursor);
+    let tile = map.tiles[idx];
+
+    // Highlight the cursor (same offsets used by the renderer).
+    draw_batch.set_bg(
+        Point::new(cursor.x - min_x + X_OFFSET, cursor.y - min_y - Y_OFFSET),
+        color("Cyan", 0.5),
+    );
+
+    let mut panels: Vec<Popup> = Vec::new();
+    let mut tile_info = Popup::new();
+    if !tile.revealed {
+        tile_info.add("Unknown".to_string());
+        tile_info.add("You haven't seen this place yet.".to_string());
+    } else {
+        tile_info.add(tile.ttype.get_name());
+        if let Some(lock) = map.get_lock(idx) {
+            tile_info.add(lock_info(&lock));
+        }
+        if tile.visible {
+            let ents = entities_at(ecs, &map, cursor);
+            if ents.is_empty() {
+                tile_info.add("There's nothing here.".to_string());
+            }
+            for ent in ents {
+                panels.push(entity_info(ecs, ent));
+            }
+        } else {
+            tile_info.add("You remember this place.".to_string());
+        }
+    }
+    panels.insert(0, tile_info);
+
+    // Stack the panels at the right side of the screen.
+    let mut y = 1;
+    for panel in panels.iter() {
+        panel.render_tooltip(WINDOW_WIDTH - 1 - panel.width(), y, draw_batch);
+        y += panel.height() - 1;
+    }
+}
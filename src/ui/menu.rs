@@ -1,4 +1,5 @@
 use super::{WINDOW_HEIGHT, WINDOW_WIDTH};
+use crate::raws::{get_action, Action};
 use crate::utils::colors::*;
 use bracket_lib::prelude::*;
 //use specs::prelude::*;
@@ -16,6 +17,7 @@ use bracket_lib::prelude::*;
 pub enum MenuSelection {
     NewGame,
     LoadGame,
+    Options,
     Quit,
 }
 
@@ -24,6 +26,7 @@ impl MenuSelection {
         String::from(match self {
             MenuSelection::NewGame => "New Journey",
             MenuSelection::LoadGame => "Continue",
+            MenuSelection::Options => "Options",
             MenuSelection::Quit => "Abandon",
         })
     }
@@ -45,6 +48,7 @@ impl MenuSelection {
 pub enum MenuResult {
     NoSelection { selected: MenuSelection },
     Selected { selected: MenuSelection },
+    Help,
 }
 
 pub fn main_menu(
@@ -82,10 +86,15 @@ pub fn main_menu(
         vec![
             MenuSelection::LoadGame,
             MenuSelection::NewGame,
+            MenuSelection::Options,
             MenuSelection::Quit,
         ]
     } else {
-        vec![MenuSelection::NewGame, MenuSelection::Quit]
+        vec![
+            MenuSelection::NewGame,
+            MenuSelection::Options,
+            MenuSelection::Quit,
+        ]
     };
 
     y += 15;
@@ -94,6 +103,11 @@ pub fn main_menu(
         y += 1;
     }
 
+    if get_action(term) == Some(Action::Help) {
+        term.key = None;
+        return MenuResult::Help;
+    }
+
     match term.key {
         None => MenuResult::NoSelection {
             selected: selection,
@@ -0,0 +1,123 @@
+use super::{WINDOW_HEIGHT, X_OFFSET};
+use crate::components::{Container, Position, Renderable};
+use crate::map_gen::{Map, TileType};
+use crate::utils::colors::*;
+use bracket_lib::prelude::*;
+use specs::prelude::*;
+
+/*
+ *
+ * minimap.rs
+ * ----------
+ * Scaled-down views of the explored map: the HUD minimap and the full map overview.
+ *
+ */
+
+fn ceil_div(a: i32, b: i32) -> i32 {
+    (a + b - 1) / b
+}
+
+/// Draws the revealed parts of the map scaled down to fit in a w x h area, with markers for the
+/// player, the exit and the known containers.
+/// With scale 1 (the map fits the area) the tiles are drawn as they are; otherwise each cell
+/// represents a block of tiles, drawn as floor (▒) or wall (█).
+pub fn draw_map_scaled(ecs: &World, draw_batch: &mut DrawBatch, x1: i32, y1: i32, w: i32, h: i32) {
+    let map = ecs.fetch::<Map>();
+    let ppos = ecs.fetch::<Point>();
+    let containers = ecs.read_storage::<Container>();
+    let positions = ecs.read_storage::<Position>();
+    let renderables = ecs.read_storage::<Renderable>();
+
+    let scale = ceil_div(map.width, w).max(ceil_div(map.height, h)).max(1);
+    let (cells_w, cells_h) = (ceil_div(map.width, scale), ceil_div(map.height, scale));
+    // Center the map in the area.
+    let x1 = x1 + (w - cells_w) / 2;
+    let y1 = y1 + (h - cells_h) / 2;
+    let bg = color("Background", 1.0);
+
+    for cy in 0..cells_h {
+        for cx in 0..cells_w {
+            let mut floor: Option<usize> = None;
+            let mut wall: Option<usize> = None;
+            let mut exit = false;
+            for y in cy * scale..((cy + 1) * scale).min(map.height) {
+                for x in cx * scale..((cx + 1) * scale).min(map.width) {
+                    let idx = map.idx(x, y);
+                    let tile = &map.tiles[idx];
+                    if !tile.revealed || tile.ttype == TileType::InvisibleWall {
+                        continue;
+                    }
+                    if map.is_exit(idx) {
+                        exit = true;
+                    }
+                    if map.is_wall(idx) || tile.ttype == TileType::Tree {
+                        wall = Some(idx);
+                    } else if floor.is_none() {
+                        floor = Some(idx);
+                    }
+                }
+            }
+
+            let pt = Point::new(x1 + cx, y1 + cy);
+            if exit {
+                draw_batch.set(
+                    pt,
+                    ColorPair::new(color("BrightMagenta", 1.0), bg),
+                    to_cp437('>'),
+                );
+            } else if scale == 1 {
+                if let Some(idx) = floor.or(wall) {
+                    let mut tile = map.tiles[idx];
+                    if !tile.visible {
+                        tile.shadowed();
+                    }
+                    draw_batch.set(pt, tile.color, tile.glyph);
+                }
+            } else if let Some(idx) = floor {
+                draw_batch.set(
+                    pt,
+                    ColorPair::new(map.tiles[idx].color.fg, bg),
+                    to_cp437('▒'),
+                );
+            } else if wall.is_some() {
+                draw_batch.set(
+                    pt,
+                    ColorPair::new(color("BrightBlack", 1.0), bg),
+                    to_cp437('█'),
+                );
+            }
+        }
+    }
+
+    for (_container, pos, render) in (&containers, &positions, &renderables).join() {
+        if map.tiles[map.idx(pos.x, pos.y)].revealed {
+            draw_batch.set(
+                Point::new(x1 + pos.x / scale, y1 + pos.y / scale),
+                ColorPair::new(render.color.fg, bg),
+                render.glyph,
+            );
+        }
+    }
+
+    draw_batch.set(
+        Point::new(x1 + ppos.x / scale, y1 + ppos.y / scale),
+        ColorPair::new(color("BrightWhite", 1.0), bg),
+        to_cp437('@'),
+    );
+}
+
+/// Renders the minimap at the bottom of the left HUD panel.
+pub fn minimap(ecs: &World, draw_batch: &mut DrawBatch) {
+    let black = color("Background", 1.0);
+    let gray = color("BrightBlack", 1.0);
+    let y = 38;
+    draw_batch.print_color(Point::new(0, y), "╞═ MAP", ColorPair::new(gray, black));
+    draw_map_scaled(
+        ecs,
+        draw_batch,
+        1,
+        y + 1,
+        X_OFFSET - 1,
+        WINDOW_HEIGHT - y - 2,
+    );
+}
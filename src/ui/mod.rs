@@ -2,9 +2,16 @@ use super::{log::Log, RunState, WINDOW_HEIGHT, WINDOW_WIDTH, X_OFFSET, Y_OFFSET}
 
 mod common;
 pub mod container;
+pub mod dialogue;
 pub mod equipment;
+pub mod help;
 pub mod hud;
 pub mod inventory;
+pub mod look;
 pub mod menu;
+pub mod minimap;
 pub mod popup;
+pub mod quests;
+pub mod settings;
 pub mod tooltips;
+pub mod trade;
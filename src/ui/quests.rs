@@ -0,0 +1,100 @@
+use super::{common::draw_named_box, WINDOW_WIDTH, X_OFFSET};
+use crate::common::has_item;
+use crate::map_gen::Map;
+use crate::quests::{QuestLog, QuestStatus};
+use crate::raws::{Objective, DIALOGUES};
+use crate::utils::colors::*;
+use bracket_lib::prelude::*;
+use specs::prelude::*;
+
+/*
+ *
+ * quests.rs
+ * ---------
+ * Lists the quests the player took, with their progress.
+ *
+ */
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum QuestLogResult {
+    Cancel,
+    Idle,
+}
+
+pub fn show_quests(ecs: &World, term: &mut BTerm, draw_batch: &mut DrawBatch) -> QuestLogResult {
+    let black = color("Background", 1.0);
+    let white = color("BrightWhite", 1.0);
+    let gray = color("BrightBlack", 1.0);
+
+    let quests = ecs.fetch::<QuestLog>().quests.clone();
+    let depth = ecs.fetch::<Map>().depth;
+
+    let x1 = X_OFFSET + 5;
+    let y1 = 5;
+    let w = WINDOW_WIDTH - X_OFFSET - 10;
+    let h = i32::max(1, quests.len() as i32 * 3) + 4;
+
+    draw_named_box("·QUESTS·", x1, y1, w, h, draw_batch);
+
+    if quests.is_empty() {
+        draw_batch.print_color(
+            Point::new(x1 + 2, y1 + 1),
+            "You have no quests.",
+            ColorPair::new(gray, black),
+        );
+    }
+
+    let dialogues = DIALOGUES.lock().unwrap();
+    for (i, progress) in quests.iter().enumerate() {
+        let quest = match dialogues.get_quest(&progress.name) {
+            Some(quest) => quest,
+            None => continue,
+        };
+        let y = y1 + 1 + i as i32 * 3;
+        let done = progress.status == QuestStatus::Done;
+        let status = if done {
+            "Done".to_string()
+        } else {
+            match &quest.objective {
+                Objective::Kill { mob, count } => {
+                    format!("{}: {}/{}", mob, i32::min(progress.kills, *count), count)
+                }
+                Objective::Fetch { item } if has_item(ecs, item) => format!("{}: found", item),
+                Objective::Fetch { item } => format!("{}: not found", item),
+                Objective::Reach { depth: target } => {
+                    format!("Depth: {}/{}", i32::min(depth, *target), target)
+                }
+            }
+        };
+
+        draw_batch.print_color(
+            Point::new(x1 + 2, y),
+            &quest.name,
+            ColorPair::new(if done { gray } else { white }, black),
+        );
+        draw_batch.print_color(
+            Point::new(x1 + w - 2 - status.len() as i32, y),
+            &status,
+            ColorPair::new(gray, black),
+        );
+        draw_batch.print_color(
+            Point::new(x1 + 4, y + 1),
+            &quest.descr,
+            ColorPair::new(gray, black),
+        );
+    }
+
+    draw_batch.print_color(
+        Point::new(x1 + 2, y1 + h - 1),
+        "Press any key to return.",
+        ColorPair::new(gray, black),
+    );
+
+    match term.key {
+        None => QuestLogResult::Idle,
+        Some(_) => {
+            term.key = None;
+            QuestLogResult::Cancel
+        }
+    }
+}
@@ -0,0 +1,172 @@
+use super::{WINDOW_HEIGHT, WINDOW_WIDTH};
+use crate::raws::{get_action, Action, COLORS};
+use crate::settings::{available_fonts, Settings, FPS_CAPS, TILE_SIZES};
+use crate::utils::colors::*;
+use bracket_lib::prelude::*;
+use specs::prelude::*;
+
+/*
+ *
+ * settings.rs
+ * -----------
+ * The options screen, reachable from the main menu.
+ *
+ */
+
+const ENTRIES: usize = 6;
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum SettingsResult {
+    Idle { selected: usize },
+    Changed { selected: usize },
+    Cancel,
+}
+
+/// Returns the element that comes `step` positions after `curr` in the list (wrapping around).
+fn cycle<T: PartialEq + Clone>(list: &[T], curr: &T, step: i32) -> T {
+    let idx = list.iter().position(|x| x == curr).unwrap_or(0) as i32;
+    let len = list.len() as i32;
+    list[((idx + step) % len + len) as usize % len as usize].clone()
+}
+
+fn print_entry(y: i32, label: &str, value: String, selected: bool, draw_batch: &mut DrawBatch) {
+    let bg = color("Background", 1.0);
+    let fg = if selected {
+        color("Cyan", 0.8)
+    } else {
+        color("White", 1.0)
+    };
+    draw_batch.print_color(Point::new(20, y), label, ColorPair::new(fg, bg));
+    draw_batch.print_color(
+        Point::new(40, y),
+        format!("< {} >", value),
+        ColorPair::new(fg, bg),
+    );
+}
+
+pub fn show_settings(
+    ecs: &World,
+    term: &mut BTerm,
+    draw_batch: &mut DrawBatch,
+    selection: usize,
+) -> SettingsResult {
+    let mut settings = ecs.write_resource::<Settings>();
+    let gray = color("BrightBlack", 1.0);
+    let bg = color("Background", 1.0);
+
+    draw_batch.draw_hollow_box(
+        Rect::with_size(0, 0, WINDOW_WIDTH - 1, WINDOW_HEIGHT - 1),
+        ColorPair::new(gray, bg),
+    );
+    draw_batch.print_color_centered(10, "OPTIONS", ColorPair::new(color("Green", 1.0), bg));
+
+    let on_off = |b: bool| if b { "On" } else { "Off" }.to_string();
+    let y = 20;
+    print_entry(
+        y,
+        "Map font",
+        settings.font.clone(),
+        selection == 0,
+        draw_batch,
+    );
+    print_entry(
+        y + 1,
+        "UI font",
+        settings.ui_font.clone(),
+        selection == 1,
+        draw_batch,
+    );
+    print_entry(
+        y + 2,
+        "Tile size",
+        format!("{}px", settings.tile_size),
+        selection == 2,
+        draw_batch,
+    );
+    print_entry(
+        y + 3,
+        "Colorscheme",
+        settings.colorscheme.clone(),
+        selection == 3,
+        draw_batch,
+    );
+    print_entry(
+        y + 4,
+        "Scanlines",
+        on_off(settings.scanlines),
+        selection == 4,
+        draw_batch,
+    );
+    print_entry(
+        y + 5,
+        "FPS cap",
+        settings.fps_cap.to_string(),
+        selection == 5,
+        draw_batch,
+    );
+
+    draw_batch.print_color_centered(
+        WINDOW_HEIGHT - 8,
+        "Fonts, tile size and FPS cap are applied after restarting.",
+        ColorPair::new(gray, bg),
+    );
+    draw_batch.print_color_centered(
+        WINDOW_HEIGHT - 6,
+        "Press ESC to save and return.",
+        ColorPair::new(gray, bg),
+    );
+
+    let step = match get_action(term) {
+        None => {
+            return SettingsResult::Idle {
+                selected: selection,
+            }
+        }
+        Some(action) => match action {
+            Action::Cancel => {
+                settings.save();
+                return SettingsResult::Cancel;
+            }
+            Action::MoveNorth => {
+                return SettingsResult::Idle {
+                    selected: (selection + ENTRIES - 1) % ENTRIES,
+                }
+            }
+            Action::MoveSouth => {
+                return SettingsResult::Idle {
+                    selected: (selection + 1) % ENTRIES,
+                }
+            }
+            Action::MoveWest => -1,
+            Action::MoveEast => 1,
+            _ => {
+                return SettingsResult::Idle {
+                    selected: selection,
+                }
+            }
+        },
+    };
+
+    match selection {
+        0 => settings.font = cycle(&available_fonts(), &settings.font, step),
+        1 => settings.ui_font = cycle(&available_fonts(), &settings.ui_font, step),
+        2 => settings.tile_size = cycle(&TILE_SIZES, &settings.tile_size, step),
+        3 => {
+            let colorschemes: Vec<String> = COLORS
+                .lock()
+                .unwrap()
+                .raws
+                .colorschemes
+                .iter()
+                .map(|c| c.name.clone())
+                .collect();
+            settings.colorscheme = cycle(&colorschemes, &settings.colorscheme, step);
+        }
+        4 => settings.scanlines = !settings.scanlines,
+        _ => settings.fps_cap = cycle(&FPS_CAPS, &settings.fps_cap, step),
+    }
+
+    SettingsResult::Changed {
+        selected: selection,
+    }
+}
@@ -1,6 +1,7 @@
 use super::{common::Popup, WINDOW_HEIGHT, WINDOW_WIDTH, X_OFFSET, Y_OFFSET};
 use crate::components::{
-    Armor, BaseStats, Description, Item, MeleeWeapon, MissileWeapon, Name, Position,
+    Armor, Awareness, BaseStats, Description, Item, Key, Lock, MeleeWeapon, MissileWeapon, Mob,
+    Name, Position,
 };
 use crate::map_gen::Map;
 use bracket_lib::prelude::*;
@@ -11,10 +12,72 @@ use specs::prelude::*;
  * tooltips.rs
  * -----------
  * Check information of entities on the game by hovering the mouse over them.
- * Easier than making a keyboard-based (l)ook system, as bracket-lib has mouse support.
+ * The same information is shown by the keyboard-based (l)ook system (see look.rs).
  *
  */
 
+/// Builds a popup with everything the player can know about an entity.
+pub fn entity_info(ecs: &World, ent: Entity) -> Popup {
+    let names = ecs.read_storage::<Name>();
+    let descriptions = ecs.read_storage::<Description>();
+    let mobs = ecs.read_storage::<Mob>();
+    let stats = ecs.read_storage::<BaseStats>();
+    let melee = ecs.read_storage::<MeleeWeapon>();
+    let missile = ecs.read_storage::<MissileWeapon>();
+    let armor = ecs.read_storage::<Armor>();
+    let item = ecs.read_storage::<Item>();
+    let locks = ecs.read_storage::<Lock>();
+    let keys = ecs.read_storage::<Key>();
+    let awareness = ecs.read_storage::<Awareness>();
+
+    let mut ttip = Popup::new();
+    if let Some(name) = names.get(ent) {
+        ttip.add(name.name.to_string());
+    }
+    if let Some(descr) = descriptions.get(ent) {
+        ttip.add(descr.descr.to_string());
+    }
+    if let Some(m) = mobs.get(ent) {
+        ttip.add(format!("\n{:?}", m.mob_type));
+    }
+    if let Some(a) = awareness.get(ent) {
+        ttip.add(format!("\n{:?}", a.state));
+    }
+    if let Some(s) = stats.get(ent) {
+        ttip.add(format!(
+            "\nHP: {}/{}\nDEF: {}\nATK: {}",
+            s.health.hp, s.health.max_hp, s.defense, s.attack.base_damage
+        ));
+    }
+    if let Some(m) = melee.get(ent) {
+        ttip.add(format!("\n{:?}\n\nDMG: {}", m.class, m.stats.base_damage));
+    }
+    if let Some(m) = missile.get(ent) {
+        ttip.add(format!(
+            "\n{:?}\n\nDMG: {}\nRange: {}\nAmmo: {}/{}",
+            m.class, m.stats.base_damage, m.stats.range, m.ammo.ammo, m.ammo.max_ammo
+        ));
+    }
+    if let Some(a) = armor.get(ent) {
+        ttip.add(format!("\nDEF: {}", a.defense));
+    }
+    if let Some(t) = item.get(ent) {
+        ttip.add(format!("\nTier: {}", t.tier));
+    }
+    if let Some(l) = locks.get(ent) {
+        ttip.add(lock_info(l));
+    }
+    if let Some(k) = keys.get(ent) {
+        ttip.add(format!("\nOpens lock #{}", k.lock_id));
+    }
+    ttip
+}
+
+/// The line shown for locked containers and doors.
+pub fn lock_info(lock: &Lock) -> String {
+    format!("\nLocked (#{}, difficulty {})", lock.id, lock.difficulty)
+}
+
 pub fn show_tooltip(
     ecs: &World,
     term: &mut BTerm,
@@ -28,39 +91,27 @@ pub fn show_tooltip(
     mouse_pos.0 += min_x - X_OFFSET;
     mouse_pos.1 += min_y + Y_OFFSET;
 
-    let names = ecs.read_storage::<Name>();
-    let descriptions = ecs.read_storage::<Description>();
     let positions = ecs.read_storage::<Position>();
-    let stats = ecs.read_storage::<BaseStats>();
-    let melee = ecs.read_storage::<MeleeWeapon>();
-    let missile = ecs.read_storage::<MissileWeapon>();
-    let armor = ecs.read_storage::<Armor>();
-    let item = ecs.read_storage::<Item>();
+    let descriptions = ecs.read_storage::<Description>();
     let entities = ecs.entities();
 
     let mut tooltips: Vec<Popup> = Vec::new();
-    for (ent, name, descr, pos) in (&entities, &names, &descriptions, &positions).join() {
+    for (ent, _descr, pos) in (&entities, &descriptions, &positions).join() {
         let idx = map.idx(pos.x, pos.y);
         if mouse_pos.0 == pos.x && mouse_pos.1 == pos.y && map.is_visible(idx) {
-            let mut ttip = Popup::new();
-            ttip.add(name.name.to_string());
-            ttip.add(descr.descr.to_string());
-            if let Some(s) = stats.get(ent) {
-                ttip.add(format!("\nHP: {}", s.health.hp));
-            }
-            if let Some(m) = melee.get(ent) {
-                ttip.add(format!("\n{:?}\n\nDMG: {}", m.class, m.stats.base_damage));
-            }
-            if let Some(m) = missile.get(ent) {
-                ttip.add(format!("\n{:?}\n\nDMG: {}", m.class, m.stats.base_damage));
-            }
-            if let Some(a) = armor.get(ent) {
-                ttip.add(format!("\nDEF: {}", a.defense));
-            }
-            if let Some(t) = item.get(ent) {
-                ttip.add(format!("\nTier: {}", t.tier));
+            tooltips.push(entity_info(ecs, ent));
+        }
+    }
+    // Locked doors.
+    if map.in_map_bounds_xy(mouse_pos.0, mouse_pos.1) {
+        let idx = map.idx(mouse_pos.0, mouse_pos.1);
+        if let Some(lock) = map.get_lock(idx) {
+            if map.is_visible(idx) {
+                let mut ttip = Popup::new();
+                ttip.add(map.tiles[idx].ttype.get_name());
+                ttip.add(lock_info(&lock));
+                tooltips.push(ttip);
             }
-            tooltips.push(ttip);
         }
     }
     if tooltips.is_empty() {
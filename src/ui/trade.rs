@@ -0,0 +1,133 @@
+use super::{
+    common::{draw_list_items, draw_named_box},
+    WINDOW_HEIGHT, WINDOW_WIDTH, X_OFFSET, Y_OFFSET,
+};
+use crate::components::Name;
+use crate::trade::{buy, buy_price, sell, sell_price, tradeable_items, wealth, CURRENCY};
+use crate::utils::colors::*;
+use bracket_lib::prelude::*;
+use specs::prelude::*;
+use std::collections::HashMap;
+
+/*
+ *
+ * trade.rs
+ * --------
+ * UI for buying from and selling to a trader. Lists either the trader's stock or the player's
+ * items, with their prices.
+ *
+ */
+
+const X: i32 = WINDOW_WIDTH;
+const Y: i32 = WINDOW_HEIGHT;
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum TradeResult {
+    Switch, // Between buying and selling.
+    Cancel,
+    Idle,
+}
+
+pub fn show_trade(
+    ecs: &World,
+    term: &mut BTerm,
+    draw_batch: &mut DrawBatch,
+    trader: Entity,
+    selling: bool,
+) -> TradeResult {
+    let black = color("Background", 1.0);
+    let white = color("BrightWhite", 1.0);
+    let gray = color("BrightBlack", 1.0);
+
+    let player = *ecs.fetch::<Entity>();
+    let trader_name = match ecs.read_storage::<Name>().get(trader) {
+        Some(name) => name.name.clone(),
+        None => "Trader".to_string(),
+    };
+    let owner = if selling { player } else { trader };
+
+    // Same items are listed once, with their price.
+    let mut items: HashMap<String, u32> = HashMap::new();
+    let mut items_vec: Vec<String> = Vec::new();
+    let mut items_ent: HashMap<String, (Entity, String)> = HashMap::new();
+    for (ent, name) in tradeable_items(ecs, owner) {
+        let price = if selling {
+            sell_price(ecs, trader, &name)
+        } else {
+            buy_price(ecs, trader, &name)
+        };
+        let label = format!("{} ({} {})", name, price, CURRENCY.to_lowercase());
+        *items.entry(label.clone()).or_insert(0) += 1;
+        if !items_vec.contains(&label) {
+            items_vec.push(label.clone());
+            items_ent.insert(label, (ent, name));
+        }
+    }
+    items_vec.sort();
+
+    let x1 = X_OFFSET + 5;
+    let y1 = 4;
+    let w = X - X_OFFSET - 10;
+    let h = Y - Y_OFFSET - 9;
+
+    draw_named_box(
+        &format!("·{}·", trader_name.to_uppercase()),
+        x1,
+        y1,
+        w,
+        h,
+        draw_batch,
+    );
+    draw_batch.print_color(
+        Point::new(x1 + 2, y1 + 1),
+        if selling { "SELLING" } else { "BUYING" },
+        ColorPair::new(white, black),
+    );
+    let money = format!("Your {}: {}", CURRENCY.to_lowercase(), wealth(ecs));
+    draw_batch.print_color(
+        Point::new(x1 + w - 1 - money.len() as i32, y1 + 1),
+        &money,
+        ColorPair::new(white, black),
+    );
+
+    if items_vec.is_empty() {
+        draw_batch.print_color(
+            Point::new(x1 + 2, y1 + 3),
+            if selling {
+                "You have nothing to sell."
+            } else {
+                "Nothing left to buy."
+            },
+            ColorPair::new(gray, black),
+        );
+    }
+    draw_list_items(&items, &items_vec, x1, y1 + 2, w, draw_batch);
+
+    draw_batch.print_color(
+        Point::new(x1 + 2, y1 + h - 1),
+        if selling {
+            "TAB to buy · ESC to leave"
+        } else {
+            "TAB to sell · ESC to leave"
+        },
+        ColorPair::new(gray, black),
+    );
+
+    match term.key {
+        None => TradeResult::Idle,
+        Some(VirtualKeyCode::Escape) => TradeResult::Cancel,
+        Some(VirtualKeyCode::Tab) => TradeResult::Switch,
+        Some(key) => {
+            let select = letter_to_option(key);
+            if select >= 0 && select < items_vec.len() as i32 {
+                let (item, name) = &items_ent[&items_vec[select as usize]];
+                if selling {
+                    sell(ecs, trader, *item, name);
+                } else {
+                    buy(ecs, trader, *item, name);
+                }
+            }
+            TradeResult::Idle
+        }
+    }
+}